@@ -5,6 +5,19 @@
 
 use core::mem::{self, MaybeUninit};
 
+/// Memory usage of the executor's bump allocator
+#[derive(Clone, Copy, Debug)]
+pub struct AllocStats {
+    /// Bytes that have been allocated, including alignment padding
+    pub used: usize,
+
+    /// Bytes that are still available
+    pub free: usize,
+
+    /// Size of the memory managed by the allocator, in bytes
+    pub total: usize,
+}
+
 pub struct Alloc {
     len: usize,
     pos: usize,
@@ -24,7 +37,7 @@ impl Alloc {
         let size = mem::size_of::<T>();
         let align = mem::align_of::<T>();
         let new_pos = round_up(self.pos, align);
-        if new_pos + size < self.len {
+        if new_pos + size <= self.len {
             self.pos = new_pos + size;
             unsafe { &mut *(self.start.add(new_pos) as *mut MaybeUninit<T>) }
         } else {
@@ -33,6 +46,14 @@ impl Alloc {
         }
     }
 
+    pub(crate) fn stats(&self) -> AllocStats {
+        AllocStats {
+            used: self.pos,
+            free: self.len - self.pos,
+            total: self.len,
+        }
+    }
+
     /// Effectively stores `val` in static memory and returns a reference to it
     pub(crate) fn alloc_init<T>(&mut self, val: T) -> &'static mut T {
         let slot = self.alloc::<T>();
@@ -10,7 +10,13 @@ use core::{
 use heapless::Vec;
 use pin_utils::pin_mut;
 
-use crate::{alloc::Alloc, NTASKS};
+#[cfg(feature = "poll-watchdog")]
+use crate::task::Overrun;
+use crate::{
+    alloc::{Alloc, AllocStats},
+    task::{JoinHandle, JoinState},
+    NTASKS,
+};
 
 /// A single-threaded executor that only works in ARM Cortex-M "Thread mode"
 /// (outside of interrupt context)
@@ -20,6 +26,12 @@ pub struct Executor {
     in_block_on: Cell<bool>,
     // NOTE `UnsafeCell` is used to minimize the span of references to the `Vec`
     tasks: UnsafeCell<Vec<&'static Task, NTASKS>>,
+    idle_hook: Cell<Option<fn()>>,
+    #[cfg(feature = "poll-watchdog")]
+    watchdog: Cell<Option<(u32, fn(Overrun))>>,
+    // number of scans of the task list
+    #[cfg(feature = "scheduler-tick")]
+    tick: Cell<u32>,
 }
 
 // NOTE `*const ()` is &AtomicBool
@@ -46,10 +58,72 @@ impl Executor {
         Self {
             in_block_on: Cell::new(false),
             tasks: UnsafeCell::new(Vec::new()),
+            idle_hook: Cell::new(None),
+            #[cfg(feature = "poll-watchdog")]
+            watchdog: Cell::new(None),
+            #[cfg(feature = "scheduler-tick")]
+            tick: Cell::new(0),
         }
     }
 
+    pub fn allocator_stats(&self) -> AllocStats {
+        // NOTE(unsafe) the allocator is initialized together with the executor and only
+        // `spawn` (which can't run concurrently with this method) modifies it
+        unsafe { (*(ALLOC.get() as *const Alloc)).stats() }
+    }
+
+    pub fn set_idle_hook(&self, hook: fn()) {
+        self.idle_hook.set(Some(hook));
+    }
+
+    #[cfg(feature = "scheduler-tick")]
+    pub fn current_tick(&self) -> u32 {
+        self.tick.get()
+    }
+
+    #[cfg(feature = "poll-watchdog")]
+    pub fn set_watchdog(&self, threshold: u32, hook: fn(Overrun)) {
+        self.watchdog.set(Some((threshold, hook)));
+    }
+
+    // Runs `poll` and reports an overrun if it took longer than the watchdog threshold
+    #[cfg(feature = "poll-watchdog")]
+    #[inline(always)]
+    fn watch<T>(&self, task: Option<usize>, poll: impl FnOnce() -> T) -> T {
+        if let Some((threshold, hook)) = self.watchdog.get() {
+            let start = crate::cycle_count();
+            let res = poll();
+            let cycles = crate::cycle_count().wrapping_sub(start);
+            if cycles > threshold {
+                hook(Overrun { task, cycles });
+            }
+            res
+        } else {
+            poll()
+        }
+    }
+
+    #[cfg(not(feature = "poll-watchdog"))]
+    #[inline(always)]
+    fn watch<T>(&self, _task: Option<usize>, poll: impl FnOnce() -> T) -> T {
+        poll()
+    }
+
     pub fn block_on<T>(&self, f: impl Future<Output = T>) -> T {
+        match self.run(f, false) {
+            Some(val) => val,
+            // NOTE `run` only gives up when `until_stalled` is set
+            None => unreachable!(),
+        }
+    }
+
+    pub fn run_until_stalled<T>(&self, f: impl Future<Output = T>) -> Option<T> {
+        self.run(f, true)
+    }
+
+    // Drives `f` and the `spawn`-ed tasks until `f` completes; if `until_stalled` is set, returns
+    // `None` instead of sleeping when none of them can make progress
+    fn run<T>(&self, f: impl Future<Output = T>, until_stalled: bool) -> Option<T> {
         // we want to avoid reentering `block_on` because then all the code
         // below has to become more complex. It's also likely that the
         // application will only call `block_on` once on an infinite task
@@ -64,17 +138,22 @@ impl Executor {
         let ready = AtomicBool::new(true);
         let waker =
             unsafe { Waker::from_raw(RawWaker::new(&ready as *const _ as *const _, &VTABLE)) };
-        let val = loop {
+        // whether the current scan started over because of `reschedule`
+        let mut restarted = false;
+        let val = 'scan: loop {
             let mut task_woken = false;
 
+            #[cfg(feature = "scheduler-tick")]
+            self.tick.set(self.tick.get().wrapping_add(1));
+
             // advance the main task
             if ready.load(Ordering::Acquire) {
                 task_woken = true;
                 ready.store(false, Ordering::Release);
 
                 let mut cx = Context::from_waker(&waker);
-                if let Poll::Ready(val) = f.as_mut().poll(&mut cx) {
-                    break val;
+                if let Poll::Ready(val) = self.watch(None, || f.as_mut().poll(&mut cx)) {
+                    break Some(val);
                 }
             }
 
@@ -83,6 +162,18 @@ impl Executor {
             // allocated `heapless::Vec<T>`); `tasks` can't shrink either
             let len = unsafe { (*self.tasks.get()).len() }; // (A)
             for i in 0..len {
+                // NOTE `reschedule` requested; start over from the main task, but only if it's
+                // ready -- the hint is of no use otherwise -- and only if this scan didn't start
+                // over already. A scan that started over runs to completion so a task that keeps
+                // waking up the main task can't starve the tasks that follow it: every task is
+                // polled at least once every two scans
+                if !restarted && RESCHEDULE.load(Ordering::Acquire) && ready.load(Ordering::Acquire)
+                {
+                    RESCHEDULE.store(false, Ordering::Release);
+                    restarted = true;
+                    continue 'scan;
+                }
+
                 let task = unsafe { (*self.tasks.get()).get_unchecked(i) };
 
                 // NOTE we don't need a CAS operation here because `wake` invocations that come from
@@ -102,21 +193,32 @@ impl Executor {
                     };
                     let mut cx = Context::from_waker(&waker);
                     // this points into a `static` memory so it's already pinned
-                    if unsafe {
-                        !Pin::new_unchecked(&mut *task.f.get())
-                            .poll(&mut cx)
-                            .is_ready()
-                    } {
+                    if !self
+                        .watch(Some(i), || unsafe {
+                            Pin::new_unchecked(&mut *task.f.get()).poll(&mut cx)
+                        })
+                        .is_ready()
+                    {
                         continue;
                     }
                 }
             }
 
+            restarted = false;
+
             if task_woken {
                 // If at least one task was woken up, do not sleep, try again
                 continue;
             }
 
+            if until_stalled {
+                break None;
+            }
+
+            if let Some(hook) = self.idle_hook.get() {
+                hook();
+            }
+
             // try to sleep; this will be a no-op if any of the previous tasks generated a SEV or an
             // interrupt ran (regardless of whether it generated a wake-up or not)
             unsafe { crate::wait_for_event() };
@@ -126,16 +228,22 @@ impl Executor {
     }
 
     // NOTE CAREFUL! this method can overlap with `block_on`
-    // FIXME we want to use `Future<Output = !>` here but the never type (`!`) is unstable; so as a
-    // workaround we'll "abort" if the task / future terminates (see `Task::new`)
-    pub fn spawn(&self, f: impl Future + 'static) {
+    pub fn spawn<T>(&self, f: impl Future<Output = T> + 'static) -> JoinHandle<T>
+    where
+        T: 'static,
+    {
+        // NOTE(unsafe) Only safe as long as `spawn` is not re-entered
+        let state = unsafe { (*(ALLOC.get() as *mut Alloc)).alloc_init(JoinState::new()) };
+
         // NOTE(unsafe) only safe as long as `spawn` is never re-entered and this does not overlap
         // with operation `(A)` (see `Task::block_on`)
-        let res = unsafe { (*self.tasks.get()).push(Task::new(f)) };
+        let res = unsafe { (*self.tasks.get()).push(Task::new(f, state)) };
         if res.is_err() {
             // OOM
             crate::abort()
         }
+
+        JoinHandle::new(state)
     }
 }
 
@@ -150,23 +258,52 @@ where
 }
 
 impl Task {
-    fn new(f: impl Future + 'static) -> &'static mut Self {
+    fn new<T>(
+        f: impl Future<Output = T> + 'static,
+        state: &'static JoinState<T>,
+    ) -> &'static mut Self
+    where
+        T: 'static,
+    {
         // NOTE(unsafe) Only safe as long as `Executor::spawn` is not re-entered
         unsafe {
             // Already initialized at this point
             let alloc = ALLOC.get() as *mut Alloc;
             (*alloc).alloc_init(Node {
                 ready: AtomicBool::new(true),
-                f: UnsafeCell::new(async {
-                    f.await;
-                    // `spawn`-ed tasks must never terminate
-                    crate::abort()
+                f: UnsafeCell::new(async move {
+                    let val = f.await;
+                    state.complete(val);
+                    // tasks are never deallocated and the executor polls them whenever they are
+                    // woken up so this one must never complete
+                    Finished.await
                 }),
             })
         }
     }
 }
 
+// A future that never completes and never wakes up its task
+struct Finished;
+
+impl Future for Finished {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        Poll::Pending
+    }
+}
+
+// NOTE may be set from interrupt context; it's only a hint so a lost update (it's cleared with a
+// load-store pair) is harmless
+static RESCHEDULE: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn reschedule() {
+    RESCHEDULE.store(true, Ordering::Release);
+    // don't go to sleep if the executor is about to
+    unsafe { crate::signal_event_ready() }
+}
+
 static mut ALLOC: UnsafeCell<MaybeUninit<Alloc>> = UnsafeCell::new(MaybeUninit::uninit());
 
 /// Returns a handle to the executor singleton
@@ -200,8 +337,16 @@ pub(crate) fn current() -> &'static Executor {
     }
 }
 
+#[cfg(any(target_arch = "arm", target_arch = "riscv32", target_arch = "riscv64"))]
 fn in_thread_mode() -> bool {
     const SCB_ICSR: *const u32 = 0xE000_ED04 as *const u32;
     // NOTE(unsafe) single-instruction load with no side effects
     unsafe { SCB_ICSR.read_volatile() as u8 == 0 }
 }
+
+// NOTE there are no interrupts on the host; the caller must not share the executor between
+// threads (e.g. a test binary must have a single `#[test]`)
+#[cfg(not(any(target_arch = "arm", target_arch = "riscv32", target_arch = "riscv64")))]
+fn in_thread_mode() -> bool {
+    true
+}
@@ -25,14 +25,36 @@ pub(crate) unsafe fn signal_event_ready() {
     asm::sev();
 }
 
-#[cfg(target_arch = "arm")]
+#[cfg(all(target_arch = "arm", not(feature = "busy-poll")))]
 #[inline]
 /// Wait for an interrupt or until notified by other hart via `signal_task_ready`
-/// This particular implementation does nothing
+/// This particular implementation sleeps the core until the next event
 pub(crate) unsafe fn wait_for_event() {
     asm::wfe();
 }
 
+#[cfg(all(target_arch = "arm", feature = "busy-poll"))]
+#[inline]
+/// Wait for an interrupt or until notified by other hart via `signal_task_ready`
+/// This particular implementation never sleeps: the core keeps running (and drawing power) so a
+/// debugger attached to it sees continuous execution instead of a core stuck in `WFE`
+pub(crate) unsafe fn wait_for_event() {}
+
+#[cfg(all(target_arch = "arm", feature = "poll-watchdog"))]
+#[inline(always)]
+/// Reads the cycle counter (`DWT.CYCCNT`)
+pub(crate) fn cycle_count() -> u32 {
+    cortex_m::peripheral::DWT::get_cycle_count()
+}
+
+// NOTE the `riscv-wait-nop` strategy already never sleeps; the others would sleep regardless
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    feature = "busy-poll",
+    not(feature = "riscv-wait-nop")
+))]
+compile_error!("on RISC-V `busy-poll` requires the `riscv-wait-nop` wait strategy");
+
 #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
 /// This keeps dropping into the debugger and never returns
 pub fn abort() -> ! {
@@ -98,5 +120,42 @@ pub(crate) unsafe fn wait_for_event() {
     TASK_READY = false;
 }
 
+#[cfg(all(any(target_arch = "riscv32", target_arch = "riscv64"), feature = "poll-watchdog"))]
+#[inline(always)]
+/// Reads the cycle counter (`mcycle`)
+pub(crate) fn cycle_count() -> u32 {
+    riscv::register::mcycle::read() as u32
+}
+
+#[cfg(not(any(target_arch = "arm", target_arch = "riscv32", target_arch = "riscv64")))]
+/// Panics; there's no debugger to drop into on the host
+///
+/// NOTE the host (e.g. `x86_64`) is only supported to run tests (see `task::run_until_stalled`)
+pub fn abort() -> ! {
+    panic!("aborted")
+}
+
+#[cfg(not(any(target_arch = "arm", target_arch = "riscv32", target_arch = "riscv64")))]
+#[inline]
+/// Prevent next `wait_for_interrupt` from sleeping, wake up other harts if needed.
+/// This particular implementation does nothing, since `wait_for_interrupt` never sleeps
+pub(crate) unsafe fn signal_event_ready() {}
+
+#[cfg(not(any(target_arch = "arm", target_arch = "riscv32", target_arch = "riscv64")))]
+#[inline]
+/// Wait for an interrupt or until notified by other hart via `signal_task_ready`
+/// This particular implementation does nothing: there are no interrupts on the host
+pub(crate) unsafe fn wait_for_event() {}
+
+#[cfg(all(
+    not(any(target_arch = "arm", target_arch = "riscv32", target_arch = "riscv64")),
+    feature = "poll-watchdog"
+))]
+#[inline(always)]
+/// There's no cycle counter on the host; this always returns 0
+pub(crate) fn cycle_count() -> u32 {
+    0
+}
+
 /// Maximum number of tasks (TODO this could be user configurable)
 type NTASKS = typenum::consts::U8;
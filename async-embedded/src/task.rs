@@ -1,13 +1,22 @@
 //! Asynchronous tasks
 
 use core::{
+    cell::Cell,
+    cmp,
     future::Future,
     pin::Pin,
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
+    time::Duration,
 };
 
+use generic_array::ArrayLength;
+use heapless::Vec;
+pub use pin_utils::pin_mut;
+
 use crate::executor;
 
+pub use crate::alloc::AllocStats;
+
 /// Drives the future `f` to completion
 ///
 /// This also makes any previously `spawn`-ed future make progress
@@ -15,16 +24,594 @@ pub fn block_on<T>(f: impl Future<Output = T>) -> T {
     executor::current().block_on(f)
 }
 
+/// Like `block_on` but returns `None` instead of sleeping once neither `f` nor any `spawn`-ed task
+/// can make progress
+///
+/// This is meant for tests, e.g. on the host: the outcome doesn't depend on interrupts. If `None`
+/// is returned `f` has been dropped; the `spawn`-ed tasks keep their state
+pub fn run_until_stalled<T>(f: impl Future<Output = T>) -> Option<T> {
+    executor::current().run_until_stalled(f)
+}
+
+/// Like `block_on` but gives up on `f` if it doesn't complete within `dur`
+///
+/// `timer` watches the deadline (see `Delay`). On time out, `f` is dropped and `Err(TimedOut)` is
+/// returned; this gives `main` a chance to reset the device or enter a safe state when the
+/// program is stuck. Previously `spawn`-ed tasks keep their state; they'll make progress again on
+/// the next `block_on` call
+pub fn block_on_timeout<T>(
+    f: impl Future<Output = T>,
+    dur: Duration,
+    timer: impl Delay,
+) -> Result<T, TimedOut> {
+    block_on(async {
+        // NOTE `f` goes first so it wins when it completes right at the deadline
+        match select(f, timer.delay(dur)).await {
+            Either::Left(val) => Ok(val),
+            Either::Right(()) => Err(TimedOut),
+        }
+    })
+}
+
+/// A timer that `block_on_timeout` can use to watch its deadline and `retry_until` to space out
+/// its attempts
+///
+/// This crate doesn't drive any hardware timer; HAL crates implement this trait for theirs
+pub trait Delay {
+    /// The future returned by `delay`
+    type Delay: Future<Output = ()>;
+
+    /// Returns a future that completes once `dur` has elapsed
+    ///
+    /// The time is measured from the call to `delay`
+    fn delay(self, dur: Duration) -> Self::Delay;
+}
+
+/// The future passed to `block_on_timeout` didn't complete in time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimedOut;
+
+/// Runs the operation `op` until it succeeds, waiting between attempts as specified by `policy`
+///
+/// `timer` measures the delays (see `Delay`). Returns the output of the first successful attempt
+/// or the error of the last attempt
+///
+/// # Panics
+///
+/// This function panics if `policy.max_attempts` is 0
+pub async fn retry_until<F, Fut, T, E>(
+    policy: RetryPolicy,
+    timer: impl Delay + Copy,
+    mut op: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    assert!(
+        policy.max_attempts != 0,
+        "`max_attempts` must be at least 1"
+    );
+
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(val) => return Ok(val),
+            Err(e) if attempt >= policy.max_attempts => return Err(e),
+            Err(_) => {
+                timer.delay(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// How `retry_until` bounds and spaces out the attempts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one; must be at least 1
+    pub max_attempts: u32,
+
+    /// Delay between attempts
+    pub backoff: Backoff,
+}
+
+/// Delay between retries
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backoff {
+    /// Always wait the same time
+    Constant(Duration),
+
+    /// Wait `initial` after the first attempt and double the delay after each attempt, up to `max`
+    Exponential {
+        /// Delay after the first attempt
+        initial: Duration,
+
+        /// Upper bound of the delay
+        max: Duration,
+    },
+}
+
+impl RetryPolicy {
+    /// Up to `max_attempts` attempts `delay` apart
+    pub fn constant(max_attempts: u32, delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            backoff: Backoff::Constant(delay),
+        }
+    }
+
+    /// Up to `max_attempts` attempts with a delay that starts at `initial` and doubles after each
+    /// attempt, up to `max`
+    pub fn exponential(max_attempts: u32, initial: Duration, max: Duration) -> Self {
+        Self {
+            max_attempts,
+            backoff: Backoff::Exponential { initial, max },
+        }
+    }
+
+    /// Delay after the attempt number `attempt` (starting at 1) fails
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match self.backoff {
+            Backoff::Constant(delay) => delay,
+            Backoff::Exponential { initial, max } => {
+                let doublings = attempt.saturating_sub(1);
+                if doublings >= 32 {
+                    max
+                } else {
+                    initial
+                        .checked_mul(1 << doublings)
+                        .map(|delay| cmp::min(delay, max))
+                        .unwrap_or(max)
+                }
+            }
+        }
+    }
+}
+
 /// Spawns a task onto the executor
 ///
 /// The spawned task will not make any progress until `block_on` is called.
 ///
-/// The future `f` must never terminate. The program will *abort* if `f` (the async code) returns.
-/// The right signature here would be `f: impl Future<Output = !>` but that requires nightly
-pub fn spawn<T>(f: impl Future<Output = T> + 'static) {
+/// The returned `JoinHandle` can be `await`-ed to get the output of `f`; dropping it detaches the
+/// task, which keeps running and whose output is discarded
+pub fn spawn<T>(f: impl Future<Output = T> + 'static) -> JoinHandle<T>
+where
+    T: 'static,
+{
     executor::current().spawn(f)
 }
 
+/// A handle to a `spawn`-ed task; it can be `await`-ed to get the output of the task
+///
+/// Once it has returned the output, the handle will never complete again
+pub struct JoinHandle<T>
+where
+    T: 'static,
+{
+    state: &'static JoinState<T>,
+}
+
+// NOTE shared between a task (which writes the output) and its `JoinHandle`; allocated next to
+// the task and never deallocated
+pub(crate) struct JoinState<T> {
+    output: Cell<Option<T>>,
+    waker: Cell<Option<Waker>>,
+    detached: Cell<bool>,
+}
+
+impl<T> JoinState<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            output: Cell::new(None),
+            waker: Cell::new(None),
+            detached: Cell::new(false),
+        }
+    }
+
+    // NOTE called by the task when `f` completes
+    pub(crate) fn complete(&self, val: T) {
+        if self.detached.get() {
+            drop(val);
+        } else {
+            self.output.set(Some(val));
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> JoinHandle<T>
+where
+    T: 'static,
+{
+    pub(crate) fn new(state: &'static JoinState<T>) -> Self {
+        Self { state }
+    }
+}
+
+impl<T> Future for JoinHandle<T>
+where
+    T: 'static,
+{
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        if let Some(val) = self.state.output.take() {
+            Poll::Ready(val)
+        } else {
+            self.state.waker.set(Some(cx.waker().clone()));
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for JoinHandle<T>
+where
+    T: 'static,
+{
+    fn drop(&mut self) {
+        self.state.detached.set(true);
+        // NOTE the waker may point into the stack of `block_on`; don't leave it behind
+        drop(self.state.waker.take());
+        drop(self.state.output.take());
+    }
+}
+
+/// Runs the futures `a` and `b` concurrently and returns both outputs once they have completed
+///
+/// Unlike `spawn`, this doesn't require the futures to be `'static` so they can borrow data
+/// owned by the caller (e.g. stack variables), including the same data if it's shared (`&-`). Both
+/// futures run as part of the current task, which is not resumed until both have completed, so
+/// the borrows can't outlive the data (structured concurrency)
+///
+/// If this future is dropped before completing (e.g. it lost a `select`) both futures are dropped
+/// with it; see the cancellation section of `select`
+pub async fn join<A, B>(a: A, b: B) -> (A::Output, B::Output)
+where
+    A: Future,
+    B: Future,
+{
+    struct Join<'a, A, B>
+    where
+        A: Future,
+        B: Future,
+    {
+        a: Pin<&'a mut A>,
+        b: Pin<&'a mut B>,
+        // NOTE outputs live outside the future so that `Join` is `Unpin`
+        a_out: &'a mut Option<A::Output>,
+        b_out: &'a mut Option<B::Output>,
+    }
+
+    impl<A, B> Future for Join<'_, A, B>
+    where
+        A: Future,
+        B: Future,
+    {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let this = &mut *self;
+
+            // NOTE a future must not be polled again after it has completed
+            if this.a_out.is_none() {
+                if let Poll::Ready(val) = this.a.as_mut().poll(cx) {
+                    *this.a_out = Some(val);
+                }
+            }
+
+            if this.b_out.is_none() {
+                if let Poll::Ready(val) = this.b.as_mut().poll(cx) {
+                    *this.b_out = Some(val);
+                }
+            }
+
+            if this.a_out.is_some() && this.b_out.is_some() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    let mut a_out = None;
+    let mut b_out = None;
+    pin_mut!(a);
+    pin_mut!(b);
+    Join {
+        a,
+        b,
+        a_out: &mut a_out,
+        b_out: &mut b_out,
+    }
+    .await;
+
+    match (a_out, b_out) {
+        (Some(a), Some(b)) => (a, b),
+        _ => unreachable!(),
+    }
+}
+
+/// A future borrowed by a `Scope`; `None` once it has completed
+pub type LocalTask<'s> = Option<Pin<&'s mut (dyn Future<Output = ()> + 's)>>;
+
+/// A set of up to `N` tasks that, unlike `spawn`-ed ones, can borrow data owned by the caller
+///
+/// The tasks are futures pinned on the stack (see `pin_mut!`) of the task that owns the scope.
+/// `run` drives them, concurrently with another future, as part of the current task and only
+/// completes once all of them have completed. The borrow checker rejects a task that borrows data
+/// which doesn't outlive the scope so the borrows can't dangle (structured concurrency)
+///
+/// ```
+/// use async_embedded::task::{self, pin_mut, Scope};
+/// use typenum::consts::U2;
+///
+/// let mut buf = [0; 4];
+/// let sum = task::run_until_stalled(async {
+///     let fill = async {
+///         for (i, byte) in buf.iter_mut().enumerate() {
+///             *byte = i as u8;
+///             task::r#yield().await;
+///         }
+///     };
+///     pin_mut!(fill);
+///
+///     let mut scope = Scope::<U2>::new();
+///     scope.spawn_local(fill);
+///     scope.run(async { 1 + 2 }).await
+/// });
+///
+/// assert_eq!(sum, Some(3));
+/// // the scope is gone so `buf` can be used again
+/// assert_eq!(buf, [0, 1, 2, 3]);
+/// ```
+///
+/// A task can't borrow data that goes away before the scope does
+///
+/// ``` compile_fail
+/// use async_embedded::task::{self, pin_mut, Scope};
+/// use typenum::consts::U2;
+///
+/// task::run_until_stalled(async {
+///     let mut scope = Scope::<U2>::new();
+///     {
+///         let mut buf = [0; 4];
+///         let fill = async {
+///             buf[0] = 1;
+///         };
+///         pin_mut!(fill);
+///         scope.spawn_local(fill);
+///     }
+///     scope.run(async {}).await
+/// });
+/// ```
+pub struct Scope<'s, N>
+where
+    N: ArrayLength<LocalTask<'s>>,
+{
+    tasks: Vec<LocalTask<'s>, N>,
+}
+
+impl<'s, N> Scope<'s, N>
+where
+    N: ArrayLength<LocalTask<'s>>,
+{
+    /// Creates an empty scope
+    pub fn new() -> Self {
+        Self { tasks: Vec::new() }
+    }
+
+    /// Adds the task `f` to the scope; it makes progress once `run` is called
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the scope already holds `N` tasks
+    pub fn spawn_local(&mut self, f: Pin<&'s mut (dyn Future<Output = ()> + 's)>) {
+        if self.tasks.push(Some(f)).is_err() {
+            panic!("scope is full");
+        }
+    }
+
+    /// Runs `f` and the tasks of the scope concurrently; returns the output of `f` once all of
+    /// them have completed
+    ///
+    /// The scope is empty afterwards so it can be reused. If this future is dropped before
+    /// completing, the tasks that didn't complete stay in the scope; they are dropped where they
+    /// were pinned
+    pub async fn run<T>(&mut self, f: impl Future<Output = T>) -> T {
+        struct Tasks<'r, 's, N>
+        where
+            N: ArrayLength<LocalTask<'s>>,
+        {
+            tasks: &'r mut Vec<LocalTask<'s>, N>,
+        }
+
+        impl<'s, N> Future for Tasks<'_, 's, N>
+        where
+            N: ArrayLength<LocalTask<'s>>,
+        {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                let mut done = true;
+                // NOTE the tasks share the waker of the current task, like the futures of `join`
+                for slot in self.tasks.iter_mut() {
+                    if let Some(task) = slot {
+                        if task.as_mut().poll(cx).is_ready() {
+                            // a future must not be polled again after it has completed
+                            *slot = None;
+                        } else {
+                            done = false;
+                        }
+                    }
+                }
+
+                if done {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            }
+        }
+
+        let (val, ()) = join(
+            f,
+            Tasks {
+                tasks: &mut self.tasks,
+            },
+        )
+        .await;
+        self.tasks.clear();
+        val
+    }
+}
+
+/// The output of `select`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Either<A, B> {
+    /// The first future completed first
+    Left(A),
+
+    /// The second future completed first
+    Right(B),
+}
+
+/// Runs the futures `a` and `b` concurrently and returns the output of whichever completes first
+///
+/// The other future is dropped (cancelled). If both futures are ready at the same time, `a` wins.
+/// Like `join`, the futures don't need to be `'static`
+///
+/// # Cancellation
+///
+/// The loser is dropped *before* this future completes, so its destructor (which e.g. stops a DMA
+/// transfer) runs while the data it borrows, which outlives the `select` call, is still alive. The
+/// same holds for both futures when this future is itself dropped before completing
+pub async fn select<A, B>(a: A, b: B) -> Either<A::Output, B::Output>
+where
+    A: Future,
+    B: Future,
+{
+    struct Select<'a, A, B> {
+        a: Pin<&'a mut A>,
+        b: Pin<&'a mut B>,
+    }
+
+    impl<A, B> Future for Select<'_, A, B>
+    where
+        A: Future,
+        B: Future,
+    {
+        type Output = Either<A::Output, B::Output>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            // NOTE both futures are polled with the waker of the current task so whichever makes
+            // progress first resumes this future
+            if let Poll::Ready(val) = self.a.as_mut().poll(cx) {
+                Poll::Ready(Either::Left(val))
+            } else if let Poll::Ready(val) = self.b.as_mut().poll(cx) {
+                Poll::Ready(Either::Right(val))
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    // NOTE `a` and `b` are locals of this `async fn` so they are dropped before it returns `Ready`
+    // (or when it's cancelled); the data they borrow is owned by our caller and outlives them
+    pin_mut!(a);
+    pin_mut!(b);
+    Select { a, b }.await
+}
+
+/// Asks the executor to restart its scan of the tasks at the next safe point
+///
+/// The executor polls the `block_on` future first and then the `spawn`-ed tasks in the order they
+/// were spawned. Calling this right after waking up a latency sensitive task (e.g. the `block_on`
+/// one) lets the executor service it without first polling the remaining tasks. This can be called
+/// from interrupt handlers
+///
+/// The executor only starts over if the main task is ready by then, and at most once per scan:
+/// a scan that started over runs to completion so the tasks spawned last are not starved when the
+/// hint is given all the time. Every task is still polled at least once every two scans, which
+/// can double the latency of the tasks that come after the one that gives the hint
+///
+/// The executor is cooperative: this doesn't preempt the task that's being polled so the latency
+/// is still bounded by the longest `poll`
+pub fn reschedule() {
+    executor::reschedule()
+}
+
+/// Returns the memory usage of the allocator that backs `spawn`
+///
+/// Each `spawn`-ed task permanently uses as much memory as the size of its future (plus some
+/// bookkeeping); call this after all tasks have been spawned to find out how much is left
+pub fn allocator_stats() -> AllocStats {
+    executor::current().allocator_stats()
+}
+
+/// Registers a function that will be called every time the executor is about to sleep
+///
+/// At that point all tasks are waiting for an event (e.g. an interrupt); the hook can be used to
+/// put unused peripherals in a low power state. The hook must not block; it runs with interrupts
+/// enabled so an event may arrive while it runs, in which case the executor won't go to sleep
+pub fn set_idle_hook(hook: fn()) {
+    executor::current().set_idle_hook(hook)
+}
+
+/// Returns the number of times the executor has scanned its tasks, in `block_on`
+///
+/// On each scan the executor polls every task that has been woken, in `spawn` order after the
+/// future passed to `block_on`, once. Two tasks that observe the same tick were polled in the same
+/// scan; this can be used to make assertions about the interleaving of tasks that don't depend on
+/// wall time. The counter wraps around on overflow
+#[cfg(feature = "scheduler-tick")]
+pub fn current_tick() -> u32 {
+    executor::current().current_tick()
+}
+
+/// A single `poll` of a task that took longer than the threshold set with `set_poll_watchdog`
+#[cfg(feature = "poll-watchdog")]
+#[derive(Clone, Copy, Debug)]
+pub struct Overrun {
+    /// Index of the task, in `spawn` order; `None` means the future passed to `block_on`
+    pub task: Option<usize>,
+
+    /// Duration of the `poll`, in clock cycles
+    pub cycles: u32,
+}
+
+/// Calls `hook` every time a single `poll` of a task takes more than `threshold` clock cycles
+///
+/// A task that doesn't yield (`.await` on a future that's not ready) for a long time starves all
+/// the other tasks; use this to find out which task needs more yield points. `hook` runs right
+/// after the offending `poll` returns
+///
+/// On ARM Cortex-M the cycle counter (`DWT.CYCCNT`) must have been enabled beforehand (see
+/// `cortex_m::peripheral::DWT::enable_cycle_counter`); on RISC-V the `mcycle` register is used
+#[cfg(feature = "poll-watchdog")]
+pub fn set_poll_watchdog(threshold: u32, hook: fn(Overrun)) {
+    executor::current().set_watchdog(threshold, hook)
+}
+
+/// Runs a long synchronous computation in slices, yielding to other tasks between slices
+///
+/// `work` is called once per `poll`; it should do a bounded amount of work and return
+/// `Poll::Pending` if there's more work left to do, or `Poll::Ready` with the result. This is the
+/// cooperative counterpart of a thread pool's `spawn_blocking`: it keeps a CPU-bound computation
+/// (e.g. a CRC over a large buffer) from starving the other tasks
+pub async fn chunked<T>(mut work: impl FnMut() -> Poll<T>) -> T {
+    loop {
+        if let Poll::Ready(x) = work() {
+            return x;
+        }
+
+        // let the other tasks run
+        r#yield().await
+    }
+}
+
 /// Use `r#yield.await` to suspend the execution of a task
 pub async fn r#yield() {
     struct Yield {
@@ -1,8 +1,22 @@
 //! Tasks synchronization primitives that are *not* thread / interrupt safe (`!Sync`)
 
 mod channel;
+pub mod event_bus;
+pub mod log;
+pub mod mpsc;
 mod mutex;
+mod notify;
+pub mod oneshot;
+mod ring;
+pub mod rpc;
+mod semaphore;
+pub mod spsc;
 mod waker_set;
 
 pub use channel::Channel;
-pub use mutex::Mutex;
+pub use event_bus::EventBus;
+pub use mpsc::Mpsc;
+pub use mutex::{Mutex, MutexGuard};
+pub use notify::Notify;
+pub use oneshot::Oneshot;
+pub use semaphore::{Semaphore, SemaphorePermit};
@@ -1,63 +1,109 @@
 // NOTE waker logic is based on async-std v1.5.0
 
-// A `!Sync` MPMC queue / Channel is just a classic ring buffer that consists of
-// an array with read and write cursors
+// A `!Sync` MPMC queue / Channel is just a classic ring buffer (see `Ring`) plus the wakers of the
+// tasks waiting on it
 
 use core::{
-    cell::{Cell, UnsafeCell},
+    cell::Cell,
     future::Future,
     marker::Unpin,
-    mem::MaybeUninit,
     pin::Pin,
     task::{Context, Poll},
 };
 
-use generic_array::{typenum::Unsigned, GenericArray};
-
-use super::waker_set::WakerSet;
-
-/// MPMC channel
-// FIXME this needs a destructor
-// TODO make this generic over the capacity -- that would require the newtype with public field hack
-// to keep the `const-fn` `new`. See `heapless` for examples of the workaround
-// TODO a SPSC version of this. It should not need the `WakerSet` but rather something like
-// `Option<Waker>`
-pub struct Channel<T> {
-    buffer: UnsafeCell<MaybeUninit<GenericArray<T, crate::NTASKS>>>,
-    read: Cell<usize>,
-    write: Cell<usize>,
+use generic_array::{typenum::Unsigned, ArrayLength};
+
+use super::{ring::Ring, waker_set::WakerSet};
+
+/// MPMC channel with a capacity of `N` messages
+///
+/// The channel can be `close`-d to tell the receivers that no more messages will be sent
+///
+/// See `spsc::Channel` for a lighter channel between two tasks
+pub struct Channel<T, N>
+where
+    N: ArrayLength<T>,
+{
+    ring: Ring<T, N>,
+    closed: Cell<bool>,
     send_wakers: WakerSet,
     recv_wakers: WakerSet,
 }
 
-impl<T> Channel<T> {
+impl<T, N> Channel<T, N>
+where
+    N: ArrayLength<T>,
+{
     /// Creates a new fixed capacity channel
     pub const fn new() -> Self {
         Self {
-            buffer: UnsafeCell::new(MaybeUninit::uninit()),
-            read: Cell::new(0),
-            write: Cell::new(0),
+            ring: Ring::new(),
+            closed: Cell::new(false),
             send_wakers: WakerSet::new(),
             recv_wakers: WakerSet::new(),
         }
     }
 
+    /// Returns the number of messages in the channel
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    /// Returns the maximum number of messages the channel can hold
+    pub fn capacity(&self) -> usize {
+        N::USIZE
+    }
+
+    /// Returns `true` if the channel holds no messages
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    /// Returns `true` if the channel can't hold more messages; `try_send` would fail
+    pub fn is_full(&self) -> bool {
+        self.ring.is_full()
+    }
+
+    /// Closes the channel
+    ///
+    /// Pending and future sends fail. The receivers can still receive the messages that are in the
+    /// channel; after that `recv` returns `None`
+    pub fn close(&self) {
+        self.closed.set(true);
+        self.send_wakers.notify_all();
+        self.recv_wakers.notify_all();
+        unsafe { crate::signal_event_ready() }
+    }
+
+    /// Returns `true` if the channel has been closed
+    pub fn is_closed(&self) -> bool {
+        self.closed.get()
+    }
+
     /// Sends a message into the channel
-    pub async fn send(&self, val: T) {
-        struct Send<'a, T> {
-            channel: &'a Channel<T>,
+    ///
+    /// Returns the message back if the channel is, or gets, closed before the message is sent
+    pub async fn send(&self, val: T) -> Result<(), T> {
+        struct Send<'a, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            channel: &'a Channel<T, N>,
             msg: Option<T>,
             opt_key: Option<usize>,
         }
 
         // XXX(japaric) why is this required here but not in `Recv`? is it due
         // to `msg.take()`?
-        impl<T> Unpin for Send<'_, T> {}
+        impl<T, N> Unpin for Send<'_, T, N> where N: ArrayLength<T> {}
 
-        impl<T> Future for Send<'_, T> {
-            type Output = ();
+        impl<T, N> Future for Send<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            type Output = Result<(), T>;
 
-            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), T>> {
                 let msg = self.msg.take().expect("UNREACHABLE");
 
                 // If the current task is in the set, remove it.
@@ -66,6 +112,10 @@ impl<T> Channel<T> {
                 }
 
                 if let Err(msg) = self.channel.try_send(msg) {
+                    if self.channel.is_closed() {
+                        return Poll::Ready(Err(msg));
+                    }
+
                     self.msg = Some(msg);
 
                     // Insert this send operation.
@@ -73,7 +123,19 @@ impl<T> Channel<T> {
 
                     Poll::Pending
                 } else {
-                    Poll::Ready(())
+                    Poll::Ready(Ok(()))
+                }
+            }
+        }
+
+        impl<T, N> Drop for Send<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            fn drop(&mut self) {
+                // If the current task is still in the set, that means it is being cancelled now.
+                if let Some(key) = self.opt_key {
+                    self.channel.send_wakers.cancel(key);
                 }
             }
         }
@@ -87,16 +149,24 @@ impl<T> Channel<T> {
     }
 
     /// Receives a message from the channel
-    pub async fn recv(&self) -> T {
-        struct Recv<'a, T> {
-            channel: &'a Channel<T>,
+    ///
+    /// Returns `None` if the channel has been closed and all its messages have been received
+    pub async fn recv(&self) -> Option<T> {
+        struct Recv<'a, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            channel: &'a Channel<T, N>,
             opt_key: Option<usize>,
         }
 
-        impl<T> Future for Recv<'_, T> {
-            type Output = T;
+        impl<T, N> Future for Recv<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            type Output = Option<T>;
 
-            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
                 // If the current task is in the set, remove it.
                 if let Some(key) = self.opt_key.take() {
                     self.channel.recv_wakers.remove(key);
@@ -104,7 +174,9 @@ impl<T> Channel<T> {
 
                 // Try receiving a message.
                 if let Some(msg) = self.channel.try_recv() {
-                    Poll::Ready(msg)
+                    Poll::Ready(Some(msg))
+                } else if self.channel.is_closed() {
+                    Poll::Ready(None)
                 } else {
                     // Insert this receive operation.
                     self.opt_key = Some(self.channel.recv_wakers.insert(cx));
@@ -113,6 +185,18 @@ impl<T> Channel<T> {
             }
         }
 
+        impl<T, N> Drop for Recv<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            fn drop(&mut self) {
+                // If the current task is still in the set, that means it is being cancelled now.
+                if let Some(key) = self.opt_key {
+                    self.channel.recv_wakers.cancel(key);
+                }
+            }
+        }
+
         Recv {
             channel: self,
             opt_key: None,
@@ -124,49 +208,25 @@ impl<T> Channel<T> {
     ///
     /// Returns None if the channel is currently empty
     pub fn try_recv(&self) -> Option<T> {
-        unsafe {
-            let read = self.read.get();
-            let write = self.write.get();
-            let cap = crate::NTASKS::USIZE;
-            let bufferp = self.buffer.get() as *mut T;
-
-            if write > read {
-                let cursor = read % cap;
-                let val = bufferp.add(cursor).read();
-                self.read.set(read.wrapping_add(1));
-                // notify a sender
-                self.send_wakers.notify_one();
-                crate::signal_event_ready();
-                Some(val)
-            } else {
-                // empty
-                None
-            }
-        }
+        let val = self.ring.pop()?;
+        // notify a sender
+        self.send_wakers.notify_one();
+        unsafe { crate::signal_event_ready() }
+        Some(val)
     }
 
     /// Attempts to send a message into the channel
     ///
-    /// Returns an error if the channel buffer is currently full
+    /// Returns an error if the channel buffer is currently full or if the channel has been closed
     pub fn try_send(&self, val: T) -> Result<(), T> {
-        unsafe {
-            let read = self.read.get();
-            let write = self.write.get();
-            let cap = crate::NTASKS::USIZE;
-            let bufferp = self.buffer.get() as *mut T;
-
-            if write < read + cap {
-                let cursor = write % cap;
-                bufferp.add(cursor).write(val);
-                self.write.set(write.wrapping_add(1));
-                // notify a receiver
-                self.recv_wakers.notify_one();
-                crate::signal_event_ready();
-                Ok(())
-            } else {
-                // full
-                Err(val)
-            }
+        if self.is_closed() {
+            return Err(val);
         }
+
+        self.ring.push(val)?;
+        // notify a receiver
+        self.recv_wakers.notify_one();
+        unsafe { crate::signal_event_ready() }
+        Ok(())
     }
 }
@@ -0,0 +1,209 @@
+//! Broadcast event bus
+
+// NOTE waker logic is based on async-std v1.5.0
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    future::Future,
+    mem::MaybeUninit,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use generic_array::{typenum::Unsigned, ArrayLength, GenericArray};
+
+use super::waker_set::WakerSet;
+
+/// Event bus: every event `publish`-ed by any task is received by all the subscribers
+///
+/// The bus keeps the last `N` events; each `Subscriber` reads them at its own pace. Publishing
+/// never blocks: a subscriber that falls more than `N` events behind loses the oldest ones and is
+/// told how many it missed (see `Lagged`)
+pub struct EventBus<E, N>
+where
+    N: ArrayLength<E>,
+{
+    buffer: UnsafeCell<MaybeUninit<GenericArray<E, N>>>,
+    // number of events published so far
+    published: Cell<usize>,
+    // slot the next event will be written to
+    // NOTE `published % N` would jump to a non-consecutive slot when `published` wraps around,
+    // unless `N` is a power of two
+    head: Cell<usize>,
+    wakers: WakerSet,
+}
+
+/// The subscriber fell behind and missed this many events
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lagged(pub usize);
+
+impl<E, N> EventBus<E, N>
+where
+    E: Copy,
+    N: ArrayLength<E>,
+{
+    /// Creates a new event bus
+    pub const fn new() -> Self {
+        Self {
+            buffer: UnsafeCell::new(MaybeUninit::uninit()),
+            published: Cell::new(0),
+            head: Cell::new(0),
+            wakers: WakerSet::new(),
+        }
+    }
+
+    /// Sends `event` to all the current subscribers
+    pub fn publish(&self, event: E) {
+        let head = self.head.get();
+        let bufferp = self.buffer.get() as *mut E;
+        // NOTE(unsafe) `E: Copy` so overwriting an old event doesn't leak anything
+        unsafe { bufferp.add(head).write(event) }
+        self.head
+            .set(if head + 1 == N::USIZE { 0 } else { head + 1 });
+        self.published.set(self.published.get().wrapping_add(1));
+
+        if self.wakers.notify_all() {
+            unsafe { crate::signal_event_ready() }
+        }
+    }
+
+    /// Returns a subscriber that will receive the events published from now on
+    pub fn subscribe(&self) -> Subscriber<'_, E, N> {
+        Subscriber {
+            bus: self,
+            next: self.published.get(),
+        }
+    }
+}
+
+/// Receiving endpoint of an `EventBus`
+pub struct Subscriber<'a, E, N>
+where
+    N: ArrayLength<E>,
+{
+    bus: &'a EventBus<E, N>,
+    // sequence number of the next event to receive
+    next: usize,
+}
+
+impl<E, N> Subscriber<'_, E, N>
+where
+    E: Copy,
+    N: ArrayLength<E>,
+{
+    /// Waits for the next event
+    ///
+    /// Returns `Err(Lagged)` once if events were lost since the last call; the following calls
+    /// return the oldest events still available
+    pub async fn next(&mut self) -> Result<E, Lagged> {
+        struct Next<'s, 'a, E, N>
+        where
+            N: ArrayLength<E>,
+        {
+            subscriber: &'s mut Subscriber<'a, E, N>,
+            opt_key: Option<usize>,
+        }
+
+        impl<E, N> Future for Next<'_, '_, E, N>
+        where
+            E: Copy,
+            N: ArrayLength<E>,
+        {
+            type Output = Result<E, Lagged>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                // If the current task is in the set, remove it.
+                if let Some(key) = self.opt_key.take() {
+                    self.subscriber.bus.wakers.remove(key);
+                }
+
+                if let Some(res) = self.subscriber.try_next() {
+                    Poll::Ready(res)
+                } else {
+                    // Insert this operation.
+                    let key = self.subscriber.bus.wakers.insert(cx);
+                    self.opt_key = Some(key);
+
+                    Poll::Pending
+                }
+            }
+        }
+
+        impl<E, N> Drop for Next<'_, '_, E, N>
+        where
+            N: ArrayLength<E>,
+        {
+            fn drop(&mut self) {
+                // NOTE all subscribers are notified of every event so there's no one to pass the
+                // notification to
+                if let Some(key) = self.opt_key {
+                    self.subscriber.bus.wakers.remove(key);
+                }
+            }
+        }
+
+        Next {
+            subscriber: self,
+            opt_key: None,
+        }
+        .await
+    }
+
+    /// Returns the next event, if there's one
+    pub fn try_next(&mut self) -> Option<Result<E, Lagged>> {
+        let published = self.bus.published.get();
+        let behind = published.wrapping_sub(self.next);
+        let cap = N::USIZE;
+
+        if behind == 0 {
+            None
+        } else if behind > cap {
+            // the oldest events have been overwritten; skip to the oldest one still in the buffer
+            self.next = published.wrapping_sub(cap);
+            Some(Err(Lagged(behind - cap)))
+        } else {
+            // the slot of the event `behind` events before the next one to be published
+            let slot = (self.bus.head.get() + cap - behind) % cap;
+            let bufferp = self.bus.buffer.get() as *const E;
+            // NOTE(unsafe) this slot holds an event that has been published and not overwritten
+            let event = unsafe { bufferp.add(slot).read() };
+            self.next = self.next.wrapping_add(1);
+            Some(Ok(event))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::typenum::consts::U3;
+
+    use super::{EventBus, Lagged};
+
+    // NOTE the sequence numbers wrap around at `usize::MAX`, too many events to publish in a
+    // test; the counter starts a few events short of it
+    #[test]
+    fn sequence_number_wraparound() {
+        let bus = EventBus::<u32, U3>::new();
+        bus.published.set(usize::max_value() - 4);
+
+        // a subscriber that keeps up
+        let mut sub = bus.subscribe();
+        for i in 0..10 {
+            bus.publish(i);
+            assert_eq!(sub.try_next(), Some(Ok(i)));
+        }
+        assert_eq!(sub.try_next(), None);
+
+        // a subscriber that falls 2 events behind the capacity, across the wraparound
+        bus.published.set(usize::max_value() - 1);
+        let mut sub = bus.subscribe();
+        for i in 10..15 {
+            bus.publish(i);
+        }
+        assert_eq!(sub.try_next(), Some(Err(Lagged(2))));
+        for i in 12..15 {
+            assert_eq!(sub.try_next(), Some(Ok(i)));
+        }
+        assert_eq!(sub.try_next(), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Logging sink with a configurable overflow policy
+
+// NOTE waker logic is based on async-std v1.5.0
+
+use core::{
+    cell::Cell,
+    future::Future,
+    marker::Unpin,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use generic_array::ArrayLength;
+
+use super::{ring::Ring, waker_set::WakerSet};
+
+/// What `Sink::log` does when the sink is full
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    /// Discard the oldest message to make room for the new one; the producer never waits
+    DropOldest,
+
+    /// Discard the new message; the producer never waits
+    DropNewest,
+
+    /// Wait until the consumer makes room; logging exerts backpressure on the producers
+    Block,
+}
+
+/// Fixed capacity queue of log messages
+///
+/// Producers `log` messages into the sink and a task drains it (e.g. into the serial port) with
+/// `recv`. The drop policies keep real-time producers from ever waiting on a slow consumer at the
+/// cost of losing messages; `dropped` reports how many were lost
+pub struct Sink<T, N>
+where
+    N: ArrayLength<T>,
+{
+    ring: Ring<T, N>,
+    dropped: Cell<usize>,
+    overflow: Overflow,
+    send_wakers: WakerSet,
+    recv_wakers: WakerSet,
+}
+
+impl<T, N> Sink<T, N>
+where
+    N: ArrayLength<T>,
+{
+    /// Creates a new sink with the given overflow policy
+    pub const fn new(overflow: Overflow) -> Self {
+        Self {
+            ring: Ring::new(),
+            dropped: Cell::new(0),
+            overflow,
+            send_wakers: WakerSet::new(),
+            recv_wakers: WakerSet::new(),
+        }
+    }
+
+    /// Returns the overflow policy of this sink
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Returns the number of messages discarded by the overflow policy so far
+    pub fn dropped(&self) -> usize {
+        self.dropped.get()
+    }
+
+    /// Returns the number of messages in the sink
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    /// Returns `true` if the sink holds no messages
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    /// Logs a message
+    ///
+    /// This only waits when the sink is full and its policy is `Overflow::Block`
+    pub async fn log(&self, msg: T) {
+        struct Log<'a, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            sink: &'a Sink<T, N>,
+            msg: Option<T>,
+            opt_key: Option<usize>,
+        }
+
+        impl<T, N> Unpin for Log<'_, T, N> where N: ArrayLength<T> {}
+
+        impl<T, N> Future for Log<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                let msg = self.msg.take().expect("UNREACHABLE");
+
+                // If the current task is in the set, remove it.
+                if let Some(key) = self.opt_key.take() {
+                    self.sink.send_wakers.remove(key);
+                }
+
+                if let Err(msg) = self.sink.try_log(msg) {
+                    self.msg = Some(msg);
+
+                    // Insert this log operation.
+                    self.opt_key = Some(self.sink.send_wakers.insert(cx));
+
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            }
+        }
+
+        impl<T, N> Drop for Log<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            fn drop(&mut self) {
+                // If the current task is still in the set, that means it is being cancelled now.
+                if let Some(key) = self.opt_key {
+                    self.sink.send_wakers.cancel(key);
+                }
+            }
+        }
+
+        Log {
+            sink: self,
+            msg: Some(msg),
+            opt_key: None,
+        }
+        .await
+    }
+
+    /// Attempts to log a message without waiting
+    ///
+    /// With the drop policies this always succeeds (the message may be discarded though). With
+    /// `Overflow::Block` this returns the message back if the sink is full
+    pub fn try_log(&self, msg: T) -> Result<(), T> {
+        if self.ring.is_full() {
+            match self.overflow {
+                Overflow::Block => return Err(msg),
+
+                Overflow::DropNewest => {
+                    drop(msg);
+                    self.dropped.set(self.dropped.get().wrapping_add(1));
+                    return Ok(());
+                }
+
+                Overflow::DropOldest => {
+                    // NOTE don't notify a producer; the slot is immediately reused
+                    drop(self.ring.pop());
+                    self.dropped.set(self.dropped.get().wrapping_add(1));
+                }
+            }
+        }
+
+        // NOTE there's room for the message at this point
+        self.ring.push(msg).ok().expect("UNREACHABLE");
+
+        // notify a receiver
+        self.recv_wakers.notify_one();
+        unsafe { crate::signal_event_ready() }
+        Ok(())
+    }
+
+    /// Receives the oldest message in the sink
+    pub async fn recv(&self) -> T {
+        struct Recv<'a, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            sink: &'a Sink<T, N>,
+            opt_key: Option<usize>,
+        }
+
+        impl<T, N> Future for Recv<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            type Output = T;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+                // If the current task is in the set, remove it.
+                if let Some(key) = self.opt_key.take() {
+                    self.sink.recv_wakers.remove(key);
+                }
+
+                // Try receiving a message.
+                if let Some(msg) = self.sink.try_recv() {
+                    Poll::Ready(msg)
+                } else {
+                    // Insert this receive operation.
+                    self.opt_key = Some(self.sink.recv_wakers.insert(cx));
+                    Poll::Pending
+                }
+            }
+        }
+
+        impl<T, N> Drop for Recv<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            fn drop(&mut self) {
+                // If the current task is still in the set, that means it is being cancelled now.
+                if let Some(key) = self.opt_key {
+                    self.sink.recv_wakers.cancel(key);
+                }
+            }
+        }
+
+        Recv {
+            sink: self,
+            opt_key: None,
+        }
+        .await
+    }
+
+    /// Attempts to receive the oldest message in the sink
+    ///
+    /// Returns `None` if the sink is currently empty
+    pub fn try_recv(&self) -> Option<T> {
+        let msg = self.ring.pop()?;
+        // notify a blocked producer
+        self.send_wakers.notify_one();
+        unsafe { crate::signal_event_ready() }
+        Some(msg)
+    }
+}
@@ -0,0 +1,220 @@
+//! Multi-producer single-consumer channel
+
+// NOTE waker logic is based on async-std v1.5.0
+
+use core::{
+    cell::Cell,
+    future::Future,
+    marker::Unpin,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use generic_array::ArrayLength;
+
+use super::{ring::Ring, waker_set::WakerSet};
+
+/// MPSC channel
+///
+/// Unlike `Channel`, only one task can receive from this channel so a single waker slot is used
+/// to track the receiver. Use `split` to get the sending and receiving endpoints
+pub struct Mpsc<T, N>
+where
+    N: ArrayLength<T>,
+{
+    ring: Ring<T, N>,
+    send_wakers: WakerSet,
+    recv_waker: Cell<Option<Waker>>,
+}
+
+impl<T, N> Mpsc<T, N>
+where
+    N: ArrayLength<T>,
+{
+    /// Creates a new fixed capacity channel
+    pub const fn new() -> Self {
+        Self {
+            ring: Ring::new(),
+            send_wakers: WakerSet::new(),
+            recv_waker: Cell::new(None),
+        }
+    }
+
+    /// Splits the channel into its sending and receiving endpoints
+    ///
+    /// The `Sender` can be cloned to get more producers
+    pub fn split(&mut self) -> (Sender<'_, T, N>, Receiver<'_, T, N>) {
+        let channel = &*self;
+        (Sender { channel }, Receiver { channel })
+    }
+
+    fn try_recv(&self) -> Option<T> {
+        let val = self.ring.pop()?;
+        // notify a sender
+        self.send_wakers.notify_one();
+        unsafe { crate::signal_event_ready() }
+        Some(val)
+    }
+
+    fn try_send(&self, val: T) -> Result<(), T> {
+        self.ring.push(val)?;
+        // notify the receiver
+        if let Some(waker) = self.recv_waker.take() {
+            waker.wake();
+        }
+        unsafe { crate::signal_event_ready() }
+        Ok(())
+    }
+}
+
+/// Sending endpoint of a MPSC channel
+pub struct Sender<'a, T, N>
+where
+    N: ArrayLength<T>,
+{
+    channel: &'a Mpsc<T, N>,
+}
+
+impl<T, N> Clone for Sender<'_, T, N>
+where
+    N: ArrayLength<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel,
+        }
+    }
+}
+
+impl<'a, T, N> Sender<'a, T, N>
+where
+    N: ArrayLength<T>,
+{
+    /// Sends a message into the channel
+    pub async fn send(&self, val: T) {
+        struct Send<'a, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            channel: &'a Mpsc<T, N>,
+            msg: Option<T>,
+            opt_key: Option<usize>,
+        }
+
+        impl<T, N> Unpin for Send<'_, T, N> where N: ArrayLength<T> {}
+
+        impl<T, N> Future for Send<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                let msg = self.msg.take().expect("UNREACHABLE");
+
+                // If the current task is in the set, remove it.
+                if let Some(key) = self.opt_key.take() {
+                    self.channel.send_wakers.remove(key);
+                }
+
+                if let Err(msg) = self.channel.try_send(msg) {
+                    self.msg = Some(msg);
+
+                    // Insert this send operation.
+                    self.opt_key = Some(self.channel.send_wakers.insert(cx));
+
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            }
+        }
+
+        impl<T, N> Drop for Send<'_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            fn drop(&mut self) {
+                // If the current task is still in the set, that means it is being cancelled now.
+                if let Some(key) = self.opt_key {
+                    self.channel.send_wakers.cancel(key);
+                }
+            }
+        }
+
+        Send {
+            channel: self.channel,
+            msg: Some(val),
+            opt_key: None,
+        }
+        .await
+    }
+
+    /// Attempts to send a message into the channel
+    ///
+    /// Returns an error if the channel buffer is currently full
+    pub fn try_send(&self, val: T) -> Result<(), T> {
+        self.channel.try_send(val)
+    }
+}
+
+/// Receiving endpoint of a MPSC channel
+pub struct Receiver<'a, T, N>
+where
+    N: ArrayLength<T>,
+{
+    channel: &'a Mpsc<T, N>,
+}
+
+impl<'a, T, N> Receiver<'a, T, N>
+where
+    N: ArrayLength<T>,
+{
+    /// Receives a message from the channel
+    pub async fn recv(&mut self) -> T {
+        struct Recv<'r, 'a, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            receiver: &'r mut Receiver<'a, T, N>,
+        }
+
+        impl<T, N> Future for Recv<'_, '_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            type Output = T;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+                let channel = self.receiver.channel;
+
+                if let Some(msg) = channel.try_recv() {
+                    Poll::Ready(msg)
+                } else {
+                    // there's a single receiver so we can overwrite any previous waker
+                    channel.recv_waker.set(Some(cx.waker().clone()));
+                    Poll::Pending
+                }
+            }
+        }
+
+        impl<T, N> Drop for Recv<'_, '_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            fn drop(&mut self) {
+                // cancelled or done; either way there's no one to wake up anymore
+                drop(self.receiver.channel.recv_waker.take());
+            }
+        }
+
+        Recv { receiver: self }.await
+    }
+
+    /// Attempts to receive a message from the channel
+    ///
+    /// Returns None if the channel is currently empty
+    pub fn try_recv(&mut self) -> Option<T> {
+        self.channel.try_recv()
+    }
+}
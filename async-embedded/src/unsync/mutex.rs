@@ -11,17 +11,43 @@ use core::{
 use super::waker_set::WakerSet;
 
 /// A mutual exclusion primitive for protecting shared data
+///
+/// The mutex comes in two flavors, picked at construction time:
+///
+/// - unfair (`new`): releasing the lock wakes one waiting task but the lock is up for grabs;
+///   whichever task gets polled first -- possibly one that was not waiting at all -- takes it.
+///   This is cheap and maximizes throughput but a task that keeps re-locking the mutex can
+///   starve the others
+///
+/// - fair (`new_fair`): releasing the lock hands it over to the task that has been waiting the
+///   longest; tasks acquire the lock in the order they started waiting (FIFO). The lock stays
+///   reserved until the woken task is polled, so other tasks cannot use the mutex in the
+///   meantime, and finding the oldest waiter costs a scan of the waiters
 pub struct Mutex<T> {
     locked: Cell<bool>,
+    fair: bool,
+    // (fair mode) key of the waiter the lock has been handed over to
+    handoff: Cell<Option<usize>>,
     value: UnsafeCell<T>,
     wakers: WakerSet,
 }
 
 impl<T> Mutex<T> {
-    /// Creates a new mutex
+    /// Creates a new unfair mutex
     pub const fn new(t: T) -> Self {
+        Self::with_fairness(t, false)
+    }
+
+    /// Creates a new fair (FIFO) mutex
+    pub const fn new_fair(t: T) -> Self {
+        Self::with_fairness(t, true)
+    }
+
+    const fn with_fairness(t: T, fair: bool) -> Self {
         Self {
             locked: Cell::new(false),
+            fair,
+            handoff: Cell::new(None),
             wakers: WakerSet::new(),
             value: UnsafeCell::new(t),
         }
@@ -40,6 +66,10 @@ impl<T> Mutex<T> {
             type Output = MutexGuard<'a, T>;
 
             fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                if self.mutex.fair {
+                    return self.poll_fair(cx);
+                }
+
                 // If the current task is in the set, remove it.
                 if let Some(key) = self.opt_key.take() {
                     self.mutex.wakers.remove(key);
@@ -58,11 +88,53 @@ impl<T> Mutex<T> {
             }
         }
 
+        impl<'a, T> Lock<'a, T> {
+            fn poll_fair(&mut self, cx: &mut Context<'_>) -> Poll<MutexGuard<'a, T>> {
+                if let Some(key) = self.opt_key {
+                    if self.mutex.handoff.get() == Some(key) {
+                        // the lock was handed over to us; it's still marked as locked
+                        self.mutex.handoff.set(None);
+                        self.mutex.wakers.remove(key);
+                        self.opt_key = None;
+
+                        Poll::Ready(MutexGuard(self.mutex))
+                    } else {
+                        // spurious poll; keep our place in the queue
+                        self.mutex.wakers.update(key, cx);
+
+                        Poll::Pending
+                    }
+                } else {
+                    match self.mutex.try_lock() {
+                        Some(guard) => Poll::Ready(guard),
+                        None => {
+                            self.opt_key = Some(self.mutex.wakers.insert(cx));
+
+                            Poll::Pending
+                        }
+                    }
+                }
+            }
+        }
+
         impl<T> Drop for Lock<'_, T> {
             fn drop(&mut self) {
                 // If the current task is still in the set, that means it is being cancelled now.
                 if let Some(key) = self.opt_key {
-                    self.mutex.wakers.cancel(key);
+                    if self.mutex.fair {
+                        self.mutex.wakers.remove(key);
+
+                        if self.mutex.handoff.get() == Some(key) {
+                            // the lock was handed over to us; pass it on to the next waiter
+                            self.mutex.handoff.set(None);
+                            self.mutex.unlock();
+                            unsafe {
+                                crate::signal_event_ready();
+                            }
+                        }
+                    } else {
+                        self.mutex.wakers.cancel(key);
+                    }
                 }
             }
         }
@@ -75,6 +147,8 @@ impl<T> Mutex<T> {
     }
 
     /// Attempts to acquire the lock
+    ///
+    /// NOTE a fair mutex that has been handed over to a waiting task is reported as locked
     pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
         if !self.locked.get() {
             self.locked.set(true);
@@ -83,6 +157,31 @@ impl<T> Mutex<T> {
             None
         }
     }
+
+    /// Returns a mutable reference to the protected data
+    ///
+    /// No locking is needed: the mutable borrow proves that no other task can access the mutex
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.value.get() }
+    }
+
+    /// Consumes the mutex and returns the protected data
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    fn unlock(&self) {
+        if self.fair {
+            match self.wakers.notify_oldest() {
+                // NOTE the mutex stays locked
+                Some(key) => self.handoff.set(Some(key)),
+                None => self.locked.set(false),
+            }
+        } else {
+            self.locked.set(false);
+            self.wakers.notify_any();
+        }
+    }
 }
 
 /// A guard that releases the lock when dropped
@@ -90,8 +189,7 @@ pub struct MutexGuard<'a, T>(&'a Mutex<T>);
 
 impl<T> Drop for MutexGuard<'_, T> {
     fn drop(&mut self) {
-        self.0.locked.set(false);
-        self.0.wakers.notify_any();
+        self.0.unlock();
         unsafe { crate::signal_event_ready(); }
     }
 }
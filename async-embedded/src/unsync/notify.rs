@@ -0,0 +1,83 @@
+// NOTE waker logic is based on async-std v1.5.0
+
+use core::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::waker_set::WakerSet;
+
+/// Event notification: lets a task wait until another task signals that something happened
+///
+/// A `notify` that happens while no task is waiting is not lost: it's stored as a single permit
+/// that the next `notified` call consumes. Several `notify` calls in a row store a single permit
+pub struct Notify {
+    permit: Cell<bool>,
+    wakers: WakerSet,
+}
+
+impl Notify {
+    /// Creates a new `Notify` with no stored permit
+    pub const fn new() -> Self {
+        Self {
+            permit: Cell::new(false),
+            wakers: WakerSet::new(),
+        }
+    }
+
+    /// Wakes up one task waiting on `notified`, or stores a permit if no task is waiting
+    pub fn notify(&self) {
+        self.permit.set(true);
+        if self.wakers.notify_any() {
+            unsafe { crate::signal_event_ready() }
+        }
+    }
+
+    /// Waits until `notify` is called
+    ///
+    /// Returns immediately, consuming the permit, if `notify` was called since the last time
+    /// this operation completed
+    pub async fn notified(&self) {
+        struct Notified<'a> {
+            notify: &'a Notify,
+            opt_key: Option<usize>,
+        }
+
+        impl Future for Notified<'_> {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                // If the current task is in the set, remove it.
+                if let Some(key) = self.opt_key.take() {
+                    self.notify.wakers.remove(key);
+                }
+
+                if self.notify.permit.replace(false) {
+                    Poll::Ready(())
+                } else {
+                    // Insert this operation.
+                    self.opt_key = Some(self.notify.wakers.insert(cx));
+
+                    Poll::Pending
+                }
+            }
+        }
+
+        impl Drop for Notified<'_> {
+            fn drop(&mut self) {
+                // If the current task is still in the set, that means it is being cancelled now.
+                if let Some(key) = self.opt_key {
+                    self.notify.wakers.cancel(key);
+                }
+            }
+        }
+
+        Notified {
+            notify: self,
+            opt_key: None,
+        }
+        .await
+    }
+}
@@ -0,0 +1,170 @@
+//! Single-value channel
+
+use core::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+/// Oneshot channel: transfers a single value from one task to another
+///
+/// The channel can be reused: once the `Receiver` returned by `split` has been dropped, `split`
+/// can be called again. A `Sender` that outlives its `Receiver` is disconnected by the next
+/// `split`: it reports `is_canceled` and its `send` drops the value
+pub struct Oneshot<T> {
+    value: Cell<Option<T>>,
+    waker: Cell<Option<Waker>>,
+    // endpoints that are alive
+    sender: Cell<bool>,
+    receiver: Cell<bool>,
+    // incremented by `split`; tells the current `Sender` apart from stale ones
+    generation: Cell<u32>,
+}
+
+/// The `Sender` was dropped without sending a value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Canceled;
+
+impl<T> Oneshot<T> {
+    /// Creates a new oneshot channel
+    pub const fn new() -> Self {
+        Self {
+            value: Cell::new(None),
+            waker: Cell::new(None),
+            sender: Cell::new(false),
+            receiver: Cell::new(false),
+            generation: Cell::new(0),
+        }
+    }
+
+    /// Splits the channel into its sending and receiving endpoints
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the `Receiver` returned by a previous `split` call is still alive
+    pub fn split(&self) -> (Sender<'_, T>, Receiver<'_, T>) {
+        assert!(!self.receiver.get(), "oneshot channel is in use");
+
+        // NOTE a `Sender` of a previous `split` may still be alive (e.g. its `Receiver` was
+        // dropped by a cancelled `rpc::Client::call`); bumping the generation disconnects it
+        let generation = self.generation.get().wrapping_add(1);
+        self.generation.set(generation);
+        self.sender.set(true);
+        self.receiver.set(true);
+
+        (
+            Sender {
+                channel: self,
+                generation,
+            },
+            Receiver { channel: self },
+        )
+    }
+
+    fn wake(&self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Sending endpoint of a oneshot channel
+pub struct Sender<'a, T> {
+    channel: &'a Oneshot<T>,
+    generation: u32,
+}
+
+impl<T> Sender<'_, T> {
+    /// Sends `val` to the receiver
+    ///
+    /// If the `Receiver` has already been dropped `val` is dropped
+    pub fn send(self, val: T) {
+        if !self.is_canceled() {
+            self.channel.value.set(Some(val));
+        }
+        // `Drop` wakes up the receiver
+    }
+
+    /// Returns `true` if the `Receiver` has been dropped
+    pub fn is_canceled(&self) -> bool {
+        self.is_stale() || !self.channel.receiver.get()
+    }
+
+    // the channel has been `split` again since this endpoint was created
+    fn is_stale(&self) -> bool {
+        self.generation != self.channel.generation.get()
+    }
+}
+
+impl<T> Drop for Sender<'_, T> {
+    fn drop(&mut self) {
+        if self.is_stale() {
+            // the endpoints of the current `split` are none of our business
+            return;
+        }
+
+        self.channel.sender.set(false);
+        // the receiver needs to observe either the value or the cancellation
+        self.channel.wake();
+        unsafe { crate::signal_event_ready() }
+    }
+}
+
+/// Receiving endpoint of a oneshot channel
+pub struct Receiver<'a, T> {
+    channel: &'a Oneshot<T>,
+}
+
+impl<T> Receiver<'_, T> {
+    /// Waits for the value
+    ///
+    /// Returns an error if the `Sender` is dropped without sending a value
+    pub async fn recv(self) -> Result<T, Canceled> {
+        struct Recv<'r, 'a, T> {
+            receiver: &'r Receiver<'a, T>,
+        }
+
+        impl<T> Future for Recv<'_, '_, T> {
+            type Output = Result<T, Canceled>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<T, Canceled>> {
+                let channel = self.receiver.channel;
+
+                if let Some(val) = channel.value.take() {
+                    Poll::Ready(Ok(val))
+                } else if !channel.sender.get() {
+                    Poll::Ready(Err(Canceled))
+                } else {
+                    // there's a single receiver so we can overwrite any previous waker
+                    channel.waker.set(Some(cx.waker().clone()));
+                    Poll::Pending
+                }
+            }
+        }
+
+        Recv { receiver: &self }.await
+    }
+
+    /// Attempts to receive the value
+    ///
+    /// Returns `Ok(None)` if the value has not been sent yet
+    pub fn try_recv(&mut self) -> Result<Option<T>, Canceled> {
+        if let Some(val) = self.channel.value.take() {
+            Ok(Some(val))
+        } else if !self.channel.sender.get() {
+            Err(Canceled)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T> Drop for Receiver<'_, T> {
+    fn drop(&mut self) {
+        self.channel.receiver.set(false);
+        // no one is going to receive these
+        drop(self.channel.value.take());
+        drop(self.channel.waker.take());
+    }
+}
@@ -0,0 +1,106 @@
+// A `!Sync` fixed capacity FIFO: the ring buffer behind the channels. It only stores the
+// messages; the channels built on top of it manage the wakers
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    mem::MaybeUninit,
+    ptr,
+};
+
+use generic_array::{typenum::Unsigned, ArrayLength, GenericArray};
+
+pub struct Ring<T, N>
+where
+    N: ArrayLength<T>,
+{
+    buffer: UnsafeCell<MaybeUninit<GenericArray<T, N>>>,
+    // NOTE the cursors wrap around at `2 * N`, not at `usize::MAX`, so that `cursor % N` maps
+    // consecutive cursors to consecutive slots for any `N`, not only for powers of two. The extra
+    // lap tells a full ring (`write - read == N`) apart from an empty one (`write == read`)
+    read: Cell<usize>,
+    write: Cell<usize>,
+}
+
+impl<T, N> Ring<T, N>
+where
+    N: ArrayLength<T>,
+{
+    pub const fn new() -> Self {
+        Self {
+            buffer: UnsafeCell::new(MaybeUninit::uninit()),
+            read: Cell::new(0),
+            write: Cell::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        let (read, write) = (self.read.get(), self.write.get());
+        if write >= read {
+            write - read
+        } else {
+            write + 2 * N::USIZE - read
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == N::USIZE
+    }
+
+    // Appends `val`; returns it back if the ring is full
+    pub fn push(&self, val: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(val);
+        }
+
+        let write = self.write.get();
+        unsafe { self.slot(write).write(val) }
+        self.write.set(advance::<T, N>(write));
+        Ok(())
+    }
+
+    // Removes the oldest message
+    pub fn pop(&self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let read = self.read.get();
+        let val = unsafe { self.slot(read).read() };
+        self.read.set(advance::<T, N>(read));
+        Some(val)
+    }
+
+    fn slot(&self, cursor: usize) -> *mut T {
+        unsafe { (self.buffer.get() as *mut T).add(cursor % N::USIZE) }
+    }
+}
+
+impl<T, N> Drop for Ring<T, N>
+where
+    N: ArrayLength<T>,
+{
+    fn drop(&mut self) {
+        // drop the messages that were never received; the slots in `read..write` are initialized
+        let mut read = self.read.get();
+        while read != self.write.get() {
+            unsafe { ptr::drop_in_place(self.slot(read)) }
+            read = advance::<T, N>(read);
+        }
+    }
+}
+
+fn advance<T, N>(cursor: usize) -> usize
+where
+    N: ArrayLength<T>,
+{
+    let next = cursor + 1;
+    if next == 2 * N::USIZE {
+        0
+    } else {
+        next
+    }
+}
@@ -0,0 +1,92 @@
+//! Request / response communication between tasks
+//!
+//! A `Server` task receives requests from any number of `Client`s through a `Mpsc` channel; each
+//! request carries the `oneshot::Sender` the server must use to reply
+//!
+//! ``` ignore
+//! static mut Q: Mpsc<Request<'static, u8, u16>, U4> = Mpsc::new();
+//! static mut R: Oneshot<u16> = Oneshot::new();
+//!
+//! let (tx, rx) = Q.split();
+//! let mut server = Server::new(rx);
+//! let mut client = Client::new(tx, &R);
+//!
+//! // server task
+//! let (req, reply) = server.recv().await;
+//! reply.send(u16::from(req) * 2);
+//!
+//! // client task
+//! let resp = client.call(21).await?;
+//! ```
+
+use generic_array::ArrayLength;
+
+use super::{
+    mpsc,
+    oneshot::{self, Canceled, Oneshot},
+};
+
+/// A request together with the endpoint the response must be sent to
+pub type Request<'a, Req, Resp> = (Req, oneshot::Sender<'a, Resp>);
+
+/// Endpoint that services requests
+pub struct Server<'a, Req, Resp, N>
+where
+    N: ArrayLength<Request<'a, Req, Resp>>,
+{
+    requests: mpsc::Receiver<'a, Request<'a, Req, Resp>, N>,
+}
+
+impl<'a, Req, Resp, N> Server<'a, Req, Resp, N>
+where
+    N: ArrayLength<Request<'a, Req, Resp>>,
+{
+    /// Creates a server from the receiving endpoint of the request queue
+    pub fn new(requests: mpsc::Receiver<'a, Request<'a, Req, Resp>, N>) -> Self {
+        Self { requests }
+    }
+
+    /// Waits for the next request
+    ///
+    /// The response must be sent using the returned `oneshot::Sender`. Dropping the `Sender`
+    /// without sending a response makes the client's `call` return `Err(Canceled)`
+    pub async fn recv(&mut self) -> Request<'a, Req, Resp> {
+        self.requests.recv().await
+    }
+}
+
+/// Endpoint that issues requests
+pub struct Client<'a, Req, Resp, N>
+where
+    N: ArrayLength<Request<'a, Req, Resp>>,
+{
+    requests: mpsc::Sender<'a, Request<'a, Req, Resp>, N>,
+    response: &'a Oneshot<Resp>,
+}
+
+impl<'a, Req, Resp, N> Client<'a, Req, Resp, N>
+where
+    N: ArrayLength<Request<'a, Req, Resp>>,
+{
+    /// Creates a client from a sending endpoint of the request queue
+    ///
+    /// Each client needs its own `response` channel; it's reused across `call`s
+    pub fn new(
+        requests: mpsc::Sender<'a, Request<'a, Req, Resp>, N>,
+        response: &'a Oneshot<Resp>,
+    ) -> Self {
+        Self { requests, response }
+    }
+
+    /// Sends a request to the server and waits for its response
+    ///
+    /// Returns an error if the server dropped the request without responding
+    ///
+    /// This is cancel safe: if the future is dropped the server may still handle the request but
+    /// its response is discarded; it's never mistaken for the response to a later `call`
+    pub async fn call(&mut self, req: Req) -> Result<Resp, Canceled> {
+        let (tx, rx) = self.response.split();
+        self.requests.send((req, tx)).await;
+        rx.recv().await
+    }
+}
@@ -0,0 +1,108 @@
+// NOTE waker logic is based on async-std v1.5.0
+
+use core::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::waker_set::WakerSet;
+
+/// A counting semaphore: limits how many tasks can run a section of code at the same time
+///
+/// Unlike `Mutex` it doesn't protect any data. Releasing a permit wakes one waiting task but, like
+/// the unfair `Mutex`, the permit is up for grabs
+pub struct Semaphore {
+    permits: Cell<usize>,
+    wakers: WakerSet,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the given number of permits
+    ///
+    /// NOTE with zero permits `acquire` never completes
+    pub const fn new(permits: usize) -> Self {
+        Self {
+            permits: Cell::new(permits),
+            wakers: WakerSet::new(),
+        }
+    }
+
+    /// Returns the number of permits that can currently be acquired
+    pub fn available(&self) -> usize {
+        self.permits.get()
+    }
+
+    /// Acquires a permit, waiting until one is released if none is available
+    ///
+    /// Returns a permit that is released when dropped
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        struct Acquire<'a> {
+            semaphore: &'a Semaphore,
+            opt_key: Option<usize>,
+        }
+
+        impl<'a> Future for Acquire<'a> {
+            type Output = SemaphorePermit<'a>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                // If the current task is in the set, remove it.
+                if let Some(key) = self.opt_key.take() {
+                    self.semaphore.wakers.remove(key);
+                }
+
+                // Try acquiring a permit.
+                match self.semaphore.try_acquire() {
+                    Some(permit) => Poll::Ready(permit),
+                    None => {
+                        // Insert this acquire operation.
+                        self.opt_key = Some(self.semaphore.wakers.insert(cx));
+
+                        Poll::Pending
+                    }
+                }
+            }
+        }
+
+        impl Drop for Acquire<'_> {
+            fn drop(&mut self) {
+                // If the current task is still in the set, that means it is being cancelled now.
+                if let Some(key) = self.opt_key {
+                    self.semaphore.wakers.cancel(key);
+                }
+            }
+        }
+
+        Acquire {
+            semaphore: self,
+            opt_key: None,
+        }
+        .await
+    }
+
+    /// Attempts to acquire a permit
+    ///
+    /// Returns `None` if all the permits are in use
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permits = self.permits.get();
+        if permits != 0 {
+            self.permits.set(permits - 1);
+            Some(SemaphorePermit(self))
+        } else {
+            None
+        }
+    }
+}
+
+/// A permit that is released when dropped
+pub struct SemaphorePermit<'a>(&'a Semaphore);
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        let semaphore = self.0;
+        semaphore.permits.set(semaphore.permits.get() + 1);
+        semaphore.wakers.notify_one();
+        unsafe { crate::signal_event_ready() }
+    }
+}
@@ -0,0 +1,198 @@
+//! Single-producer single-consumer channel
+
+use core::{
+    cell::Cell,
+    future::Future,
+    marker::Unpin,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use generic_array::ArrayLength;
+
+use super::ring::Ring;
+
+/// SPSC channel
+///
+/// Only one task sends and only one task receives so, unlike `Channel` and `Mpsc`, a single
+/// waker slot is used for each direction. Use `split` to get the sending and receiving endpoints
+pub struct Channel<T, N>
+where
+    N: ArrayLength<T>,
+{
+    ring: Ring<T, N>,
+    send_waker: Cell<Option<Waker>>,
+    recv_waker: Cell<Option<Waker>>,
+}
+
+impl<T, N> Channel<T, N>
+where
+    N: ArrayLength<T>,
+{
+    /// Creates a new fixed capacity channel
+    pub const fn new() -> Self {
+        Self {
+            ring: Ring::new(),
+            send_waker: Cell::new(None),
+            recv_waker: Cell::new(None),
+        }
+    }
+
+    /// Splits the channel into its sending and receiving endpoints
+    pub fn split(&mut self) -> (Producer<'_, T, N>, Consumer<'_, T, N>) {
+        let channel = &*self;
+        (Producer { channel }, Consumer { channel })
+    }
+
+    fn try_recv(&self) -> Option<T> {
+        let val = self.ring.pop()?;
+        // notify the sender
+        wake(&self.send_waker);
+        Some(val)
+    }
+
+    fn try_send(&self, val: T) -> Result<(), T> {
+        self.ring.push(val)?;
+        // notify the receiver
+        wake(&self.recv_waker);
+        Ok(())
+    }
+}
+
+fn wake(slot: &Cell<Option<Waker>>) {
+    if let Some(waker) = slot.take() {
+        waker.wake();
+        unsafe { crate::signal_event_ready() }
+    }
+}
+
+/// Sending endpoint of a SPSC channel
+pub struct Producer<'a, T, N>
+where
+    N: ArrayLength<T>,
+{
+    channel: &'a Channel<T, N>,
+}
+
+impl<'a, T, N> Producer<'a, T, N>
+where
+    N: ArrayLength<T>,
+{
+    /// Sends a message into the channel
+    pub async fn send(&mut self, val: T) {
+        struct Send<'r, 'a, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            producer: &'r mut Producer<'a, T, N>,
+            msg: Option<T>,
+        }
+
+        impl<T, N> Unpin for Send<'_, '_, T, N> where N: ArrayLength<T> {}
+
+        impl<T, N> Future for Send<'_, '_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                let msg = self.msg.take().expect("UNREACHABLE");
+                let channel = self.producer.channel;
+
+                if let Err(msg) = channel.try_send(msg) {
+                    self.msg = Some(msg);
+                    // there's a single sender so we can overwrite any previous waker
+                    channel.send_waker.set(Some(cx.waker().clone()));
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            }
+        }
+
+        impl<T, N> Drop for Send<'_, '_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            fn drop(&mut self) {
+                // cancelled or done; either way there's no one to wake up anymore
+                drop(self.producer.channel.send_waker.take());
+            }
+        }
+
+        Send {
+            producer: self,
+            msg: Some(val),
+        }
+        .await
+    }
+
+    /// Attempts to send a message into the channel
+    ///
+    /// Returns an error if the channel buffer is currently full
+    pub fn try_send(&mut self, val: T) -> Result<(), T> {
+        self.channel.try_send(val)
+    }
+}
+
+/// Receiving endpoint of a SPSC channel
+pub struct Consumer<'a, T, N>
+where
+    N: ArrayLength<T>,
+{
+    channel: &'a Channel<T, N>,
+}
+
+impl<'a, T, N> Consumer<'a, T, N>
+where
+    N: ArrayLength<T>,
+{
+    /// Receives a message from the channel
+    pub async fn recv(&mut self) -> T {
+        struct Recv<'r, 'a, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            consumer: &'r mut Consumer<'a, T, N>,
+        }
+
+        impl<T, N> Future for Recv<'_, '_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            type Output = T;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+                let channel = self.consumer.channel;
+
+                if let Some(msg) = channel.try_recv() {
+                    Poll::Ready(msg)
+                } else {
+                    // there's a single receiver so we can overwrite any previous waker
+                    channel.recv_waker.set(Some(cx.waker().clone()));
+                    Poll::Pending
+                }
+            }
+        }
+
+        impl<T, N> Drop for Recv<'_, '_, T, N>
+        where
+            N: ArrayLength<T>,
+        {
+            fn drop(&mut self) {
+                // cancelled or done; either way there's no one to wake up anymore
+                drop(self.consumer.channel.recv_waker.take());
+            }
+        }
+
+        Recv { consumer: self }.await
+    }
+
+    /// Attempts to receive a message from the channel
+    ///
+    /// Returns None if the channel is currently empty
+    pub fn try_recv(&mut self) -> Option<T> {
+        self.channel.try_recv()
+    }
+}
@@ -36,6 +36,11 @@ impl WakerSet {
         unsafe { (*self.inner.get()).notify_one() }
     }
 
+    pub fn notify_all(&self) -> bool {
+        // NOTE(unsafe) single-threaded context; OK as long as no references are returned
+        unsafe { (*self.inner.get()).notify_all() }
+    }
+
     pub fn insert(&self, cx: &Context<'_>) -> usize {
         // NOTE(unsafe) single-threaded context; OK as long as no references are returned
         unsafe { (*self.inner.get()).insert(cx) }
@@ -45,6 +50,16 @@ impl WakerSet {
         // NOTE(unsafe) single-threaded context; OK as long as no references are returned
         unsafe { (*self.inner.get()).remove(key) }
     }
+
+    pub fn notify_oldest(&self) -> Option<usize> {
+        // NOTE(unsafe) single-threaded context; OK as long as no references are returned
+        unsafe { (*self.inner.get()).notify_oldest() }
+    }
+
+    pub fn update(&self, key: usize, cx: &Context<'_>) {
+        // NOTE(unsafe) single-threaded context; OK as long as no references are returned
+        unsafe { (*self.inner.get()).update(key, cx) }
+    }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -53,14 +68,17 @@ enum Notify {
     Any,
     /// Notify one additional entry.
     One,
-    // Notify all entries.
-    // All,
+    /// Notify all entries.
+    All,
 }
 
 struct Inner {
     // NOTE the number of entries is capped at `NTASKS`
-    entries: Slab<Option<Waker>, crate::NTASKS>,
+    // NOTE each waker is tagged with its insertion order (see `notify_oldest`)
+    entries: Slab<(u32, Option<Waker>), crate::NTASKS>,
     notifiable: usize,
+    // insertion counter
+    next: u32,
 }
 
 impl Inner {
@@ -68,6 +86,7 @@ impl Inner {
         Self {
             entries: Slab(i::Slab::new()),
             notifiable: 0,
+            next: 0,
         }
     }
 
@@ -76,10 +95,10 @@ impl Inner {
     /// Returns `true` if another blocked operation from the set was notified.
     fn cancel(&mut self, key: usize) -> bool {
         match self.entries.remove(key) {
-            Some(_) => self.notifiable -= 1,
-            None => {
+            Some((_, Some(_))) => self.notifiable -= 1,
+            Some((_, None)) => {
                 // The operation was cancelled and notified so notify another operation instead.
-                for (_, opt_waker) in self.entries.iter_mut() {
+                for (_, (_, opt_waker)) in self.entries.iter_mut() {
                     // If there is no waker in this entry, that means it was already woken.
                     if let Some(w) = opt_waker.take() {
                         w.wake();
@@ -88,6 +107,7 @@ impl Inner {
                     }
                 }
             }
+            None => {}
         }
 
         false
@@ -107,13 +127,20 @@ impl Inner {
         self.notify(Notify::One)
     }
 
+    /// Notifies all blocked operations.
+    ///
+    /// Returns `true` if at least one operation was notified.
+    fn notify_all(&mut self) -> bool {
+        self.notify(Notify::All)
+    }
+
     /// Notifies blocked operations, either one or all of them.
     ///
     /// Returns `true` if at least one operation was notified.
     fn notify(&mut self, n: Notify) -> bool {
         let mut notified = false;
 
-        for (_, opt_waker) in self.entries.iter_mut() {
+        for (_, (_, opt_waker)) in self.entries.iter_mut() {
             // If there is no waker in this entry, that means it was already woken.
             if let Some(w) = opt_waker.take() {
                 w.wake();
@@ -133,16 +160,68 @@ impl Inner {
         notified
     }
 
+    /// Notifies the blocked operation that was inserted first and has not been notified yet.
+    ///
+    /// Returns the key of the notified operation, if any.
+    fn notify_oldest(&mut self) -> Option<usize> {
+        let next = self.next;
+        // (key, age)
+        let mut oldest: Option<(usize, u32)> = None;
+
+        for (key, (seq, opt_waker)) in self.entries.iter_mut() {
+            // If there is no waker in this entry, that means it was already woken.
+            if opt_waker.is_some() {
+                // NOTE wrapping distance to the counter; larger means older
+                let age = next.wrapping_sub(*seq);
+                match oldest {
+                    Some((_, max)) if max >= age => {}
+                    _ => oldest = Some((key, age)),
+                }
+            }
+        }
+
+        let (oldest, _) = oldest?;
+        for (key, (_, opt_waker)) in self.entries.iter_mut() {
+            if key == oldest {
+                if let Some(w) = opt_waker.take() {
+                    w.wake();
+                    self.notifiable -= 1;
+                }
+                break;
+            }
+        }
+
+        Some(oldest)
+    }
+
     fn insert(&mut self, cx: &Context<'_>) -> usize {
         let w = cx.waker().clone();
-        let key = self.entries.insert(Some(w)).expect("OOM");
+        let seq = self.next;
+        self.next = seq.wrapping_add(1);
+        let key = self.entries.insert((seq, Some(w))).expect("OOM");
         self.notifiable += 1;
         key
     }
 
+    /// Replaces the waker of an operation that has not been notified yet, keeping its place
+    /// in the insertion order.
+    fn update(&mut self, key: usize, cx: &Context<'_>) {
+        for (k, (_, opt_waker)) in self.entries.iter_mut() {
+            if k == key {
+                if let Some(w) = opt_waker {
+                    if !w.will_wake(cx.waker()) {
+                        *w = cx.waker().clone();
+                    }
+                }
+                return;
+            }
+        }
+    }
+
     /// Removes the waker of an operation.
     fn remove(&mut self, key: usize) {
-        if self.entries.remove(key).is_some() {
+        // NOTE a notified operation was already discounted when its waker was taken
+        if let Some((_, Some(_))) = self.entries.remove(key) {
             self.notifiable -= 1;
         }
     }
@@ -0,0 +1,45 @@
+//! The memory usage reported by `allocator_stats` as tasks are spawned
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use std::mem;
+
+use async_embedded::task::{self, AllocStats};
+
+// size of the state captured by each task
+const SIZE: usize = 64;
+
+#[test]
+fn spawn_uses_memory() {
+    let before = task::allocator_stats();
+    check(before);
+
+    let task = || {
+        let buf = [0u8; SIZE];
+        async move {
+            task::r#yield().await;
+            // NOTE `buf` is used after the `await` so it's stored in the future
+            assert_eq!(buf.len(), SIZE);
+        }
+    };
+    let size = mem::size_of_val(&task());
+
+    task::spawn(task());
+    let first = task::allocator_stats();
+    check(first);
+
+    task::spawn(task());
+    let second = task::allocator_stats();
+    check(second);
+
+    // each task uses at least the size of its future
+    assert!(size >= SIZE);
+    assert!(first.used - before.used >= size);
+    assert!(second.used - first.used >= size);
+}
+
+// the memory is either used or free
+fn check(stats: AllocStats) {
+    assert_eq!(stats.used + stats.free, stats.total);
+}
@@ -0,0 +1,31 @@
+//! The capacity of a `Channel` is independent of the number of tasks the executor can run
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{task, unsync::Channel};
+use typenum::consts::{U2, U64};
+
+#[test]
+fn capacity() {
+    let c = Channel::<u32, U2>::new();
+    assert_eq!(c.try_send(1), Ok(()));
+    assert_eq!(c.try_send(2), Ok(()));
+    assert_eq!(c.try_send(3), Err(3));
+    assert_eq!(task::run_until_stalled(c.recv()), Some(Some(1)));
+    assert_eq!(c.try_send(3), Ok(()));
+    assert_eq!(c.try_recv(), Some(2));
+    assert_eq!(c.try_recv(), Some(3));
+    assert_eq!(c.try_recv(), None);
+
+    // more slots than the executor has tasks
+    let c = Channel::<u32, U64>::new();
+    for i in 0..64 {
+        assert_eq!(c.try_send(i), Ok(()));
+    }
+    assert_eq!(c.try_send(64), Err(64));
+    for i in 0..64 {
+        assert_eq!(c.try_recv(), Some(i));
+    }
+    assert_eq!(c.try_recv(), None);
+}
@@ -0,0 +1,52 @@
+//! `Channel::close`: the buffered messages can still be received and the tasks waiting on the
+//! channel are woken up
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{task, unsync::Channel};
+use typenum::consts::U4;
+
+#[test]
+fn close() {
+    // a half-full channel is closed; the receiver still gets the buffered messages
+    let c = Channel::<u32, U4>::new();
+    c.try_send(1).unwrap();
+    c.try_send(2).unwrap();
+    c.close();
+    assert!(c.is_closed());
+    assert_eq!(c.try_send(3), Err(3));
+    let res = task::run_until_stalled(async {
+        (
+            c.send(3).await,
+            c.recv().await,
+            c.recv().await,
+            c.recv().await,
+        )
+    });
+    assert_eq!(res, Some((Err(3), Some(1), Some(2), None)));
+
+    // a receiver waiting on an empty channel
+    let c = Channel::<u32, U4>::new();
+    let res = task::run_until_stalled(task::join(c.recv(), async {
+        // let the receiver run first
+        task::r#yield().await;
+        c.close();
+    }));
+    assert_eq!(res, Some((None, ())));
+
+    // a sender waiting on a full channel
+    let c = Channel::<u32, U4>::new();
+    for i in 0..4 {
+        c.try_send(i).unwrap();
+    }
+    let res = task::run_until_stalled(task::join(c.send(4), async {
+        task::r#yield().await;
+        c.close();
+    }));
+    assert_eq!(res, Some((Err(4), ())));
+    for i in 0..4 {
+        assert_eq!(c.try_recv(), Some(i));
+    }
+    assert_eq!(c.try_recv(), None);
+}
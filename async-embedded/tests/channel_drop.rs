@@ -0,0 +1,56 @@
+//! Dropping a `Channel` drops the messages it still holds, also when its cursors have wrapped
+//! around
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use std::cell::Cell;
+
+use async_embedded::{task, unsync::Channel};
+use typenum::consts::U8;
+
+#[test]
+fn drop_buffered_messages() {
+    let drops = Cell::new(0);
+
+    let res = task::run_until_stalled(async {
+        let channel = Channel::<_, U8>::new();
+        for _ in 0..3 {
+            channel.send(Counted { drops: &drops }).await.ok();
+        }
+
+        // a received message is dropped by the receiver
+        drop(channel.recv().await);
+        drops.get()
+    });
+    assert_eq!(res, Some(1));
+    // the 2 messages that were never received are dropped with the channel
+    assert_eq!(drops.get(), 3);
+
+    // the destructor also handles a ring buffer whose cursors have wrapped around (capacity: 8)
+    drops.set(0);
+    let res = task::run_until_stalled(async {
+        let channel = Channel::<_, U8>::new();
+        for _ in 0..10 {
+            channel.send(Counted { drops: &drops }).await.ok();
+            drop(channel.recv().await);
+        }
+        for _ in 0..2 {
+            channel.send(Counted { drops: &drops }).await.ok();
+        }
+        drops.get()
+    });
+    assert_eq!(res, Some(10));
+    assert_eq!(drops.get(), 12);
+}
+
+// increments `drops` when dropped
+struct Counted<'a> {
+    drops: &'a Cell<usize>,
+}
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
@@ -0,0 +1,48 @@
+//! The `Channel` accessors and the message order, also across the wraparound of its cursors
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{task, unsync::Channel};
+use typenum::consts::{U3, U4};
+
+#[test]
+fn len_and_wraparound() {
+    let c = Channel::<u32, U4>::new();
+    assert_eq!(c.capacity(), 4);
+    assert!(c.is_empty() && !c.is_full());
+    for i in 0..4 {
+        assert_eq!(c.len(), i as usize);
+        assert_eq!(c.try_send(i), Ok(()));
+    }
+    assert!(c.is_full() && !c.is_empty());
+    assert_eq!(c.try_send(4), Err(4));
+
+    // a capacity that is not a power of two; the cursors wrap around at twice the capacity, every
+    // 6 messages
+    let c = Channel::<u32, U3>::new();
+    let res = task::run_until_stalled(async {
+        let mut next_in = 0;
+        let mut next_out = 0;
+        // each lap fills the channel and then drains two thirds of it; the cursors wrap around
+        // several times and at different points of the lap
+        for _ in 0..8 {
+            while !c.is_full() {
+                c.send(next_in).await.unwrap();
+                next_in += 1;
+            }
+            assert_eq!(c.len(), 3);
+            for _ in 0..2 {
+                // messages come out in FIFO order, none overwritten
+                assert_eq!(c.recv().await, Some(next_out));
+                next_out += 1;
+            }
+            assert_eq!(c.len(), 1);
+        }
+        assert_eq!(c.recv().await, Some(next_out));
+        next_out + 1
+    });
+    // 8 laps of 2 messages plus the last one
+    assert_eq!(res, Some(17));
+    assert!(c.is_empty() && !c.is_full());
+}
@@ -0,0 +1,43 @@
+//! The order of the events of an `EventBus` whose capacity is not a power of two, across several
+//! laps of its buffer, for a subscriber that keeps up and for one that falls behind
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{
+    task,
+    unsync::event_bus::{EventBus, Lagged},
+};
+use typenum::consts::U3;
+
+#[test]
+fn event_order() {
+    let bus = EventBus::<u32, U3>::new();
+
+    // a subscriber that keeps up
+    let mut sub = bus.subscribe();
+    for i in 0..10 {
+        bus.publish(i);
+        assert_eq!(sub.try_next(), Some(Ok(i)));
+    }
+    assert_eq!(sub.try_next(), None);
+
+    // a subscriber that falls 2 events behind the capacity
+    let mut sub = bus.subscribe();
+    for i in 10..15 {
+        bus.publish(i);
+    }
+    assert_eq!(sub.try_next(), Some(Err(Lagged(2))));
+    for i in 12..15 {
+        assert_eq!(sub.try_next(), Some(Ok(i)));
+    }
+    assert_eq!(sub.try_next(), None);
+
+    // a subscriber waiting for the next event is woken up by `publish`
+    let res = task::run_until_stalled(task::join(sub.next(), async {
+        // let the subscriber run first
+        task::r#yield().await;
+        bus.publish(15);
+    }));
+    assert_eq!(res, Some((Ok(15), ())));
+}
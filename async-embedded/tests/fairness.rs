@@ -0,0 +1,27 @@
+//! Tasks that `yield` take turns in the order they were spawned
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use core::cell::RefCell;
+
+use async_embedded::{task, unsync::Notify};
+
+#[test]
+fn yield_round_robin() {
+    let log: &'static RefCell<Vec<u8>> = Box::leak(Box::new(RefCell::new(Vec::new())));
+
+    for id in 0..3 {
+        task::spawn(async move {
+            for _ in 0..3 {
+                log.borrow_mut().push(id);
+                task::r#yield().await;
+            }
+        });
+    }
+
+    let never = Notify::new();
+    assert_eq!(task::run_until_stalled(never.notified()), None);
+
+    assert_eq!(*log.borrow(), [0, 1, 2, 0, 1, 2, 0, 1, 2]);
+}
@@ -0,0 +1,52 @@
+//! A task blocked on a `Mutex` that, once it gets the lock, is held back by a full `Channel`
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{
+    task,
+    unsync::{Channel, Mutex, Notify},
+};
+use typenum::consts::U2;
+
+#[test]
+fn mutex_handoff_and_channel_backpressure() {
+    let mutex: &'static Mutex<Vec<u32>> = Box::leak(Box::new(Mutex::new(Vec::new())));
+    let channel: &'static Channel<u32, U2> = Box::leak(Box::new(Channel::new()));
+
+    let guard = mutex.try_lock().unwrap();
+    task::spawn(async move {
+        mutex.lock().await.push(1);
+
+        for i in 0..4 {
+            channel.send(i).await.unwrap();
+        }
+        channel.close();
+    });
+
+    // the task waits for the lock
+    assert_eq!(stall(), None);
+    assert!(channel.is_empty());
+
+    // the task gets the lock and then fills the channel
+    drop(guard);
+    assert_eq!(stall(), None);
+    assert_eq!(*mutex.try_lock().unwrap(), [1]);
+    assert!(channel.is_full());
+
+    // the task resumes as messages are received
+    let received = task::run_until_stalled(async {
+        let mut received = vec![];
+        while let Some(val) = channel.recv().await {
+            received.push(val);
+        }
+        received
+    });
+    assert_eq!(received, Some(vec![0, 1, 2, 3]));
+}
+
+// Runs the tasks until none of them can make progress
+fn stall() -> Option<()> {
+    let never = Notify::new();
+    task::run_until_stalled(never.notified())
+}
@@ -0,0 +1,76 @@
+//! A full `Sink` under each overflow policy: which messages survive and whether the producer waits
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{
+    task,
+    unsync::log::{Overflow, Sink},
+};
+use typenum::consts::U4;
+
+#[test]
+fn overflow_policies() {
+    // the 2 oldest messages are overwritten
+    let sink: &'static Sink<u32, U4> = Box::leak(Box::new(Sink::new(Overflow::DropOldest)));
+    let res = task::run_until_stalled(async {
+        for i in 0..6 {
+            sink.log(i).await;
+        }
+        drain(sink)
+    });
+    assert_eq!(res, Some(vec![2, 3, 4, 5]));
+    assert_eq!(sink.dropped(), 2);
+
+    // the 2 newest messages are discarded
+    let sink: &'static Sink<u32, U4> = Box::leak(Box::new(Sink::new(Overflow::DropNewest)));
+    let res = task::run_until_stalled(async {
+        for i in 0..6 {
+            sink.log(i).await;
+        }
+        drain(sink)
+    });
+    assert_eq!(res, Some(vec![0, 1, 2, 3]));
+    assert_eq!(sink.dropped(), 2);
+
+    // the producer waits for the consumer; nothing is lost
+    let sink: &'static Sink<u32, U4> = Box::leak(Box::new(Sink::new(Overflow::Block)));
+    let producer = task::spawn(async move {
+        for i in 0..6 {
+            sink.log(i).await;
+        }
+    });
+
+    // the producer fills the sink and then waits
+    assert_eq!(task::run_until_stalled(ticks(2)), Some(()));
+    assert_eq!(sink.len(), 4);
+    assert_eq!(sink.try_log(6), Err(6));
+
+    let res = task::run_until_stalled(async {
+        let mut received = vec![];
+        for _ in 0..6 {
+            received.push(sink.recv().await);
+        }
+        producer.await;
+        received
+    });
+    assert_eq!(res, Some(vec![0, 1, 2, 3, 4, 5]));
+    assert_eq!(sink.dropped(), 0);
+    assert!(sink.is_empty());
+}
+
+// Takes all the messages out of the `sink`
+fn drain(sink: &Sink<u32, U4>) -> Vec<u32> {
+    let mut msgs = vec![];
+    while let Some(msg) = sink.try_recv() {
+        msgs.push(msg);
+    }
+    msgs
+}
+
+// Lets the other tasks run for `n` scans of the executor; there's no timer on the host
+async fn ticks(n: u32) {
+    for _ in 0..n {
+        task::r#yield().await;
+    }
+}
@@ -0,0 +1,27 @@
+//! Access to the data of a `Mutex` without locking it: `get_mut` and `into_inner`
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{task, unsync::Mutex};
+
+#[test]
+fn get_mut_and_into_inner() {
+    let mut m = Mutex::new([0; 4]);
+
+    // initialization: no task is running yet so we have exclusive access
+    for (i, x) in m.get_mut().iter_mut().enumerate() {
+        *x = i as u32;
+    }
+    // `get_mut` didn't lock the mutex
+    assert!(m.try_lock().is_some());
+
+    let res = task::run_until_stalled(async {
+        let data = *m.lock().await;
+
+        let local = Mutex::new(0);
+        *local.lock().await += 1;
+        (data, local.into_inner())
+    });
+    assert_eq!(res, Some(([0, 1, 2, 3], 1)));
+}
@@ -0,0 +1,48 @@
+//! The `Oneshot` channel: the value is sent before or after the receiver waits for it, or never
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{
+    task,
+    unsync::{oneshot::Canceled, Oneshot},
+};
+
+#[test]
+fn send_recv_cancel() {
+    let o = Oneshot::new();
+
+    let res = task::run_until_stalled(async {
+        // the value is already there when the receiver polls
+        let first = {
+            let (tx, rx) = o.split();
+            tx.send(1);
+            rx.recv().await
+        };
+
+        // the receiver waits for the value
+        let (second, ()) = {
+            let (tx, rx) = o.split();
+            task::join(rx.recv(), async {
+                // let the receiver run first
+                task::r#yield().await;
+                tx.send(2);
+            })
+            .await
+        };
+
+        // the sender goes away without sending
+        let (third, ()) = {
+            let (tx, rx) = o.split();
+            task::join(rx.recv(), async {
+                task::r#yield().await;
+                drop(tx);
+            })
+            .await
+        };
+
+        (first, second, third)
+    });
+
+    assert_eq!(res, Some((Ok(1), Ok(2), Err(Canceled))));
+}
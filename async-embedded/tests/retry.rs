@@ -0,0 +1,120 @@
+//! The delays `retry_until` waits between attempts, for each backoff policy, and its attempt cap
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use std::cell::{Cell, RefCell};
+
+use async_embedded::task::{self, Delay, RetryPolicy};
+
+#[test]
+fn backoff_and_attempt_cap() {
+    let delays = RefCell::new(vec![]);
+    let timer = FakeTimer { delays: &delays };
+
+    // every attempt fails; the error of the last one is returned
+    let attempts = Cell::new(0);
+    let res = task::run_until_stalled(task::retry_until(
+        RetryPolicy::constant(3, ms(10)),
+        timer,
+        || {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move { Err::<(), _>(attempt) }
+        },
+    ));
+    assert_eq!(res, Some(Err(3)));
+    // no delay after the last attempt
+    assert_eq!(delays.replace(vec![]), [ms(10), ms(10)]);
+
+    // the delay doubles up to `max`
+    attempts.set(0);
+    let res = task::run_until_stalled(task::retry_until(
+        RetryPolicy::exponential(7, ms(100), ms(1000)),
+        timer,
+        || {
+            attempts.set(attempts.get() + 1);
+            async { Err::<(), _>(()) }
+        },
+    ));
+    assert_eq!(res, Some(Err(())));
+    assert_eq!(attempts.get(), 7);
+    assert_eq!(
+        delays.replace(vec![]),
+        [ms(100), ms(200), ms(400), ms(800), ms(1000), ms(1000)]
+    );
+
+    // the first success ends the retries
+    attempts.set(0);
+    let res = task::run_until_stalled(task::retry_until(
+        RetryPolicy::exponential(7, ms(100), ms(1000)),
+        timer,
+        || {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move {
+                if attempt == 4 {
+                    Ok(attempt)
+                } else {
+                    Err(())
+                }
+            }
+        },
+    ));
+    assert_eq!(res, Some(Ok(4)));
+    assert_eq!(delays.replace(vec![]), [ms(100), ms(200), ms(400)]);
+
+    // a single attempt is never retried
+    attempts.set(0);
+    let res = task::run_until_stalled(task::retry_until(
+        RetryPolicy::constant(1, ms(10)),
+        timer,
+        || {
+            attempts.set(attempts.get() + 1);
+            async { Err::<(), _>(()) }
+        },
+    ));
+    assert_eq!(res, Some(Err(())));
+    assert_eq!(attempts.get(), 1);
+    assert!(delays.borrow().is_empty());
+
+    // the delay saturates instead of overflowing
+    let policy = RetryPolicy::exponential(u32::max_value(), ms(100), ms(1000));
+    assert_eq!(policy.delay(33), ms(1000));
+    assert_eq!(policy.delay(u32::max_value()), ms(1000));
+}
+
+// Records the requested delays; they elapse right away
+#[derive(Clone, Copy)]
+struct FakeTimer<'a> {
+    delays: &'a RefCell<Vec<Duration>>,
+}
+
+impl Delay for FakeTimer<'_> {
+    type Delay = Elapsed;
+
+    fn delay(self, dur: Duration) -> Elapsed {
+        self.delays.borrow_mut().push(dur);
+        Elapsed
+    }
+}
+
+struct Elapsed;
+
+impl Future for Elapsed {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        Poll::Ready(())
+    }
+}
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
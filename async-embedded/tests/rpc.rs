@@ -0,0 +1,52 @@
+//! A `Client::call` that's cancelled before the server handles its request: the next `call`
+//! reuses the response channel and gets its own response, not the one to the cancelled request
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{
+    task::{self, Either},
+    unsync::{
+        rpc::{Client, Request, Server},
+        Mpsc, Oneshot,
+    },
+};
+use typenum::consts::U4;
+
+#[test]
+fn cancelled_call() {
+    let queue: &'static mut Mpsc<Request<'static, u32, u32>, U4> = Box::leak(Box::new(Mpsc::new()));
+    let response: &'static Oneshot<u32> = Box::leak(Box::new(Oneshot::new()));
+
+    let (tx, rx) = queue.split();
+    let mut server = Server::new(rx);
+    let mut client = Client::new(tx, response);
+
+    task::spawn(async move {
+        // the server is slow to start
+        ticks(5).await;
+
+        loop {
+            let (req, reply) = server.recv().await;
+            reply.send(req * 2);
+        }
+    });
+
+    let res = task::run_until_stalled(async {
+        // the request is queued but the server doesn't get to it before the timeout
+        let first = task::select(client.call(1), ticks(2)).await;
+        // the server handles the stale request first; its response is discarded
+        let second = client.call(21).await;
+        let third = client.call(4).await;
+        (first, second, third)
+    });
+
+    assert_eq!(res, Some((Either::Right(()), Ok(42), Ok(8))));
+}
+
+// A timeout that expires after `n` scans of the executor; there's no timer on the host
+async fn ticks(n: u32) {
+    for _ in 0..n {
+        task::r#yield().await;
+    }
+}
@@ -0,0 +1,37 @@
+//! The interleaving of two tasks that `yield`, asserted with the scheduler tick
+//!
+//! Requires the `scheduler-tick` feature
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::task;
+
+const N: usize = 4;
+
+#[test]
+fn interleaving() {
+    let ha = task::spawn(ticks());
+    let hb = task::spawn(ticks());
+
+    let (a, b) = task::run_until_stalled(task::join(ha, hb)).unwrap();
+    for i in 0..N {
+        // both tasks were polled in the same scan ...
+        assert_eq!(a[i], b[i]);
+
+        // ... and, after yielding, again in the next one
+        if i + 1 < N {
+            assert_eq!(a[i + 1], a[i] + 1);
+        }
+    }
+}
+
+// Records the tick of `N` consecutive polls
+async fn ticks() -> [u32; N] {
+    let mut ticks = [0; N];
+    for tick in ticks.iter_mut() {
+        *tick = task::current_tick();
+        task::r#yield().await;
+    }
+    ticks
+}
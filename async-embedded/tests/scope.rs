@@ -0,0 +1,72 @@
+//! Tasks of a `Scope` that borrow the stack of the main task: a `Channel` and a buffer. The
+//! borrows end when `run` returns; the compile time half of the check is the `compile_fail`
+//! example of `Scope`
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use std::cell::RefCell;
+
+use async_embedded::{
+    task::{self, pin_mut, Either, Scope},
+    unsync::Channel,
+};
+use typenum::consts::{U2, U4};
+
+#[test]
+fn tasks_borrow_the_stack() {
+    let mut buf = [0; 8];
+    let log = RefCell::new(vec![]);
+
+    let res = task::run_until_stalled(async {
+        let channel = Channel::<u32, U2>::new();
+
+        let producer = async {
+            for i in 0..8 {
+                channel.send(i).await.unwrap();
+            }
+        };
+        let consumer = async {
+            for slot in buf.iter_mut() {
+                *slot = channel.recv().await.unwrap();
+            }
+        };
+        let second = async {
+            log.borrow_mut().push(1);
+            ticks(5).await;
+            log.borrow_mut().push(3);
+        };
+        pin_mut!(producer);
+        pin_mut!(consumer);
+        pin_mut!(second);
+
+        // NOTE the tasks must be declared before the scope; they must outlive it
+        let mut scope = Scope::<U4>::new();
+        scope.spawn_local(producer);
+        scope.spawn_local(consumer);
+        // `run` waits for the tasks even though this future completes right away
+        let first = scope.run(async { channel.len() }).await;
+
+        // the scope can be reused; it's empty again
+        scope.spawn_local(second);
+
+        // a cancelled `run` leaves the unfinished tasks in the scope
+        let cancelled =
+            task::select(scope.run(ticks(20)), async { log.borrow_mut().push(2) }).await;
+        let resumed = scope.run(async { 42 }).await;
+
+        (first, cancelled, resumed)
+    });
+
+    assert_eq!(res, Some((0, Either::Right(()), 42)));
+    // all the borrows have ended
+    assert_eq!(buf, [0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(log.into_inner(), [1, 2, 3]);
+}
+
+// A timeout that expires after `n` scans of the executor; there's no timer on the host
+async fn ticks(n: u32) {
+    for _ in 0..n {
+        task::r#yield().await;
+    }
+}
@@ -0,0 +1,70 @@
+//! A `select` between a `Channel` and a timeout: the receive (or send) that loses is cancelled
+//! without losing the message that's sent later, no matter how many times it loses
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{
+    task::{self, Either},
+    unsync::Channel,
+};
+use typenum::consts::U1;
+
+#[test]
+fn select_timeout_then_receive() {
+    let channel: &'static Channel<u32, U1> = Box::leak(Box::new(Channel::new()));
+
+    task::spawn(async move {
+        ticks(5).await;
+        channel.send(42).await.unwrap();
+    });
+
+    let res = task::run_until_stalled(async {
+        // each cancelled receive gives back its place in the set of waiting receivers; there's
+        // room for `NTASKS` (8) of them
+        for _ in 0..LOSSES {
+            assert_eq!(
+                task::select(channel.recv(), async {}).await,
+                Either::Right(())
+            );
+        }
+
+        // the timeout expires before the message is sent
+        let first = task::select(channel.recv(), ticks(3)).await;
+        let second = task::select(channel.recv(), ticks(10)).await;
+        (first, second)
+    });
+
+    assert_eq!(res, Some((Either::Right(()), Either::Left(Some(42)))));
+
+    // the same for senders: the channel is full so every send loses
+    channel.try_send(0).unwrap();
+    let res = task::run_until_stalled(async {
+        for i in 0..LOSSES {
+            match task::select(channel.send(i), async {}).await {
+                Either::Left(res) => panic!("send completed: {:?}", res),
+                Either::Right(()) => {}
+            }
+        }
+
+        // a receive makes room for the next send
+        task::spawn(async move {
+            ticks(3).await;
+            channel.recv().await;
+        });
+        task::select(channel.send(1), ticks(10)).await
+    });
+
+    assert_eq!(res, Some(Either::Left(Ok(()))));
+    assert_eq!(channel.try_recv(), Some(1));
+}
+
+// more than the capacity of a `WakerSet`
+const LOSSES: u32 = 20;
+
+// A timeout that expires after `n` scans of the executor; there's no timer on the host
+async fn ticks(n: u32) {
+    for _ in 0..n {
+        task::r#yield().await;
+    }
+}
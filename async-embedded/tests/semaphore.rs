@@ -0,0 +1,59 @@
+//! The `Semaphore`: an `acquire` waits for a permit to be released and, with zero permits, waits
+//! forever instead of underflowing the count
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use std::cell::Cell;
+
+use async_embedded::{
+    task::{self, Either},
+    unsync::Semaphore,
+};
+
+#[test]
+fn acquire_and_release() {
+    let s = Semaphore::new(2);
+    let acquired = Cell::new(false);
+
+    let res = task::run_until_stalled(async {
+        let first = s.acquire().await;
+        let _second = s.acquire().await;
+        assert_eq!(s.available(), 0);
+        assert!(s.try_acquire().is_none());
+
+        task::join(
+            async {
+                let _third = s.acquire().await;
+                acquired.set(true);
+            },
+            async {
+                // let the third `acquire` run first
+                task::r#yield().await;
+                assert!(!acquired.get());
+
+                drop(first);
+            },
+        )
+        .await;
+
+        // the third permit was released when the future that held it completed
+        s.available()
+    });
+    assert_eq!(res, Some(1));
+    assert!(acquired.get());
+    // the second one when the `async` block completed
+    assert_eq!(s.available(), 2);
+
+    // with zero permits `acquire` queues forever instead of underflowing the count
+    let s = Semaphore::new(0);
+    assert!(s.try_acquire().is_none());
+    let res = task::run_until_stalled(async {
+        match task::select(s.acquire(), task::r#yield()).await {
+            Either::Left(_) => panic!("acquired a permit"),
+            Either::Right(()) => {}
+        }
+    });
+    assert_eq!(res, Some(()));
+    assert_eq!(s.available(), 0);
+}
@@ -0,0 +1,48 @@
+//! The SPSC channel: its full capacity, also across the wraparound of the ring buffer, and the
+//! wakeups of a receiver waiting on an empty channel and of a sender waiting on a full one
+//!
+//! NOTE the executor is a singleton that can't be shared between threads; each test binary has a
+//! single `#[test]`
+
+use async_embedded::{task, unsync::spsc::Channel};
+use typenum::consts::U4;
+
+#[test]
+fn capacity_and_wakeups() {
+    let mut c = Channel::<u32, U4>::new();
+    let (mut tx, mut rx) = c.split();
+
+    // push and pop across the full capacity, twice to wrap around the ring buffer
+    for round in 0..2 {
+        for i in 0..4 {
+            assert_eq!(tx.try_send(round * 4 + i), Ok(()));
+        }
+        assert_eq!(tx.try_send(99), Err(99));
+
+        for i in 0..4 {
+            assert_eq!(rx.try_recv(), Some(round * 4 + i));
+        }
+        assert_eq!(rx.try_recv(), None);
+    }
+
+    // the receiver waits on an empty channel until the sender sends
+    let res = task::run_until_stalled(task::join(rx.recv(), async {
+        // let the receiver run first
+        task::r#yield().await;
+        tx.send(1).await;
+    }));
+    assert_eq!(res, Some((1, ())));
+
+    // the sender waits on a full channel until the receiver makes room
+    for i in 0..4 {
+        tx.try_send(i).unwrap();
+    }
+    let res = task::run_until_stalled(task::join(tx.send(4), async {
+        task::r#yield().await;
+        rx.recv().await
+    }));
+    assert_eq!(res, Some(((), 0)));
+    for i in 1..5 {
+        assert_eq!(rx.try_recv(), Some(i));
+    }
+}
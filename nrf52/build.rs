@@ -1,12 +1,226 @@
+// NOTE `cargo test` doesn't run the tests of build scripts; run them on the host with
+// `rustc --edition 2018 --test build.rs -o build-tests && ./build-tests`
+
 use std::{env, error::Error, fs, path::PathBuf};
 
+const FLASH_START: u32 = 0x0000_0000;
+const RAM_START: u32 = 0x2000_0000;
+
 fn main() -> Result<(), Box<dyn Error>> {
     let out_dir = &PathBuf::from(env::var("OUT_DIR")?);
 
+    // memory map of the selected chip: (FLASH, RAM) sizes in KiB
+    let chips = [
+        ("NRF52840", (1024, 256)),
+        ("NRF52833", (512, 128)),
+        ("NRF52832", (512, 64)),
+    ];
+    let selected = chips
+        .iter()
+        .filter(|(chip, _)| env::var_os(format!("CARGO_FEATURE_{}", chip)).is_some())
+        .collect::<Vec<_>>();
+    let (flash, ram) = match &selected[..] {
+        [(_, sizes)] => *sizes,
+        // reported by a `compile_error!` in `lib.rs`
+        _ => return Ok(()),
+    };
+
+    // the regions can be shrunk, e.g. to leave room for a bootloader or a SoftDevice; see README
+    let (flash_origin, flash_length) = region("FLASH", FLASH_START, flash * 1024)?;
+    let (ram_origin, ram_length) = region("RAM", RAM_START, ram * 1024)?;
+
     // place the linker script somewhere the linker can find it
-    let filename = "memory.x";
-    fs::copy(filename, out_dir.join(filename))?;
+    fs::write(
+        out_dir.join("memory.x"),
+        memory_x((flash_origin, flash_length), (ram_origin, ram_length)),
+    )?;
     println!("cargo:rustc-link-search={}", out_dir.display());
+    println!("cargo:rerun-if-changed=build.rs");
 
     Ok(())
 }
+
+// Returns the (origin, length) of the `name` memory region, which physically spans `size` bytes
+// starting at `start`
+//
+// The origin and length can be overridden with the `NRF52_<name>_ORIGIN` and
+// `NRF52_<name>_LENGTH` environment variables. By default the region extends from its origin to
+// the end of the physical memory
+fn region(name: &str, start: u32, size: u32) -> Result<(u32, u32), String> {
+    let origin = var(&format!("NRF52_{}_ORIGIN", name))?;
+    let length = var(&format!("NRF52_{}_LENGTH", name))?;
+
+    resolve(name, start, size, origin, length)
+}
+
+// Applies the `origin` and `length` overrides, if any, to the `name` memory region; see `region`
+fn resolve(
+    name: &str,
+    start: u32,
+    size: u32,
+    origin: Option<u32>,
+    length: Option<u32>,
+) -> Result<(u32, u32), String> {
+    let end = u64::from(start) + u64::from(size);
+
+    let origin = origin.unwrap_or(start);
+    if origin < start || u64::from(origin) >= end {
+        return Err(format!(
+            "NRF52_{}_ORIGIN ({:#x}) is outside the {} of the chip ({:#x} - {:#x})",
+            name, origin, name, start, end
+        ));
+    }
+
+    let available = (end - u64::from(origin)) as u32;
+    let length = length.unwrap_or(available);
+    if length == 0 || length > available {
+        return Err(format!(
+            "NRF52_{}_LENGTH ({:#x}) must be non-zero and at most {:#x} with an origin of {:#x}",
+            name, length, available, origin
+        ));
+    }
+
+    Ok((origin, length))
+}
+
+// Reads an address or size from the environment variable `key`; see `parse`
+fn var(key: &str) -> Result<Option<u32>, String> {
+    println!("cargo:rerun-if-env-changed={}", key);
+
+    match env::var(key) {
+        Ok(value) => parse(key, &value).map(Some),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(e) => Err(format!("{}: {}", key, e)),
+    }
+}
+
+// Parses the `value` of the environment variable `key` as an address or size
+//
+// Accepts decimal and hexadecimal (`0x` prefix) numbers, with an optional `K` (KiB) suffix
+fn parse(key: &str, value: &str) -> Result<u32, String> {
+    let invalid = || format!("{}: `{}` is not a valid address or size", key, value);
+    let trimmed = value.trim();
+    let (digits, scale) = match trimmed.strip_suffix('K') {
+        Some(digits) => (digits, 1024),
+        None => (trimmed, 1),
+    };
+    let number = match digits.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => digits.parse(),
+    }
+    .map_err(|_| invalid())?;
+
+    number.checked_mul(scale).ok_or_else(invalid)
+}
+
+// Generates the `memory.x` linker script from the (origin, length) of the memory regions
+fn memory_x(flash: (u32, u32), ram: (u32, u32)) -> String {
+    format!(
+        "MEMORY
+{{
+  FLASH : ORIGIN = {:#010x}, LENGTH = {:#x}
+  RAM : ORIGIN = {:#010x}, LENGTH = {:#x}
+}}
+",
+        flash.0, flash.1, ram.0, ram.1
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{memory_x, parse, resolve, FLASH_START, RAM_START};
+
+    #[test]
+    fn parse_valid() {
+        assert_eq!(parse("X", "4096"), Ok(4096));
+        assert_eq!(parse("X", "0x27000"), Ok(0x27000));
+        assert_eq!(parse("X", "156K"), Ok(156 * 1024));
+        assert_eq!(parse("X", "0x10K"), Ok(16 * 1024));
+        assert_eq!(parse("X", " 0x2000 "), Ok(0x2000));
+    }
+
+    #[test]
+    fn parse_malformed() {
+        for value in &[
+            "", "K", "0x", "-1", "12Q", "0x12G", "1.5K", "0X10", "4096 K",
+        ] {
+            assert!(parse("X", value).is_err(), "accepted `{}`", value);
+        }
+
+        // overflows `u32`
+        assert!(parse("X", "0x100000000").is_err());
+        assert!(parse("X", "4194304K").is_err());
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(
+            resolve("FLASH", FLASH_START, 1024 * 1024, None, None),
+            Ok((0, 1024 * 1024))
+        );
+        assert_eq!(
+            resolve("RAM", RAM_START, 256 * 1024, None, None),
+            Ok((0x2000_0000, 256 * 1024))
+        );
+    }
+
+    #[test]
+    fn overrides() {
+        // S140 v7 SoftDevice on the nRF52840
+        let flash = resolve("FLASH", FLASH_START, 1024 * 1024, Some(0x27000), None).unwrap();
+        let ram = resolve("RAM", RAM_START, 256 * 1024, Some(0x2000_2000), None).unwrap();
+        assert_eq!(flash, (0x27000, 0xd9000));
+        assert_eq!(ram, (0x2000_2000, 0x3e000));
+
+        assert_eq!(
+            memory_x(flash, ram),
+            "MEMORY
+{
+  FLASH : ORIGIN = 0x00027000, LENGTH = 0xd9000
+  RAM : ORIGIN = 0x20002000, LENGTH = 0x3e000
+}
+"
+        );
+
+        // shrunk, e.g. to leave room for a bootloader at the end of the Flash
+        assert_eq!(
+            resolve("FLASH", FLASH_START, 512 * 1024, Some(0x1000), Some(0x1000)),
+            Ok((0x1000, 0x1000))
+        );
+        // the whole region, explicitly
+        assert_eq!(
+            resolve(
+                "RAM",
+                RAM_START,
+                64 * 1024,
+                Some(RAM_START),
+                Some(64 * 1024)
+            ),
+            Ok((RAM_START, 64 * 1024))
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let ram = |origin, length| resolve("RAM", RAM_START, 64 * 1024, origin, length);
+
+        // origin before the start or past the end of RAM
+        assert!(ram(Some(0x1fff_f000), None).is_err());
+        assert!(ram(Some(RAM_START + 64 * 1024), None).is_err());
+        // empty
+        assert!(ram(None, Some(0)).is_err());
+        // past the end of RAM
+        assert!(ram(None, Some(64 * 1024 + 1)).is_err());
+        assert!(ram(Some(RAM_START + 0x1000), Some(64 * 1024)).is_err());
+
+        // past the end of the address space
+        assert!(resolve(
+            "FLASH",
+            0xffff_f000,
+            0x1000,
+            Some(0xffff_f000),
+            Some(0x2000)
+        )
+        .is_err());
+    }
+}
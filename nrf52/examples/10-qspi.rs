@@ -0,0 +1,56 @@
+//! Erase / write / read round trip on the external QSPI flash
+//!
+//! Expected output (MX25R6435F):
+//!
+//! ```
+//! JEDEC ID: [c2, 28, 17]
+//! erased: OK
+//! round trip: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::qspi::{Erase, Qspi};
+use panic_udf as _; // panic handler
+
+// use a sector near the end of the flash
+const ADDRESS: usize = nrf52::qspi::CAPACITY - nrf52::qspi::SECTOR_SIZE;
+
+#[entry]
+fn main() -> ! {
+    let mut qspi = Qspi::take();
+
+    task::block_on(async {
+        let id = qspi.jedec_id().await;
+        hprintln!("JEDEC ID: {:x?}", id).ok();
+
+        let mut buf = [0; 64];
+        qspi.erase(ADDRESS, Erase::Sector).await.unwrap();
+        qspi.read(ADDRESS, &mut buf).await.unwrap();
+        let erased = buf.iter().all(|byte| *byte == 0xff);
+        hprintln!("erased: {}", if erased { "OK" } else { "FAIL" }).ok();
+
+        let mut pattern = [0; 64];
+        for (i, byte) in pattern.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        qspi.write(ADDRESS, &pattern).await.unwrap();
+        qspi.read(ADDRESS, &mut buf).await.unwrap();
+        hprintln!(
+            "round trip: {}",
+            if buf == pattern { "OK" } else { "FAIL" }
+        )
+        .ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
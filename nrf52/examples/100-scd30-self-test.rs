@@ -0,0 +1,57 @@
+//! Runs the SCD30 self-test on a healthy sensor and on one that doesn't respond because it's
+//! re-initializing after a soft reset; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! healthy sensor: OK
+//! unresponsive sensor: Twim(Src(address NACK))
+//! recovered sensor: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    scd30::{self, Error, Scd30},
+    timer::{ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim: &'static Mutex<Twim> = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut scd30 = Scd30::new(twim);
+    let timer = Timer::take();
+
+    task::block_on(async {
+        scd30.self_test().await.unwrap();
+        hprintln!("healthy sensor: OK").ok();
+
+        // NOTE issue the reset command without waiting for the sensor to come back, like
+        // `Scd30::soft_reset` would
+        let (address, command) = scd30::soft_reset_command();
+        twim.lock().await.write(address, &command).await.unwrap();
+        match scd30.self_test().await {
+            Err(e @ Error::Twim(_)) => hprintln!("unresponsive sensor: {:?}", e).ok(),
+            res => panic!("{:?}", res),
+        };
+
+        timer.wait(2.secs()).await;
+        scd30.self_test().await.unwrap();
+        hprintln!("recovered sensor: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,70 @@
+//! Reads out the SCD30 as fast as it produces measurements and then too slowly, and checks that
+//! `missed_since_last` tells both cases apart; panics if a check fails
+//!
+//! The sensor keeps its data ready flag set until the measurement is read out. When the flag is
+//! already set at the time `get_measurement` is called the measurement may be a stale one
+//!
+//! Expected output:
+//!
+//! ```
+//! fresh measurements: OK
+//! stale measurement: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    scd30::Scd30,
+    timer::{ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+// seconds
+const INTERVAL: u16 = 2;
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut scd30 = Scd30::new(twim);
+    let timer = Timer::take();
+
+    task::block_on(async {
+        scd30.set_measurement_interval(INTERVAL).await.unwrap();
+        scd30.start_continuous_measurement(0).await.unwrap();
+
+        // the first read-out has nothing to compare against
+        scd30.get_measurement().await.unwrap();
+        assert!(!scd30.missed_since_last());
+
+        // back to back read-outs: each one waits for the data ready flag to toggle
+        for _ in 0..3 {
+            scd30.get_measurement().await.unwrap();
+            assert!(!scd30.missed_since_last());
+        }
+        hprintln!("fresh measurements: OK").ok();
+
+        // the flag is set, and stays set, while we are busy doing something else
+        timer.wait(u32::from(2 * INTERVAL).secs()).await;
+        scd30.get_measurement().await.unwrap();
+        assert!(scd30.missed_since_last());
+
+        // caught up
+        scd30.get_measurement().await.unwrap();
+        assert!(!scd30.missed_since_last());
+        hprintln!("stale measurement: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
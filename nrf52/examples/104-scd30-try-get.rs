@@ -0,0 +1,66 @@
+//! Checks `try_get_measurement` right after a read-out, when no new measurement can be ready, and
+//! after waiting for a full measurement interval; panics if a check fails
+//!
+//! Expected output (the numbers will vary):
+//!
+//! ```
+//! not ready: OK
+//! ready: CO2: 600 ppm, T: 24.1 °C, RH: 40%
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    scd30::Scd30,
+    timer::{ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+// seconds
+const INTERVAL: u16 = 2;
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut scd30 = Scd30::new(twim);
+    let timer = Timer::take();
+
+    task::block_on(async {
+        scd30.set_measurement_interval(INTERVAL).await.unwrap();
+        scd30.start_continuous_measurement(0).await.unwrap();
+
+        // the sensor only produces a new measurement once per interval
+        scd30.get_measurement().await.unwrap();
+        assert!(scd30.try_get_measurement().await.unwrap().is_none());
+        hprintln!("not ready: OK").ok();
+
+        // NOTE plus some margin
+        timer.wait(u32::from(INTERVAL).secs() + 500.millis()).await;
+        match scd30.try_get_measurement().await.unwrap() {
+            Some(m) => {
+                hprintln!("ready: {}", m).ok();
+                assert!(m.co2 >= 0. && m.co2 <= 40_000.);
+                assert!(m.rh >= 0. && m.rh <= 100.);
+                assert!(m.t >= -40. && m.t <= 70.);
+            }
+            None => panic!("no measurement after a full interval"),
+        }
+
+        // the measurement has been read out
+        assert!(scd30.try_get_measurement().await.unwrap().is_none());
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,80 @@
+//! Checks the expiry of `timer::deadline` and that `Scd30::get_measurement` gives up when the
+//! sensor stops producing measurements; panics if a check fails
+//!
+//! Expected output (the numbers will vary):
+//!
+//! ```
+//! deadline: OK
+//! get_measurement timed out after 5000 ms
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    scd30::{Error, Scd30},
+    timer::{self, ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut scd30 = Scd30::new(twim);
+    let timer = Timer::take();
+
+    task::block_on(async {
+        // a zero duration deadline has already expired
+        let deadline = timer::deadline(0.secs());
+        assert!(deadline.expired());
+        assert_eq!(deadline.remaining(), 0.secs());
+
+        let deadline = timer::deadline(10.millis());
+        assert!(!deadline.expired());
+        assert!(deadline.remaining() <= 10.millis());
+
+        // checking the deadline doesn't wait
+        let mut polls = 0;
+        while !deadline.expired() {
+            polls += 1;
+            task::r#yield().await;
+        }
+        assert!(polls > 1);
+        assert!(Timer::now() >= deadline.instant());
+        assert_eq!(deadline.remaining(), 0.secs());
+
+        // a wait that ends after the deadline
+        let deadline = timer::deadline(10.millis());
+        timer.wait(11.millis()).await;
+        assert!(deadline.expired());
+        hprintln!("deadline: OK").ok();
+
+        // with a 2 second interval the driver waits up to 5 seconds for a measurement
+        scd30.set_measurement_interval(2).await.unwrap();
+        scd30.start_continuous_measurement(0).await.unwrap();
+        scd30.get_measurement().await.unwrap();
+        scd30.stop_continuous_measurement().await.unwrap();
+
+        let start = Timer::now();
+        match scd30.get_measurement().await {
+            Err(Error::Timeout) => {}
+            res => panic!("{:?}", res.map(|_| ())),
+        }
+        let elapsed = start.elapsed();
+        hprintln!("get_measurement timed out after {} ms", elapsed.as_millis()).ok();
+        assert!(elapsed >= 5.secs() && elapsed < 6.secs());
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
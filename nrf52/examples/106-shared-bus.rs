@@ -0,0 +1,81 @@
+//! Reads the DS3231 while the SCD30 driver polls its data ready status on the same bus, and
+//! checks that the RTC stays responsive; panics if a check fails
+//!
+//! Expected output (the numbers will vary):
+//!
+//! ```
+//! 3 measurements; 250 RTC reads; slowest RTC read: 1 ms
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::{cell::Cell, cmp, time::Duration};
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    ds3231::Ds3231,
+    scd30::Scd30,
+    timer::{ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+// number of sensor measurements to wait for
+const MEASUREMENTS: u32 = 3;
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+    static mut C: Cell<u32> = Cell::new(0);
+
+    let twim: &'static _ = M.get_or_insert(Mutex::new(Twim::take()));
+    let measurements: &'static _ = C;
+    let timer = Timer::take();
+
+    // NOTE without the RDY pin the driver polls the sensor over the bus until a measurement is
+    // ready
+    let mut scd30 = Scd30::new(twim);
+    task::spawn(async move {
+        scd30.start_continuous_measurement(0).await.unwrap();
+        loop {
+            scd30.get_measurement().await.unwrap();
+            measurements.set(measurements.get() + 1);
+        }
+    });
+
+    let mut ds3231 = Ds3231::new(twim);
+    task::block_on(async {
+        let mut reads = 0;
+        let mut slowest = Duration::default();
+        while measurements.get() < MEASUREMENTS {
+            let start = Timer::now();
+            ds3231.get_time().await.unwrap();
+            slowest = cmp::max(slowest, start.elapsed());
+            reads += 1;
+
+            timer.wait(20.millis()).await;
+        }
+
+        hprintln!(
+            "{} measurements; {} RTC reads; slowest RTC read: {} ms",
+            MEASUREMENTS,
+            reads,
+            slowest.as_millis()
+        )
+        .ok();
+        // NOTE at most one sensor transaction can be in progress when the RTC read starts; the
+        // sensor may stretch the clock for up to 150 ms. A starved RTC would have to wait for a
+        // whole measurement interval (2 s)
+        assert!(slowest < 200.millis());
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
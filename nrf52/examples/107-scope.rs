@@ -0,0 +1,79 @@
+//! Tasks that borrow stack variables, run next to the main task
+//!
+//! Unlike `spawn`-ed tasks, the tasks of a `Scope` can borrow data owned by the main task: no
+//! `static` variables are needed. Unlike `join`, the number of tasks is not fixed by the call
+//!
+//! Expected output:
+//!
+//! ```
+//! main: waiting for 3 tasks
+//! task 0: done
+//! task 1: done
+//! task 2: done
+//! task 3: done
+//! buffer: [1, 1, 2, 2, 4, 4, 8, 8]
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task::{self, pin_mut, Scope};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use heapless::consts;
+use nrf52 as _; // memory layout
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    task::block_on(async {
+        // lives on the stack of this (the main) task
+        let mut buf = [0u8; 8];
+
+        {
+            let mut windows = buf.chunks_mut(2);
+            let t0 = fill(0, windows.next().expect("UNREACHABLE"));
+            let t1 = fill(1, windows.next().expect("UNREACHABLE"));
+            let t2 = fill(2, windows.next().expect("UNREACHABLE"));
+            let t3 = fill(3, windows.next().expect("UNREACHABLE"));
+            pin_mut!(t0);
+            pin_mut!(t1);
+            pin_mut!(t2);
+            pin_mut!(t3);
+
+            // NOTE the tasks must be declared before the scope; they must outlive it
+            let mut scope = Scope::<consts::U4>::new();
+            scope.spawn_local(t0);
+            scope.spawn_local(t1);
+            scope.spawn_local(t2);
+            scope
+                .run(async {
+                    hprintln!("main: waiting for 3 tasks").ok();
+                })
+                .await;
+
+            // the scope is empty again; run the last task on its own
+            scope.spawn_local(t3);
+            scope.run(async {}).await;
+        }
+
+        // all tasks have completed; `buf` is no longer borrowed
+        hprintln!("buffer: {:?}", buf).ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
+
+// fills `window`, one byte at a time, with `1 << i`
+async fn fill(i: usize, window: &mut [u8]) {
+    for byte in window.iter_mut() {
+        *byte = 1 << i;
+        task::r#yield().await;
+    }
+    hprintln!("task {}: done", i).ok();
+}
@@ -0,0 +1,43 @@
+//! Drives a 4-bit counter onto a parallel bus
+//!
+//! D0 = P1.10
+//! D1 = P1.11
+//! D2 = P1.12
+//! D3 = P1.13
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::time::Duration;
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use nrf52::{
+    gpio::{self, Port},
+    timer::Timer,
+};
+use panic_udf as _; // panic handler
+
+const PINS: [u8; 4] = [10, 11, 12, 13];
+
+#[entry]
+fn main() -> ! {
+    let mut timer = Timer::take();
+
+    let (all, _) = gpio::masks(&PINS, 0b1111);
+    Port::P1.set_outputs(all);
+
+    task::block_on(async {
+        let mut value = 0;
+        loop {
+            // all 4 lines change (almost) at the same time
+            let (set, clear) = gpio::masks(&PINS, value);
+            Port::P1.write_mask(set, clear);
+
+            value = (value + 1) % 16;
+            timer.wait(Duration::from_millis(500)).await;
+        }
+    })
+}
@@ -0,0 +1,71 @@
+//! Print sensor data as it arrives through a channel
+//!
+//! The CO2 reading is also smoothed with a moving average over the last 5 samples (10 seconds)
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::{fmt::Write as _, time::Duration};
+
+use async_embedded::{
+    task,
+    unsync::{Channel, Mutex},
+};
+use cortex_m_rt::entry;
+use heapless::{consts, String};
+use nrf52::{
+    filter::MovingAverage,
+    scd30::{self, Measurement, Scd30},
+    serial,
+    timer::Timer,
+    twim::Twim,
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut C: Channel<Result<Measurement, scd30::Error>, consts::U8> = Channel::new();
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let c: &'static _ = C;
+
+    // task that reads out the sensor
+    let mut timer = Timer::take();
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let scd30 = Scd30::new(twim);
+    task::spawn(async move {
+        // the sensor produces new data every 2 seconds
+        scd30.run(&mut timer, Duration::from_secs(2), c).await
+    });
+
+    // task that reports the sensor data
+    let (mut tx, _rx) = serial::take();
+    task::block_on(async {
+        let mut tx_buf = String::<consts::U64>::new();
+        let mut co2_avg = MovingAverage::<consts::U5>::new();
+
+        loop {
+            match c.recv().await {
+                Some(Ok(m)) => {
+                    let avg = co2_avg.push(m.co2);
+
+                    tx_buf.clear();
+                    // will not fail; the buffer is big enough
+                    let _ = writeln!(
+                        &mut tx_buf,
+                        "CO2: {}ppm (avg: {}ppm)\nT: {}C\nRH: {}%",
+                        m.co2 as u16, avg as u16, m.t as i8, m.rh as u8
+                    );
+                    tx.write(tx_buf.as_bytes()).await;
+                }
+
+                Some(Err(_)) => tx.write(b"error reading the sensor\n").await,
+
+                // NOTE the channel is never closed
+                None => {}
+            }
+        }
+    })
+}
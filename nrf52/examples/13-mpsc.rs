@@ -0,0 +1,51 @@
+//! Several producers feeding a single consumer through a MPSC channel
+//!
+//! Expected output:
+//!
+//! ```
+//! received 1 from A
+//! received 1 from B
+//! received 1 from C
+//! received 2 from A
+//! received 2 from B
+//! received 2 from C
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mpsc};
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use heapless::consts;
+use nrf52 as _; // memory layout
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut Q: Mpsc<(char, u32), consts::U4> = Mpsc::new();
+
+    let (tx, mut rx) = Q.split();
+
+    for name in ['A', 'B', 'C'].iter().cloned() {
+        let tx = tx.clone();
+        task::spawn(async move {
+            let mut count = 0;
+            loop {
+                count += 1;
+                tx.send((name, count)).await;
+                task::r#yield().await;
+            }
+        });
+    }
+
+    task::block_on(async {
+        loop {
+            let (name, count) = rx.recv().await;
+            hprintln!("received {} from {}", count, name).ok();
+        }
+    })
+}
@@ -0,0 +1,66 @@
+//! Finding tasks that don't yield often enough
+//!
+//! Run with `--features poll-watchdog`
+//!
+//! Expected output:
+//!
+//! ```
+//! task 0 did not yield for 4000020 cycles
+//! task 0 did not yield for 4000017 cycles
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::time::Duration;
+
+use async_embedded::task::{self, Overrun};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{led::Red, timer::Timer};
+use panic_udf as _; // panic handler
+
+// 64 MHz core clock -> 1 ms
+const THRESHOLD: u32 = 64_000;
+
+fn report(overrun: Overrun) {
+    if let Some(task) = overrun.task {
+        hprintln!("task {} did not yield for {} cycles", task, overrun.cycles).ok();
+    } else {
+        hprintln!("`block_on` did not yield for {} cycles", overrun.cycles).ok();
+    }
+}
+
+#[entry]
+fn main() -> ! {
+    let mut cp = cortex_m::Peripherals::take().unwrap();
+    cp.DCB.enable_trace();
+    cp.DWT.enable_cycle_counter();
+
+    task::set_poll_watchdog(THRESHOLD, report);
+
+    // a task that does a lot of work between yield points
+    task::spawn(async {
+        loop {
+            // stand-in for some long computation (~60 ms)
+            asm::delay(4_000_000);
+            task::r#yield().await;
+        }
+    });
+
+    // heartbeat task; it will be noticeably irregular
+    let mut timer = Timer::take();
+    let dur = Duration::from_millis(100);
+    task::block_on(async {
+        loop {
+            Red.on();
+            timer.wait(dur).await;
+            Red.off();
+            timer.wait(dur).await;
+        }
+    })
+}
@@ -0,0 +1,74 @@
+//! Waking up on a DS3231 alarm
+//!
+//! The INT/SQW pin of the DS3231 must be connected to P0.02. The device sleeps (`WFE`) until the
+//! alarm fires; then the LED blinks a heartbeat and the next alarm is scheduled 10 seconds later
+//!
+//! Expected output:
+//!
+//! ```
+//! alarm @ 00:00:10
+//! alarm @ 00:00:20
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::time::Duration;
+
+use async_embedded::{task, unsync::Mutex};
+use chrono::{NaiveTime, Timelike as _};
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    ds3231::{Alarm1, Ds3231},
+    gpio::{InputPin, Pull},
+    led::Red,
+    pin,
+    timer::Timer,
+    twim::Twim,
+};
+use panic_udf as _; // panic handler
+
+// seconds between alarms
+const PERIOD: u32 = 10;
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+    // INT/SQW is open drain
+    let mut int = InputPin::new(pin!(0, 2), Pull::Up).unwrap();
+    let mut timer = Timer::take();
+    let dur = Duration::from_millis(100);
+
+    task::block_on(async {
+        ds3231.set_time(NaiveTime::from_hms(0, 0, 0)).await.unwrap();
+
+        loop {
+            let now = ds3231.get_time().await.unwrap();
+            let next = NaiveTime::from_num_seconds_from_midnight(
+                (now.num_seconds_from_midnight() + PERIOD) % (24 * 60 * 60),
+                0,
+            );
+            ds3231.set_alarm1(Alarm1::Daily(next)).await.unwrap();
+
+            // nothing else to do; the executor puts the device to sleep
+            ds3231.wait_for_alarm(&mut int).await.unwrap();
+            hprintln!("alarm @ {}", next).ok();
+
+            // heartbeat
+            Red.on();
+            timer.wait(dur).await;
+            Red.off();
+            timer.wait(dur).await;
+            Red.on();
+            timer.wait(dur).await;
+            Red.off();
+        }
+    })
+}
@@ -0,0 +1,44 @@
+//! Signaling a fatal error on the LED
+//!
+//! The task below reads from an I2C address where there's no device; the error is deemed fatal
+//! and the red LED blinks "SOS" (... --- ...) forever
+//!
+//! Expected output:
+//!
+//! ```
+//! fatal error: Src(2)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::time::Duration;
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{led, timer::Timer, twim::Twim};
+use panic_udf as _; // panic handler
+
+// no device should be using this (reserved) address
+const ADDRESS: u8 = 0b000_0001;
+
+#[entry]
+fn main() -> ! {
+    let mut timer = Timer::take();
+    let mut twim = Twim::take();
+
+    task::block_on(async {
+        let mut buf = [0; 2];
+        if let Err(e) = twim.read(ADDRESS, &mut buf).await {
+            hprintln!("fatal error: {:?}", e).ok();
+            led::panic_blink(&mut timer, led::SOS, Duration::from_millis(200)).await
+        }
+
+        loop {
+            task::r#yield().await;
+        }
+    })
+}
@@ -0,0 +1,58 @@
+//! Recording and dumping an event trace
+//!
+//! Run with `--features trace`
+//!
+//! Expected output (on the serial interface):
+//!
+//! ```
+//!        123 User(0)
+//!        130 TwimStart(104)
+//!        145 TwimEnd(104)
+//!       3412 TimerExpired
+//!       3413 User(1)
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::time::Duration;
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use nrf52::{
+    ds3231::Ds3231,
+    serial,
+    timer::Timer,
+    trace::{self, EventId},
+    twim::Twim,
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+    let mut timer = Timer::take();
+    let (mut tx, _rx) = serial::take();
+
+    task::block_on(async {
+        for i in 0..4 {
+            trace::record(EventId::User(i));
+            ds3231.get_time().await.ok();
+            timer.wait(Duration::from_millis(100)).await;
+        }
+
+        let trace = trace::take();
+        trace.dump(&mut tx).await;
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,45 @@
+//! Running the high frequency crystal oscillator only when it's needed
+//!
+//! The HFXO is started right before a serial transmission (the baud rate is more accurate when
+//! derived from the crystal) and stopped while the device is idle
+//!
+//! Expected output (on the serial interface):
+//!
+//! ```
+//! the quick brown fox jumps over the lazy dog
+//! the quick brown fox jumps over the lazy dog
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use nrf52::{
+    clock::Clocks,
+    serial,
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let mut clocks = Clocks::take();
+    let mut timer = Timer::take();
+    let (mut tx, _rx) = serial::take();
+
+    task::block_on(async {
+        loop {
+            clocks.start_hfclk().await;
+            tx.write(b"the quick brown fox jumps over the lazy dog\n")
+                .await;
+            clocks.stop_hfclk();
+
+            // idle; the `Timer` runs from the LFCLK
+            timer.wait(1.secs()).await;
+        }
+    })
+}
@@ -0,0 +1,65 @@
+//! Disabling idle peripherals to reduce the sleep current
+//!
+//! Measure the current consumption (e.g. with a Power Profiler Kit) with and without the
+//! `LowPower` registration: between the serial messages the UARTE and TWIM are disabled so the
+//! HFCLK is released while the core sleeps
+//!
+//! Expected output (on the serial interface):
+//!
+//! ```
+//! 00:00:01
+//! 00:00:03
+//! 00:00:05
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::fmt::Write as _;
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m_rt::entry;
+use heapless::{consts, String};
+use nrf52::{
+    ds3231::Ds3231,
+    power::LowPower,
+    serial,
+    timer::{ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    LowPower {
+        uarte: true,
+        twim: true,
+    }
+    .register();
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+    let mut timer = Timer::take();
+    let (mut tx, _rx) = serial::take();
+
+    task::block_on(async {
+        let mut buf = String::<consts::U16>::new();
+
+        loop {
+            if let Ok(time) = ds3231.get_time().await {
+                buf.clear();
+                // will not fail; the buffer is big enough
+                let _ = writeln!(&mut buf, "{}", time);
+                tx.write(buf.as_bytes()).await;
+            }
+
+            // both peripherals are disabled while waiting
+            timer.wait(2.secs()).await;
+        }
+    })
+}
@@ -27,7 +27,7 @@ use panic_udf as _; // panic handler
 #[entry]
 fn main() -> ! {
     static mut X: Cell<i64> = Cell::new(0);
-    // not-async-aware, one-shot channel
+    // not-async-aware, one-shot channel; see `unsync::oneshot` for an async-aware one
     static mut Y: RefCell<Option<i32>> = RefCell::new(None);
 
     // only references with `'static` lifetimes can be sent to `spawn`-ed tasks
@@ -0,0 +1,110 @@
+//! Several tasks requesting data from a single task that owns the I2C bus
+//!
+//! Expected output:
+//!
+//! ```
+//! time: 12:34:56
+//! CO2: 652 ppm, T: 26.3 °C, RH: 23%
+//! time: 12:34:57
+//! time: 12:34:58
+//! CO2: 655 ppm, T: 26.3 °C, RH: 23%
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{
+    task,
+    unsync::{
+        rpc::{Client, Request, Server},
+        Mpsc, Mutex, Oneshot,
+    },
+};
+use chrono::NaiveTime;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use heapless::consts;
+use nrf52::{
+    ds3231::Ds3231,
+    scd30::{Measurement, Scd30},
+    timer::{ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_udf as _; // panic handler
+
+enum Query {
+    Time,
+    Measurement,
+}
+
+enum Response {
+    Time(NaiveTime),
+    Measurement(Measurement),
+    Error,
+}
+
+#[entry]
+fn main() -> ! {
+    static mut Q: Mpsc<Request<'static, Query, Response>, consts::U2> = Mpsc::new();
+    static mut R1: Oneshot<Response> = Oneshot::new();
+    static mut R2: Oneshot<Response> = Oneshot::new();
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let (tx, rx) = Q.split();
+    let mut server = Server::new(rx);
+    let mut clock_client = Client::new(tx.clone(), R1);
+    let mut sensor_client = Client::new(tx, R2);
+
+    // server: the only task that talks to the I2C devices
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+    let mut scd30 = Scd30::new(twim);
+    task::spawn(async move {
+        loop {
+            let (query, reply) = server.recv().await;
+            let response = match query {
+                Query::Time => ds3231.get_time().await.map(Response::Time).ok(),
+                Query::Measurement => scd30
+                    .get_measurement()
+                    .await
+                    .map(Response::Measurement)
+                    .ok(),
+            };
+            reply.send(response.unwrap_or(Response::Error));
+        }
+    });
+
+    // client: reports the CO2 level as often as the sensor produces new data
+    task::spawn(async move {
+        loop {
+            match sensor_client.call(Query::Measurement).await {
+                Ok(Response::Measurement(m)) => {
+                    hprintln!("{}", m).ok();
+                }
+                _ => {
+                    hprintln!("sensor error").ok();
+                }
+            }
+        }
+    });
+
+    // client: reports the time every second
+    let mut timer = Timer::take();
+    task::block_on(async {
+        loop {
+            match clock_client.call(Query::Time).await {
+                Ok(Response::Time(time)) => {
+                    hprintln!("time: {}", time).ok();
+                }
+                _ => {
+                    hprintln!("clock error").ok();
+                }
+            }
+
+            timer.wait(1.secs()).await;
+        }
+    })
+}
@@ -0,0 +1,86 @@
+//! Computing a CRC over a large buffer in small slices so that other tasks can run in between
+//!
+//! Expected output:
+//!
+//! ```
+//! B: slice 0
+//! A: tick
+//! B: slice 1
+//! A: tick
+//! B: slice 2
+//! A: tick
+//! B: slice 3
+//! A: tick
+//! CRC: 0xa2912082
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::task::Poll;
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52 as _; // memory layout
+use panic_udf as _; // panic handler
+
+const SLICE: usize = 1024;
+
+#[entry]
+fn main() -> ! {
+    static mut BUF: [u8; 4 * SLICE] = [0; 4 * SLICE];
+
+    for (i, byte) in BUF.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let buf: &'static [u8] = BUF;
+
+    // task A
+    task::spawn(async {
+        loop {
+            hprintln!("A: tick").ok();
+            task::r#yield().await;
+        }
+    });
+
+    // task B
+    task::block_on(async {
+        let mut crc = !0;
+        let mut slices = buf.chunks(SLICE).enumerate();
+        let crc = task::chunked(|| {
+            if let Some((i, slice)) = slices.next() {
+                hprintln!("B: slice {}", i).ok();
+                crc = crc32(crc, slice);
+                Poll::Pending
+            } else {
+                Poll::Ready(!crc)
+            }
+        })
+        .await;
+
+        hprintln!("CRC: {:#010x}", crc).ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
+
+// CRC-32 (IEEE 802.3); bitwise to keep it slow
+fn crc32(mut crc: u32, bytes: &[u8]) -> u32 {
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
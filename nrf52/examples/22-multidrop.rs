@@ -0,0 +1,65 @@
+//! Master of a 9-bit multidrop bus (@ 9600 bauds)
+//!
+//! TXD = P0.06
+//! RXD = P0.08
+//!
+//! Every second the master addresses node 5 (a word with the 9th bit set), sends it a `PING`
+//! command and waits for a 2-byte reply. Nodes ignore data words until they see their own
+//! address. A node can be simulated with a USB-serial adapter configured for mark / space
+//! parity
+//!
+//! Expected output:
+//!
+//! ```
+//! node 5 replied: [0x50, 0x4f]
+//! node 5 replied: [0x50, 0x4f]
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    serial,
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_udf as _; // panic handler
+
+// 9th bit; marks a word as a node address
+const ADDRESS_MARK: u16 = 1 << 8;
+const NODE: u16 = 5;
+const PING: u16 = 0x01;
+
+#[entry]
+fn main() -> ! {
+    let mut timer = Timer::take();
+    let (mut tx, mut rx) = serial::take();
+    serial::set_nine_bit(&mut tx, &mut rx, true);
+
+    task::block_on(async {
+        loop {
+            tx.write9(&[ADDRESS_MARK | NODE, PING]).await;
+
+            let mut reply = [0; 2];
+            match rx.read9(&mut reply).await {
+                Ok(()) if reply.iter().all(|word| word & ADDRESS_MARK == 0) => {
+                    hprintln!("node {} replied: {:#04x?}", NODE, reply).ok();
+                }
+                // another node grabbed the bus
+                Ok(()) => {
+                    hprintln!("unexpected address: {:#05x?}", reply).ok();
+                }
+                Err(e) => {
+                    hprintln!("error: {:?}", e).ok();
+                }
+            }
+
+            timer.wait(1.secs()).await;
+        }
+    })
+}
@@ -0,0 +1,90 @@
+//! Log flusher: bytes received over the serial line (@ 9600 bauds) are buffered and echoed back
+//! once per second, or right away when the buffer fills up
+//!
+//! TXD = P0.06
+//! RXD = P0.08
+//!
+//! Expected output (typing slowly and then pasting a long line):
+//!
+//! ```
+//! Deadline: hello
+//! Deadline: world
+//! Notified: the quick brown
+//! Notified:  fox jumps over
+//! Deadline:  the lazy dog
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{
+    task,
+    unsync::{Mutex, Notify},
+};
+use cortex_m_rt::entry;
+use heapless::{consts, Vec};
+use nrf52::{
+    serial,
+    timer::{ext::DurationExt as _, Timer, Wakeup},
+};
+use panic_udf as _; // panic handler
+
+type Log = Vec<u8, consts::U16>;
+
+#[entry]
+fn main() -> ! {
+    static mut L: Option<Mutex<Log>> = None;
+    static mut N: Notify = Notify::new();
+
+    let log: &'static _ = L.get_or_insert(Mutex::new(Log::new()));
+    let full: &'static _ = N;
+    let (mut tx, mut rx) = serial::take();
+
+    // task that fills the log
+    task::spawn(async move {
+        let mut byte = [0];
+        loop {
+            if rx.read(&mut byte).await.is_err() {
+                continue;
+            }
+
+            let mut log = log.lock().await;
+            // NOTE drops the byte if the flusher is lagging behind
+            let _ = log.push(byte[0]);
+            if log.len() == log.capacity() {
+                full.notify();
+            }
+        }
+    });
+
+    // task that flushes the log
+    let mut timer = Timer::take();
+    task::block_on(async {
+        let mut deadline = Timer::now() + 1.secs();
+        loop {
+            let wakeup = timer.sleep_until_or(deadline, full).await;
+            if wakeup == Wakeup::Deadline {
+                deadline = deadline + 1.secs();
+            }
+
+            let bytes = {
+                let mut log = log.lock().await;
+                let bytes = log.clone();
+                log.clear();
+                bytes
+            };
+
+            if !bytes.is_empty() {
+                tx.write(match wakeup {
+                    Wakeup::Deadline => b"Deadline: ",
+                    Wakeup::Notified => b"Notified: ",
+                })
+                .await;
+                tx.write(&bytes).await;
+                tx.write(b"\n").await;
+            }
+        }
+    })
+}
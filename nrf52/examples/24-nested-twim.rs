@@ -0,0 +1,66 @@
+//! Leaking an I2C transfer and then starting a new one trips the reentrancy guard
+//!
+//! Run with `--features reentrancy-guard`
+//!
+//! Expected output:
+//!
+//! ```
+//! leaked a transfer; starting another one
+//! panicked at '`Twim` transaction started while another one was in progress', (..)
+//! ```
+
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::{
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::twim::Twim;
+use panic_semihosting as _; // panic handler
+
+// SCD30
+const ADDRESS: u8 = 0x61;
+
+#[entry]
+fn main() -> ! {
+    let mut twim = Twim::take();
+
+    task::block_on(async {
+        let mut buf = [0; 3];
+        let mut read = twim.read(ADDRESS, &mut buf);
+        // NOTE(unsafe) `read` must not be moved after this point -- but this program does exactly
+        // that: it starts the transfer and then leaks the future, which never runs its destructor
+        PollOnce(unsafe { Pin::new_unchecked(&mut read) }).await;
+        mem::forget(read);
+
+        hprintln!("leaked a transfer; starting another one").ok();
+
+        let mut buf = [0; 3];
+        let _ = twim.read(ADDRESS, &mut buf).await;
+
+        unreachable!()
+    })
+}
+
+// Polls a future exactly once
+struct PollOnce<'a, F>(Pin<&'a mut F>);
+
+impl<F> Future for PollOnce<'_, F>
+where
+    F: Future,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let _ = self.0.as_mut().poll(cx);
+        Poll::Ready(())
+    }
+}
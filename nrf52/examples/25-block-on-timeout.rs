@@ -0,0 +1,55 @@
+//! Recovering from a stuck `main` future
+//!
+//! Expected output:
+//!
+//! ```
+//! waiting for an event that never happens
+//! timed out; entering safe state
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{
+    task::{self, TimedOut},
+    unsync::Notify,
+};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    led::Red,
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut N: Notify = Notify::new();
+
+    let never: &'static _ = N;
+    let timer = Timer::take();
+
+    let start = Timer::now();
+    let res = task::block_on_timeout(
+        async {
+            hprintln!("waiting for an event that never happens").ok();
+            // nothing calls `notify`
+            never.notified().await;
+        },
+        2.secs(),
+        &timer,
+    );
+
+    // the call must not give up before the deadline
+    if res == Err(TimedOut) && Timer::now() - start >= 2.secs() {
+        hprintln!("timed out; entering safe state").ok();
+        Red.on();
+    }
+
+    loop {
+        asm::bkpt();
+    }
+}
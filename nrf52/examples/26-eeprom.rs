@@ -0,0 +1,51 @@
+//! Write a message that straddles a page boundary into an AT24C256 EEPROM and read it back
+//!
+//! Expected output:
+//!
+//! ```
+//! read back: "hello from the other side of the page"
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::str;
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    eeprom::{self, At24, At24c256, Model as _},
+    timer::Timer,
+    twim::Twim,
+};
+use panic_udf as _; // panic handler
+
+const MESSAGE: &[u8] = b"hello from the other side of the page";
+// 16 bytes go into the first page; the rest into the next one
+const ADDRESS: usize = 2 * At24c256::PAGE_SIZE - 16;
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut eeprom = At24::<At24c256>::new(twim, eeprom::ADDRESS);
+    let mut timer = Timer::take();
+
+    task::block_on(async {
+        eeprom.write(&mut timer, ADDRESS, MESSAGE).await.unwrap();
+
+        let mut buf = [0; MESSAGE.len()];
+        eeprom.read(ADDRESS, &mut buf).await.unwrap();
+
+        hprintln!("read back: {:?}", str::from_utf8(&buf).unwrap()).ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
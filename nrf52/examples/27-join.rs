@@ -0,0 +1,68 @@
+//! Concurrent futures that borrow stack variables
+//!
+//! Unlike `spawn`-ed tasks, the futures passed to `join` can borrow data owned by the caller: no
+//! `static` variables are needed
+//!
+//! Expected output:
+//!
+//! ```
+//! A: send 0
+//! A: send 1
+//! A: send 2
+//! A: send 3
+//! B: recv 0
+//! B: recv 1
+//! B: recv 2
+//! B: recv 3
+//! buffer: [0, 1, 2, 3]; sum: 6
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Channel};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use heapless::consts;
+use nrf52 as _; // memory layout
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    task::block_on(async {
+        // both live on the stack of this (the main) task
+        let c = Channel::<_, consts::U8>::new();
+        let mut buf = [0; 4];
+
+        let (_, sum) = task::join(
+            // A: borrows `c`
+            async {
+                for i in 0..4 {
+                    hprintln!("A: send {}", i).ok();
+                    c.send(i).await.ok();
+                }
+            },
+            // B: borrows `c` and `buf`
+            async {
+                let mut sum = 0;
+                for slot in buf.iter_mut() {
+                    *slot = c.recv().await.expect("UNREACHABLE");
+                    hprintln!("B: recv {}", slot).ok();
+                    sum += *slot;
+                }
+                sum
+            },
+        )
+        .await;
+
+        // both futures have completed; `buf` is no longer borrowed
+        hprintln!("buffer: {:?}; sum: {}", buf, sum).ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
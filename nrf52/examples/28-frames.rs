@@ -0,0 +1,81 @@
+//! Length-prefixed frames over a serial loopback (@ 9600 bauds)
+//!
+//! TXD = P0.06
+//! RXD = P0.08
+//!
+//! Connect TXD to RXD. The frames are sent by one task and received by another one
+//!
+//! Expected output:
+//!
+//! ```
+//! frame: []
+//! frame: [104, 101, 108, 108, 111]
+//! oversize frame: 64 bytes
+//! frame: [255, 255, (..), 255] (32 bytes)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::serial::{self, FrameError, LengthPrefixedReader, LengthPrefixedWriter};
+use panic_udf as _; // panic handler
+
+// largest frame the receiver accepts
+const MAX_LEN: usize = 32;
+
+#[entry]
+fn main() -> ! {
+    let (tx, rx) = serial::take();
+    let mut writer = LengthPrefixedWriter::new(tx);
+    let mut reader = LengthPrefixedReader::new(rx);
+
+    // sender
+    task::spawn(async move {
+        writer.write_frame(&[]).await;
+        writer.write_frame(b"hello").await;
+        writer.write_frame(&[0; 2 * MAX_LEN]).await;
+        writer.write_frame(&[0xff; MAX_LEN]).await;
+
+        loop {
+            task::r#yield().await;
+        }
+    });
+
+    // receiver
+    task::block_on(async {
+        let mut buf = [0; MAX_LEN];
+        for _ in 0..4 {
+            match reader.read_frame(&mut buf).await {
+                Ok(n) if n == MAX_LEN => {
+                    hprintln!(
+                        "frame: [{}, {}, (..), {}] ({} bytes)",
+                        buf[0],
+                        buf[1],
+                        buf[n - 1],
+                        n
+                    )
+                    .ok();
+                }
+                Ok(n) => {
+                    hprintln!("frame: {:?}", &buf[..n]).ok();
+                }
+                Err(FrameError::Oversize { len }) => {
+                    hprintln!("oversize frame: {} bytes", len).ok();
+                }
+                Err(FrameError::Serial(e)) => {
+                    hprintln!("error: {:?}", e).ok();
+                }
+            }
+        }
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
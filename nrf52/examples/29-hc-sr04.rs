@@ -0,0 +1,67 @@
+//! Measuring distances with an HC-SR04 ultrasonic range sensor
+//!
+//! TRIG = P0.03
+//! ECHO = P0.04 (NOTE the sensor runs at 5V; use a level shifter or a voltage divider)
+//!
+//! A 10 us pulse on TRIG starts a measurement; the sensor then drives ECHO high for as long as the
+//! sound took to travel to the obstacle and back (~58 us per cm)
+//!
+//! Expected output:
+//!
+//! ```
+//! distance: 42 cm
+//! distance: 41 cm
+//! out of range
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    gpio::{InputPin, PinState, Port, Pull},
+    pin,
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_udf as _; // panic handler
+
+const TRIG: u8 = 3;
+
+#[entry]
+fn main() -> ! {
+    let mut timer = Timer::take();
+    let mut echo = InputPin::new(pin!(0, 4), Pull::Down).unwrap();
+    Port::P0.write(TRIG, PinState::Low);
+    Port::P0.set_outputs(1 << TRIG);
+
+    task::block_on(async {
+        loop {
+            // ~10 us @ 64 MHz
+            Port::P0.write(TRIG, PinState::High);
+            asm::delay(640);
+            Port::P0.write(TRIG, PinState::Low);
+
+            // the sensor gives up after ~38 ms (no obstacle within 4 m)
+            match echo
+                .measure_pulse(&mut timer, PinState::High, 50.millis())
+                .await
+            {
+                Ok(width) if width.as_millis() < 30 => {
+                    hprintln!("distance: {} cm", width.as_micros() / 58).ok();
+                }
+                _ => {
+                    hprintln!("out of range").ok();
+                }
+            }
+
+            // the datasheet recommends at least 60 ms between measurements
+            timer.wait(100.millis()).await;
+        }
+    })
+}
@@ -0,0 +1,81 @@
+//! Broadcasting a "config changed" event to several tasks
+//!
+//! Expected output:
+//!
+//! ```
+//! main: publish ConfigChanged { period: 2 }
+//! heartbeat: period = 2
+//! logger: config changed (period = 2)
+//! main: publish ConfigChanged { period: 5 }
+//! heartbeat: period = 5
+//! logger: config changed (period = 5)
+//! DONE
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::EventBus};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use heapless::consts;
+use nrf52 as _; // memory layout
+use panic_udf as _; // panic handler
+
+#[derive(Clone, Copy, Debug)]
+enum Event {
+    ConfigChanged { period: u32 },
+}
+
+#[entry]
+fn main() -> ! {
+    static mut B: EventBus<Event, consts::U4> = EventBus::new();
+
+    let bus: &'static _ = B;
+
+    // subscriber
+    let mut events = bus.subscribe();
+    task::spawn(async move {
+        loop {
+            if let Ok(Event::ConfigChanged { period }) = events.next().await {
+                hprintln!("heartbeat: period = {}", period).ok();
+            }
+        }
+    });
+
+    // subscriber
+    let mut events = bus.subscribe();
+    task::spawn(async move {
+        loop {
+            match events.next().await {
+                Ok(Event::ConfigChanged { period }) => {
+                    hprintln!("logger: config changed (period = {})", period).ok();
+                }
+                Err(lagged) => {
+                    hprintln!("logger: missed {} events", lagged.0).ok();
+                }
+            }
+        }
+    });
+
+    // publisher
+    task::block_on(async {
+        for &period in [2, 5].iter() {
+            let event = Event::ConfigChanged { period };
+            hprintln!("main: publish {:?}", event).ok();
+            bus.publish(event);
+
+            // let the subscribers run
+            task::r#yield().await;
+        }
+
+        hprintln!("DONE").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
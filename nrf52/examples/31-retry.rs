@@ -0,0 +1,62 @@
+//! Retrying a flaky sensor read with exponential backoff
+//!
+//! Expected output (sensor disconnected for the first few attempts):
+//!
+//! ```
+//! attempt 1
+//! attempt 2
+//! attempt 3
+//! firmware version: 3.66
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{
+    task::{self, RetryPolicy},
+    unsync::Mutex,
+};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    scd30::Scd30,
+    timer::{ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim: &'static _ = M.get_or_insert(Mutex::new(Twim::take()));
+    let timer = Timer::take();
+
+    task::block_on(async {
+        // waits 100 ms, 200 ms, 400 ms, .. between attempts
+        let policy = RetryPolicy::exponential(8, 100.millis(), 2.secs());
+
+        let mut attempt = 0;
+        let res = task::retry_until(policy, &timer, || {
+            attempt += 1;
+            hprintln!("attempt {}", attempt).ok();
+            // NOTE the future can't borrow from the closure so each attempt uses its own
+            // (zero cost) driver handle
+            let mut scd30 = Scd30::new(twim);
+            async move { scd30.firmware_version().await }
+        })
+        .await;
+
+        match res {
+            Ok((major, minor)) => hprintln!("firmware version: {}.{}", major, minor).ok(),
+            Err(e) => hprintln!("giving up: {:?}", e).ok(),
+        };
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
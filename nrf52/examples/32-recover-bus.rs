@@ -0,0 +1,53 @@
+//! Reading the RTC through a bus that recovers from faults
+//!
+//! To inject a fault briefly short the SDA line (P0.26) to ground while the time is being read.
+//!
+//! Expected output:
+//!
+//! ```
+//! 18:49:30 (recoveries: 0)
+//! 18:49:31 (recoveries: 0)
+//! error: Src(4)
+//! 18:49:33 (recoveries: 1)
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    ds3231::Ds3231,
+    timer::{ext::DurationExt as _, Timer},
+    twim::{RecoveringBus, Twim},
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut B: Option<RecoveringBus> = None;
+
+    let bus: &'static _ = B.get_or_insert(RecoveringBus::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(bus.twim());
+    let mut timer = Timer::take();
+
+    task::block_on(async {
+        loop {
+            match bus.run(ds3231.get_time()).await {
+                Ok(time) => {
+                    hprintln!("{} (recoveries: {})", time, bus.recoveries()).ok();
+                }
+
+                Err(e) => {
+                    hprintln!("error: {:?}", e).ok();
+                }
+            }
+
+            timer.wait(1.secs()).await;
+        }
+    })
+}
@@ -0,0 +1,120 @@
+//! Exercises the interactions between the `unsync` primitives; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! mutex hand-off + channel backpressure: OK
+//! yield fairness: OK
+//! timeout: OK
+//! all checks passed
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::cell::Cell;
+
+use async_embedded::{
+    task::{self, TimedOut},
+    unsync::{Channel, Mutex, Notify},
+};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use heapless::consts;
+use nrf52::timer::{ext::DurationExt as _, Timer};
+use panic_semihosting as _; // panic handler
+
+// more messages than the channel can hold
+const N: u32 = 20;
+
+#[entry]
+fn main() -> ! {
+    let mut timer = Timer::take();
+
+    task::block_on(async {
+        // A sends while holding the mutex and gets blocked by the full channel; B waits for the
+        // mutex; C drains the channel
+        let m = Mutex::new(0);
+        let c = Channel::<_, consts::U8>::new();
+        let sent = Cell::new(0);
+        let received = Cell::new(0);
+
+        task::join(
+            async {
+                let mut guard = m.lock().await;
+                for i in 0..N {
+                    c.send(i).await.ok();
+                    sent.set(sent.get() + 1);
+                    // NOTE the channel holds at most `NTASKS` (8) messages
+                    assert!(sent.get() - received.get() <= 8);
+                }
+                *guard = N;
+            },
+            task::join(
+                async {
+                    task::r#yield().await;
+                    let guard = m.lock().await;
+                    // the mutex was held until all messages were sent
+                    assert_eq!(*guard, N);
+                    assert_eq!(sent.get(), N);
+                },
+                async {
+                    for i in 0..N {
+                        // messages arrive in order
+                        assert_eq!(c.recv().await, Some(i));
+                        received.set(received.get() + 1);
+                    }
+                },
+            ),
+        )
+        .await;
+        assert_eq!(received.get(), N);
+        hprintln!("mutex hand-off + channel backpressure: OK").ok();
+
+        // two tasks that yield after each step advance in lockstep
+        let a = Cell::new(0);
+        let b = Cell::new(0);
+        let step = |mine: &Cell<u32>, theirs: &Cell<u32>| {
+            mine.set(mine.get() + 1);
+            assert!((mine.get() as i32 - theirs.get() as i32).abs() <= 1);
+        };
+        task::join(
+            async {
+                for _ in 0..N {
+                    step(&a, &b);
+                    task::r#yield().await;
+                }
+            },
+            async {
+                for _ in 0..N {
+                    step(&b, &a);
+                    task::r#yield().await;
+                }
+            },
+        )
+        .await;
+        hprintln!("yield fairness: OK").ok();
+
+        // a timeout cancels a future that waits on a primitive that's never signaled
+        let never = Notify::new();
+        let deadline = Timer::now() + 100.millis();
+        let res = timer.timeout_at(deadline, never.notified()).await;
+        assert!(res == Err(TimedOut));
+        // the notifier has no stale waiter left behind
+        never.notify();
+        timer
+            .timeout_at(Timer::now() + 100.millis(), never.notified())
+            .await
+            .unwrap();
+        hprintln!("timeout: OK").ok();
+
+        hprintln!("all checks passed").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,46 @@
+//! Telling apart devices that share an address by their identification register
+//!
+//! The BMP280 (pressure) and BME280 (pressure + humidity) sensors both live at address 0x76 and
+//! report a different chip ID in register 0xD0
+//!
+//! Expected output (with a BME280 connected):
+//!
+//! ```
+//! found a BME280; humidity is available
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::twim::Twim;
+use panic_udf as _; // panic handler
+
+const ADDRESS: u8 = 0x76;
+const CHIP_ID: u8 = 0xd0;
+
+#[entry]
+fn main() -> ! {
+    let mut twim = Twim::take();
+
+    task::block_on(async {
+        if twim.identify(ADDRESS, CHIP_ID, &[0x60]).await.unwrap() {
+            hprintln!("found a BME280; humidity is available").ok();
+        } else if twim.identify(ADDRESS, CHIP_ID, &[0x58]).await.unwrap() {
+            hprintln!("found a BMP280; no humidity sensor").ok();
+        } else if twim.probe(ADDRESS).await.unwrap() {
+            hprintln!("unknown device at {:#04x}", ADDRESS).ok();
+        } else {
+            hprintln!("no sensor found").ok();
+        }
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,48 @@
+//! Liveness beacon sent over the serial line while another task echoes back the input
+//!
+//! TXD = P0.06
+//! RXD = P0.08
+//!
+//! Expected output (with local echo disabled; typing "hi" after a few seconds):
+//!
+//! ```
+//! alive
+//! hialive
+//! alive
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m_rt::entry;
+use nrf52::{
+    serial::{self, Beacon, Tx},
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Tx>> = None;
+
+    let (tx, mut rx) = serial::take();
+    let tx: &'static _ = M.get_or_insert(Mutex::new(tx));
+
+    let mut timer = Timer::take();
+    task::spawn(async move { Beacon::new(tx, b"alive\n", 5.secs()).run(&mut timer).await });
+
+    // the beacon only holds the transmitter while it's sending; the echo stays responsive
+    task::block_on(async {
+        let mut buf = [0; 1];
+        loop {
+            // a byte was lost; there's nothing to echo back
+            if rx.read(&mut buf).await.is_ok() {
+                tx.lock().await.write(&buf).await;
+            }
+        }
+    })
+}
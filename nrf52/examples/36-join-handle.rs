@@ -0,0 +1,42 @@
+//! Awaiting the output of a `spawn`-ed task
+//!
+//! Expected output:
+//!
+//! ```
+//! A: waiting for B
+//! B: computing
+//! A: B returned 42
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::timer::{ext::DurationExt as _, Timer};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let mut timer = Timer::take();
+
+    let b = task::spawn(async move {
+        hprintln!("B: computing").ok();
+        timer.wait(1.secs()).await;
+        42
+    });
+
+    task::block_on(async {
+        hprintln!("A: waiting for B").ok();
+        let answer = b.await;
+        hprintln!("A: B returned {}", answer).ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
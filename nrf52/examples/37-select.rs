@@ -0,0 +1,47 @@
+//! Echo back data received over the serial line; report when the line has been idle for a while
+//!
+//! TXD = P0.06
+//! RXD = P0.08
+//!
+//! Expected output (with local echo disabled; typing "hi" and then waiting):
+//!
+//! ```
+//! hi
+//! (idle)
+//! (idle)
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task::{self, Either};
+use cortex_m_rt::entry;
+use nrf52::{
+    serial,
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let (mut tx, mut rx) = serial::take();
+    let mut timer = Timer::take();
+
+    task::block_on(async {
+        let mut buf = [0; 1];
+        loop {
+            match task::select(rx.read(&mut buf), timer.wait(5.secs())).await {
+                Either::Left(Ok(())) => tx.write(&buf).await,
+
+                // a byte was lost; there's nothing to echo back
+                Either::Left(Err(_)) => {}
+
+                // the `read` was cancelled; no data was lost because the line was idle
+                Either::Right(()) => tx.write(b"\n(idle)\n").await,
+            }
+        }
+    })
+}
@@ -0,0 +1,58 @@
+//! Cancelling a DMA transfer with a timeout doesn't corrupt memory
+//!
+//! TXD = P0.06
+//! RXD = P0.08
+//!
+//! Expected output (typing "a" after "timed out" is printed):
+//!
+//! ```
+//! timed out
+//! received 'a'
+//! stale buffer untouched: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task::{self, Either};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    serial,
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_semihosting as _; // panic handler
+
+const CANARY: u8 = 0xaa;
+
+#[entry]
+fn main() -> ! {
+    let (_tx, mut rx) = serial::take();
+    let mut timer = Timer::take();
+
+    task::block_on(async {
+        // DMA target of the cancelled transfer
+        let mut stale = [CANARY; 16];
+
+        match task::select(rx.read(&mut stale), timer.wait(1.secs())).await {
+            Either::Left(_) => panic!("nothing should have been sent yet"),
+            // the `read` has been dropped, and the transfer stopped, by now
+            Either::Right(()) => hprintln!("timed out").ok(),
+        };
+
+        let mut fresh = [0; 1];
+        rx.read(&mut fresh).await.unwrap();
+        hprintln!("received {:?}", fresh[0] as char).ok();
+
+        // the DMA would have written the byte here if the first transfer was still running
+        assert!(stale.iter().all(|b| *b == CANARY));
+        hprintln!("stale buffer untouched: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
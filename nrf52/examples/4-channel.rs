@@ -20,12 +20,13 @@ use async_embedded::{task, unsync::Channel};
 use cortex_m::asm;
 use cortex_m_rt::entry;
 use cortex_m_semihosting::hprintln;
+use heapless::consts;
 use nrf52 as _; // memory layout
 use panic_udf as _; // panic handler
 
 #[entry]
 fn main() -> ! {
-    static mut C: Channel<i32> = Channel::new();
+    static mut C: Channel<i32, consts::U8> = Channel::new();
 
     // coerce to a shared (`&-`) reference to avoid _one_ of the `move` blocks taking ownership of
     // the owning static (`&'static mut`) reference
@@ -34,7 +35,7 @@ fn main() -> ! {
     task::spawn(async move {
         hprintln!("A: before send").ok();
 
-        c.send(42).await;
+        c.send(42).await.ok();
 
         hprintln!("A: after send").ok();
 
@@ -48,7 +49,7 @@ fn main() -> ! {
         hprintln!("B: before recv").ok();
 
         // cannot immediately make progress; context switch to A
-        let msg = c.recv().await;
+        let msg = c.recv().await.expect("UNREACHABLE");
 
         hprintln!("B: {}", msg).ok();
 
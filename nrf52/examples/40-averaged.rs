@@ -0,0 +1,39 @@
+//! Smoothing the sensor readings with a moving average
+//!
+//! Expected output:
+//!
+//! ```
+//! CO2: 652 ppm, T: 26.3 °C, RH: 23%
+//! CO2: 655 ppm, T: 26.3 °C, RH: 23%
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use heapless::consts;
+use nrf52::{scd30::Scd30, twim::Twim};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim: &'static _ = M.get_or_insert(Mutex::new(Twim::take()));
+    // the sensor produces a measurement every 2 seconds; average over the last 10 seconds
+    let mut scd30 = Scd30::new(twim).averaged::<consts::U8>(5);
+
+    task::block_on(async {
+        loop {
+            match scd30.get_measurement().await {
+                Ok(m) => hprintln!("{}", m).ok(),
+                Err(e) => hprintln!("error: {:?}", e).ok(),
+            };
+        }
+    })
+}
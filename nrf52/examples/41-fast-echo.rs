@@ -0,0 +1,33 @@
+//! Echo back data received over the serial line @ 115200 bauds
+//!
+//! TXD = P0.06
+//! RXD = P0.08
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use nrf52::serial::{self, Baudrate, Config};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let (mut tx, mut rx) = serial::take_with(Config {
+        baudrate: Baudrate::Baud115200,
+        ..Config::default()
+    })
+    .unwrap();
+
+    task::block_on(async {
+        let mut buf = [0; 1];
+        loop {
+            // a byte was lost; there's nothing to echo back
+            if rx.read(&mut buf).await.is_ok() {
+                tx.write(&buf).await;
+            }
+        }
+    })
+}
@@ -0,0 +1,42 @@
+//! Trading a driver for direct, but still exclusive, access to its peripheral
+//!
+//! Expected output:
+//!
+//! ```
+//! TWIM frequency: 0x01980000
+//! firmware version: 3.66
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{scd30::Scd30, twim::Twim};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    // while we hold the token the driver doesn't exist so it can't touch the registers
+    let mut token = Twim::take().into_token();
+    let frequency = token.registers(|twim| twim.frequency.read().bits());
+    hprintln!("TWIM frequency: {:#010x}", frequency).ok();
+
+    let twim: &'static _ = M.get_or_insert(Mutex::new(Twim::from_token(token)));
+    let mut scd30 = Scd30::new(twim);
+
+    task::block_on(async {
+        let (major, minor) = scd30.firmware_version().await.unwrap();
+        hprintln!("firmware version: {}.{}", major, minor).ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,80 @@
+//! Checks that a fair mutex is acquired in FIFO order; panics if a check fails
+//!
+//! Tasks 1, 2 and 3 start waiting on the mutex, in that order, while task 4 holds it. Task 4
+//! then releases the mutex and immediately tries to lock it again.
+//!
+//! Expected output:
+//!
+//! ```
+//! fair: 1234
+//! unfair: 4123
+//! all checks passed
+//! ```
+//!
+//! The fair mutex hands the lock over to the waiters in order so task 4 has to queue behind
+//! them. The unfair mutex makes no ordering guarantee; with it task 4 happens to barge in
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::cell::Cell;
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52 as _; // memory layout
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    task::block_on(async {
+        let fair = acquisition_order(&Mutex::new_fair(())).await;
+        hprintln!("fair: {}", fair).ok();
+        assert_eq!(fair, 1234);
+
+        // NOTE no assertion; any order is valid
+        let unfair = acquisition_order(&Mutex::new(())).await;
+        hprintln!("unfair: {}", unfair).ok();
+
+        hprintln!("all checks passed").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
+
+// Returns the IDs of the tasks in the order they acquired the mutex, as decimal digits
+async fn acquisition_order(m: &Mutex<()>) -> u32 {
+    let log = Cell::new(0);
+    let waiter = |id: u32| {
+        let log = &log;
+        async move {
+            let _guard = m.lock().await;
+            log.set(log.get() * 10 + id);
+            // hold the lock for a while
+            task::r#yield().await;
+        }
+    };
+
+    task::join(
+        async {
+            let guard = m.lock().await;
+            // let the other tasks queue up
+            for _ in 0..3 {
+                task::r#yield().await;
+            }
+            drop(guard);
+
+            let _guard = m.lock().await;
+            log.set(log.get() * 10 + 4);
+        },
+        task::join(waiter(1), task::join(waiter(2), waiter(3))),
+    )
+    .await;
+
+    log.get()
+}
@@ -0,0 +1,58 @@
+//! Phase-locked blinking with `Timer::interval`; panics if a check fails
+//!
+//! Every tick does some busy work to simulate servicing latency. The deadlines are still
+//! exactly one period (3,276 ticks of the 32,768 Hz clock) apart
+//!
+//! Expected output:
+//!
+//! ```
+//! period: 3276 ticks
+//! 100 ticks: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    led::Red,
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let mut timer = Timer::take();
+
+    task::block_on(async {
+        let mut interval = timer.interval(100.millis());
+        let mut prev = interval.tick().await;
+        let period = interval.next_deadline().ticks() - prev.ticks();
+        hprintln!("period: {} ticks", period).ok();
+
+        for i in 0..100 {
+            if i % 2 == 0 {
+                Red.on();
+            } else {
+                Red.off();
+            }
+
+            // simulated servicing latency; roughly 1 ms at 64 MHz
+            asm::delay(64_000);
+
+            let deadline = interval.tick().await;
+            assert_eq!(deadline.ticks() - prev.ticks(), period);
+            prev = deadline;
+        }
+        hprintln!("100 ticks: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,77 @@
+//! Logs the drift of the DS3231 clock relative to the nRF52 RTC over the serial line
+//!
+//! TXD = P0.06
+//!
+//! Expected output (the numbers will vary):
+//!
+//! ```
+//! window: 61s, offset: 0ms, drift: 0.0ppm
+//! window: 122s, offset: 1ms, drift: 8.2ppm
+//! window: 183s, offset: 1ms, drift: 5.5ppm
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::fmt::Write as _;
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m_rt::entry;
+use heapless::{consts, String};
+use nrf52::{
+    drift::DriftMonitor,
+    ds3231::Ds3231,
+    serial,
+    timer::{ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+    let (mut tx, _rx) = serial::take();
+    let mut timer = Timer::take();
+
+    task::block_on(async {
+        let mut monitor = DriftMonitor::new();
+        let mut s = String::<consts::U64>::new();
+
+        loop {
+            match monitor.update(&mut ds3231).await {
+                Ok(Some(drift)) => {
+                    s.clear();
+                    // will not fail; the buffer is big enough
+                    let _ = writeln!(
+                        &mut s,
+                        "window: {}s, offset: {}ms, drift: {:.1}ppm",
+                        drift.window.as_secs(),
+                        drift.offset_ms,
+                        drift.ppm
+                    );
+                    tx.write(s.as_bytes()).await;
+                }
+
+                // start of a new window
+                Ok(None) => {}
+
+                Err(e) => {
+                    s.clear();
+                    let _ = writeln!(&mut s, "error: {:?}", e);
+                    tx.write(s.as_bytes()).await;
+                    monitor.reset();
+                }
+            }
+
+            // NOTE this also keeps `Timer::now` (called by `update`) running at least once every
+            // 512 seconds
+            timer.wait(60.secs()).await;
+        }
+    })
+}
@@ -0,0 +1,64 @@
+//! Several tasks waiting on the same timer at the same time; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! 50ms: woken after 49ms
+//! 80ms: woken after 79ms
+//! all checks passed
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::cell::Cell;
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::timer::{ext::DurationExt as _, Timer};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let timer = Timer::take();
+
+    task::block_on(async {
+        let order = Cell::new(0);
+        let start = Timer::now();
+
+        // the waits overlap; the longer one starts first
+        let (long, short) = task::join(
+            async {
+                timer.wait(80.millis()).await;
+                order.set(order.get() * 10 + 2);
+                start.elapsed()
+            },
+            async {
+                timer.wait(50.millis()).await;
+                order.set(order.get() * 10 + 1);
+                start.elapsed()
+            },
+        )
+        .await;
+
+        hprintln!("50ms: woken after {}ms", short.as_millis()).ok();
+        hprintln!("80ms: woken after {}ms", long.as_millis()).ok();
+
+        // the 50 ms wait completed first
+        assert_eq!(order.get(), 12);
+        // neither wait was cut short or delayed by the other one
+        // NOTE the waits are rounded down to whole ticks (~30.5 us)
+        assert!(short >= 49.millis() && short < 52.millis());
+        assert!(long >= 79.millis() && long < 82.millis());
+
+        hprintln!("all checks passed").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
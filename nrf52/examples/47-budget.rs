@@ -0,0 +1,47 @@
+//! Enforcing a per-cycle time budget; panics if fewer or more jobs than expected are done
+//!
+//! Expected output:
+//!
+//! ```
+//! cycle: 3 of 5 jobs done
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::timer::{ext::DurationExt as _, Budget, Timer};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let timer = Timer::take();
+
+    task::block_on(async {
+        // 5 jobs of 4 ms each but only 10 ms to spend on them: the third job overdraws the
+        // budget and the last two are skipped
+        let mut budget = Budget::new(10.millis());
+        let mut done = 0;
+        for _ in 0..5 {
+            if budget.is_exhausted() {
+                break;
+            }
+
+            let start = Timer::now();
+            timer.wait(4.millis()).await;
+            budget.charge_since(start);
+            done += 1;
+        }
+        hprintln!("cycle: {} of 5 jobs done", done).ok();
+        assert_eq!(done, 3);
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,55 @@
+//! A wait longer than one period (512 s) of the RTC counter; panics if a check fails
+//!
+//! Expected output (after 10 minutes):
+//!
+//! ```
+//! waited 600s; the counter overflowed 1 time(s)
+//! all checks passed
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::timer::{ext::DurationExt as _, Timer};
+use panic_semihosting as _; // panic handler
+
+// ticks of the 32,768 Hz clock in 10 minutes
+const TEN_MINUTES: u64 = 600 * 32_768;
+
+#[entry]
+fn main() -> ! {
+    let timer = Timer::take();
+
+    task::block_on(async {
+        let start = Timer::now();
+        timer.wait(600.secs()).await;
+        let end = Timer::now();
+
+        // the 24-bit counter overflows every 2^24 ticks
+        let overflows = (end.ticks() >> 24) - (start.ticks() >> 24);
+        let expected = ((start.ticks() + TEN_MINUTES) >> 24) - (start.ticks() >> 24);
+        hprintln!(
+            "waited {}s; the counter overflowed {} time(s)",
+            end.duration_since(start).as_secs(),
+            overflows
+        )
+        .ok();
+
+        assert!(end.ticks() - start.ticks() >= TEN_MINUTES);
+        // NOTE 10 minutes is 1.17 periods of the counter
+        assert!(expected == 1 || expected == 2);
+        assert_eq!(overflows, expected);
+
+        hprintln!("all checks passed").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
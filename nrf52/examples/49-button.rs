@@ -0,0 +1,56 @@
+//! Waiting for button presses
+//!
+//! Button 1 of the nRF52840-DK is connected to P0.11; it shorts the pin to ground when pressed.
+//! Each press toggles the LED
+//!
+//! Expected output (pressing the button 3 times):
+//!
+//! ```
+//! press #1
+//! press #2
+//! press #3
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    gpio::{Edge, InputPin, Pull},
+    led::Red,
+    pin,
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    // the button has no external pull-up resistor
+    let mut button = InputPin::new(pin!(0, 11), Pull::Up).unwrap();
+    let timer = Timer::take();
+
+    task::block_on(async {
+        let mut presses = 0;
+        loop {
+            button.wait_for_edge(Edge::Falling).await;
+            presses += 1;
+            hprintln!("press #{}", presses).ok();
+
+            if presses % 2 == 1 {
+                Red.on();
+            } else {
+                Red.off();
+            }
+
+            // debounce: ignore the bounces of the press and wait for the release
+            timer.wait(20.millis()).await;
+            button.wait_for_high().await;
+            timer.wait(20.millis()).await;
+        }
+    })
+}
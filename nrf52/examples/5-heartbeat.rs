@@ -15,18 +15,19 @@
 #![no_main]
 #![no_std]
 
-use core::time::Duration;
-
 use async_embedded::task;
 use cortex_m_rt::entry;
-use nrf52::{led::Red, timer::Timer};
+use nrf52::{
+    led::Red,
+    timer::{ext::DurationExt as _, Timer},
+};
 use panic_udf as _; // panic handler
 
 #[entry]
 fn main() -> ! {
     let mut timer = Timer::take();
 
-    let dur = Duration::from_millis(100);
+    let dur = 100.millis();
     task::block_on(async {
         loop {
             Red.on();
@@ -0,0 +1,63 @@
+//! A task that pumps serial input lines into a channel feeding a command-processing task
+//!
+//! TXD = P0.06
+//! RXD = P0.08
+//!
+//! Scripted input can be fed from the host, e.g. `printf 'ping\rsum 1 2\rnope\r' > /dev/ttyACM0`
+//!
+//! Expected output:
+//!
+//! ```
+//! pong
+//! 3
+//! unknown command: nope
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::fmt::Write as _;
+
+use async_embedded::{task, unsync::Channel};
+use cortex_m_rt::entry;
+use heapless::{consts, String};
+use nrf52::serial;
+use panic_udf as _; // panic handler
+
+type Line = String<consts::U32>;
+
+#[entry]
+fn main() -> ! {
+    static mut LINES: Channel<Line, consts::U8> = Channel::new();
+
+    let lines: &'static _ = LINES;
+    let (mut tx, rx) = serial::take();
+
+    task::spawn(serial::pump_lines(rx, lines));
+
+    task::block_on(async {
+        let mut reply = String::<consts::U64>::new();
+        loop {
+            let line = match lines.recv().await {
+                Some(line) => line,
+                // NOTE the channel is never closed
+                None => continue,
+            };
+
+            reply.clear();
+            let mut words = line.split_whitespace();
+            // NOTE the buffer is big enough for all the replies
+            let _ = match (words.next(), words.next(), words.next()) {
+                (Some("ping"), None, _) => writeln!(&mut reply, "pong"),
+                (Some("sum"), Some(a), Some(b)) => match (a.parse::<i32>(), b.parse::<i32>()) {
+                    (Ok(a), Ok(b)) => writeln!(&mut reply, "{}", a.wrapping_add(b)),
+                    _ => writeln!(&mut reply, "usage: sum <a> <b>"),
+                },
+                _ => writeln!(&mut reply, "unknown command: {}", line),
+            };
+            tx.write(reply.as_bytes()).await;
+        }
+    })
+}
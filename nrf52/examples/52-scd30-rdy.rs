@@ -0,0 +1,76 @@
+//! Polling the SCD30 vs waiting on its RDY pin
+//!
+//! The RDY pin of the sensor must be connected to P0.03. The sensor produces a measurement every
+//! 2 seconds. While polling, the executor never gets to sleep between measurements; while waiting
+//! on the RDY pin it sleeps (`WFE`) until the pin goes high. The number of times the executor went
+//! to sleep per measurement is a proxy for the power savings
+//!
+//! Expected output (the numbers will vary):
+//!
+//! ```
+//! polling: CO2 = 652ppm; slept 0 times
+//! polling: CO2 = 655ppm; slept 0 times
+//! polling: CO2 = 651ppm; slept 0 times
+//! RDY pin: CO2 = 650ppm; slept 2 times
+//! RDY pin: CO2 = 652ppm; slept 1 times
+//! RDY pin: CO2 = 653ppm; slept 1 times
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    gpio::{InputPin, Pull},
+    pin,
+    scd30::Scd30,
+    timer::Timer,
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+static SLEEPS: AtomicU32 = AtomicU32::new(0);
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+    static mut T: Option<Timer> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let timer = T.get_or_insert(Timer::take());
+    // RDY is push-pull
+    let rdy = InputPin::new(pin!(0, 3), Pull::None).unwrap();
+
+    task::set_idle_hook(|| {
+        SLEEPS.fetch_add(1, Ordering::Relaxed);
+    });
+
+    task::block_on(async {
+        let mut scd30 = Scd30::new(twim);
+        for _ in 0..3 {
+            SLEEPS.store(0, Ordering::Relaxed);
+            let m = scd30.get_measurement().await.unwrap();
+            let sleeps = SLEEPS.load(Ordering::Relaxed);
+            hprintln!("polling: CO2 = {}ppm; slept {} times", m.co2 as u16, sleeps).ok();
+        }
+
+        let mut scd30 = Scd30::with_rdy_pin(twim, rdy, timer);
+        for _ in 0..3 {
+            SLEEPS.store(0, Ordering::Relaxed);
+            let m = scd30.get_measurement().await.unwrap();
+            let sleeps = SLEEPS.load(Ordering::Relaxed);
+            hprintln!("RDY pin: CO2 = {}ppm; slept {} times", m.co2 as u16, sleeps).ok();
+        }
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,65 @@
+//! SPIM loopback; panics if a check fails
+//!
+//! Connect MOSI (P1.13) to MISO (P1.14); on the nRF52833 and nRF52832 that's P0.23 to P0.24
+//!
+//! Expected output:
+//!
+//! ```
+//! transfer: OK
+//! long transfer: OK
+//! flash write: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::spim::{Config, Frequency, Spim};
+use panic_semihosting as _; // panic handler
+
+// lives in Flash; `write` has to copy it into RAM
+static GREETING: [u8; 5] = *b"hello";
+
+#[entry]
+fn main() -> ! {
+    static mut LONG: [u8; 600] = [0; 600];
+
+    let mut spim = Spim::take_with(Config {
+        frequency: Frequency::M8,
+        ..Config::default()
+    })
+    .unwrap();
+    let long = LONG;
+
+    task::block_on(async {
+        let pattern = [0x00, 0xff, 0x55, 0xaa, 0x01, 0x80, 0xde, 0xad];
+        let mut buf = pattern;
+        spim.transfer(&mut buf).await;
+        assert_eq!(buf, pattern);
+        hprintln!("transfer: OK").ok();
+
+        // spans three DMA transfers
+        for (i, byte) in long.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        spim.transfer(long).await;
+        assert!(long.iter().enumerate().all(|(i, byte)| *byte == i as u8));
+        hprintln!("long transfer: OK").ok();
+
+        spim.write(&GREETING).await;
+        // the SPIM is still usable after a bounced write
+        let mut buf = [0x42];
+        spim.transfer(&mut buf).await;
+        assert_eq!(buf, [0x42]);
+        hprintln!("flash write: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
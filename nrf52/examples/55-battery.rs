@@ -0,0 +1,47 @@
+//! Prints the supply voltage once per second
+//!
+//! Measuring VDD directly works when the board runs straight off a battery (e.g. a coin cell).
+//! For a higher voltage battery, connect it to AIN0 (P0.02) through a resistor divider and use
+//! `Channel::single_ended(Input::Ain0)`; then scale the result by the divider ratio
+//!
+//! Expected output:
+//!
+//! ```
+//! battery: 3012 mV
+//! battery: 3010 mV
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    saadc::{Channel, Input, Saadc},
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_udf as _; // panic handler
+
+// 1/6 gain and the internal 0.6 V reference: 0 - 3.6 V input range
+const BATTERY: Channel = Channel::single_ended(Input::Vdd);
+
+#[entry]
+fn main() -> ! {
+    let mut saadc = Saadc::take();
+    let timer = Timer::take();
+
+    task::block_on(async {
+        loop {
+            let sample = saadc.read(BATTERY).await;
+            // NOTE `BATTERY` uses the internal reference so the conversion can't fail
+            let mv = BATTERY.millivolts(sample).unwrap();
+            hprintln!("battery: {} mV", mv).ok();
+
+            timer.wait(1.secs()).await;
+        }
+    })
+}
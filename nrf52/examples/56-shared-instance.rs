@@ -0,0 +1,54 @@
+//! Checks that the TWIM and the SPIM can't both use the `Serial0` instance; panics if a check
+//! fails
+//!
+//! Expected output:
+//!
+//! ```
+//! SPIM on Serial0 after TWIM: OK
+//! second TWIM: OK
+//! SPIM on Serial2: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    instance::{InUse, Serial},
+    spim::{self, Config, Spim},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let _twim = Twim::take();
+
+    let serial0 = Config {
+        instance: Serial::Serial0,
+        ..Config::default()
+    };
+    match Spim::take_with(serial0) {
+        Err(spim::Error::InUse) => {}
+        _ => panic!("the SPIM took the instance of the TWIM"),
+    }
+    hprintln!("SPIM on Serial0 after TWIM: OK").ok();
+
+    match Twim::try_take() {
+        Err(InUse) => {}
+        Ok(_) => panic!("the TWIM was taken twice"),
+    }
+    hprintln!("second TWIM: OK").ok();
+
+    // the failed attempt didn't take the SPIM
+    let _spim = Spim::take_with(Config::default()).unwrap();
+    hprintln!("SPIM on Serial2: OK").ok();
+
+    loop {
+        asm::bkpt();
+    }
+}
@@ -0,0 +1,43 @@
+//! Echoes back lines of 8 bytes; on a reception error, echoes back the bytes received before the
+//! error and reports it
+//!
+//! Sending a break or using the wrong baud rate on the host produces an error
+//!
+//! TXD = P0.06
+//! RXD = P0.08
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use nrf52::serial::{self, PartialRead};
+use panic_udf as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let (mut tx, mut rx) = serial::take();
+
+    task::block_on(async {
+        let mut buf = [0; 8];
+        loop {
+            match rx.read_partial(&mut buf).await {
+                Ok(()) => tx.write(&buf).await,
+
+                Err(PartialRead { error, received }) => {
+                    tx.write(&buf[..received]).await;
+
+                    let msg: &[u8] = match error {
+                        serial::Error::Overrun => b" <overrun>\n",
+                        serial::Error::Parity => b" <parity>\n",
+                        serial::Error::Framing => b" <framing>\n",
+                        serial::Error::Break => b" <break>\n",
+                    };
+                    tx.write(msg).await;
+                }
+            }
+        }
+    })
+}
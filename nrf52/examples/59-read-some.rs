@@ -0,0 +1,54 @@
+//! `Rx::read_some` returns once the line goes quiet; panics if a check fails
+//!
+//! Connect TXD (P0.28) to RXD (P0.29)
+//!
+//! Expected output:
+//!
+//! ```
+//! received 3 bytes: [1, 2, 3]
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    pin,
+    serial::{self, Baudrate, Config},
+    timer::{ext::DurationExt as _, Timer},
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let (mut tx, mut rx) = serial::take_with(Config {
+        baudrate: Baudrate::Baud115200,
+        tx: pin!(0, 28),
+        rx: pin!(0, 29),
+    })
+    .unwrap();
+    let timer = Timer::take();
+
+    task::spawn(async move {
+        // 3 bytes and then silence
+        tx.write(&[1, 2, 3]).await;
+    });
+
+    task::block_on(async {
+        let mut buf = [0; 16];
+        // one byte takes less than 100 us at 115200 bauds
+        let n = rx.read_some(&mut buf, &timer, 5.millis()).await.unwrap();
+
+        hprintln!("received {} bytes: {:?}", n, &buf[..n]).ok();
+        assert_eq!(&buf[..n], &[1, 2, 3]);
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
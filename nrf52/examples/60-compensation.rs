@@ -0,0 +1,52 @@
+//! Keeps the temperature readings of the SCD30 in line with the temperature of the DS3231
+//!
+//! Both devices share the I2C bus. `Scd30::compensate` runs the loop below as a single task
+//!
+//! Expected output (the numbers will vary):
+//!
+//! ```
+//! offset: 1.80 C
+//! offset: 1.92 C
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    ds3231::Ds3231,
+    scd30::Scd30,
+    timer::{ext::DurationExt as _, Timer},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut scd30 = Scd30::new(twim);
+    let mut ds3231 = Ds3231::new(twim);
+    let timer = Timer::take();
+
+    task::block_on(async {
+        loop {
+            match scd30.compensate_once(&mut ds3231).await {
+                Ok(offset) => {
+                    hprintln!("offset: {:.2} C", offset).ok();
+                }
+                Err(e) => {
+                    hprintln!("error: {:?}", e).ok();
+                }
+            }
+
+            timer.wait(300.secs()).await;
+        }
+    })
+}
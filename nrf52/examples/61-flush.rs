@@ -0,0 +1,52 @@
+//! `Tx::flush` waits for the last byte to leave the transmitter; panics if a check fails
+//!
+//! At 9600 bps a frame takes ~1 ms, about 34 ticks of the 32,768 Hz clock
+//!
+//! TXD = P0.06
+//!
+//! Expected output (the numbers will vary slightly):
+//!
+//! ```
+//! flush after write: 35 ticks
+//! flush with nothing written: 0 ticks
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{serial, timer::Timer};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let (mut tx, _rx) = serial::take();
+
+    task::block_on(async {
+        tx.write(b"Hello, world!\n").await;
+
+        // `write` completed on ENDTX; the last byte is still on its way out
+        let start = Timer::now();
+        tx.flush().await;
+        let after_write = Timer::now().ticks() - start.ticks();
+
+        let start = Timer::now();
+        tx.flush().await;
+        let idle = Timer::now().ticks() - start.ticks();
+
+        hprintln!("flush after write: {} ticks", after_write).ok();
+        hprintln!("flush with nothing written: {} ticks", idle).ok();
+        // at least half a frame
+        assert!(after_write >= 16);
+        assert!(idle <= 1);
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,91 @@
+//! Reads the DS3231 temperature once per minute with the I2C bus disabled in between; panics if
+//! the enable / disable sequencing is wrong
+//!
+//! Expected output (the numbers will vary):
+//!
+//! ```
+//! sequencing: OK
+//! 23.25 C (enabled 3 times)
+//! 23.25 C (enabled 4 times)
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    timer::{ext::DurationExt as _, Timer},
+    twim::{PowerGated, Twim},
+};
+use panic_semihosting as _; // panic handler
+
+const DS3231: u8 = 0b110_1000;
+const TEMP_MSB: u8 = 0x11;
+
+#[entry]
+fn main() -> ! {
+    static mut G: Option<PowerGated> = None;
+
+    let bus = G.get_or_insert(PowerGated::new(Twim::take()));
+    let timer = Timer::take();
+
+    task::block_on(async {
+        check_sequencing(bus).await;
+        hprintln!("sequencing: OK").ok();
+
+        loop {
+            let raw = {
+                let mut twim = bus.lock().await;
+                twim.read_reg_u16(DS3231, TEMP_MSB).await
+            };
+            assert!(!bus.is_enabled());
+
+            match raw {
+                // 10-bit two's complement value in the upper bits; 0.25 C resolution
+                Ok(raw) => {
+                    let celsius = f32::from(raw as i16 >> 6) / 4.;
+                    hprintln!("{:.2} C (enabled {} times)", celsius, bus.cycles()).ok();
+                }
+                Err(e) => {
+                    hprintln!("error: {:?}", e).ok();
+                }
+            }
+
+            timer.wait(60.secs()).await;
+        }
+    })
+}
+
+async fn check_sequencing(bus: &PowerGated) {
+    // disabled until first used
+    assert!(!bus.is_enabled());
+    assert_eq!(bus.cycles(), 0);
+
+    // enabled only while locked
+    {
+        let _twim = bus.lock().await;
+        assert!(bus.is_enabled());
+        assert_eq!(bus.cycles(), 1);
+    }
+    assert!(!bus.is_enabled());
+
+    // back-to-back users share a single enable / disable cycle
+    let a = async {
+        let _twim = bus.lock().await;
+        // let the other task queue up for the bus
+        task::r#yield().await;
+        assert!(bus.is_enabled());
+    };
+    let b = async {
+        let _twim = bus.lock().await;
+        assert!(bus.is_enabled());
+    };
+    task::join(a, b).await;
+    assert!(!bus.is_enabled());
+    assert_eq!(bus.cycles(), 2);
+}
@@ -0,0 +1,39 @@
+//! Panics after a few seconds; the red LED fast-blinks and then stays on
+//!
+//! The panic handler is provided by the `panic-led` feature
+//!
+//! Expected output:
+//!
+//! ```
+//! panicking in 3 seconds
+//! ```
+//!
+//! NOTE the panic message is not printed anywhere; with a debugger attached the core halts on the
+//! `UDF` instruction once the LED stops blinking
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    led::Green,
+    timer::{ext::DurationExt as _, Timer},
+};
+
+#[entry]
+fn main() -> ! {
+    let timer = Timer::take();
+
+    task::block_on(async {
+        hprintln!("panicking in 3 seconds").ok();
+        Green.on();
+        timer.wait(3.secs()).await;
+        Green.off();
+    });
+
+    panic!("deliberate panic")
+}
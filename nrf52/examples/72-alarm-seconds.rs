@@ -0,0 +1,67 @@
+//! Sets a DS3231 alarm 10 seconds out and turns the green LED on when it fires
+//!
+//! The INT/SQW pin of the DS3231 must be connected to P0.02. Alarm 1 is matched on the seconds
+//! alone, which is enough for alarms less than a minute out; Alarm 2 fires at the start of every
+//! minute
+//!
+//! Expected output (the times will vary):
+//!
+//! ```
+//! now: 12:34:56; alarm 1 @ second 6
+//! Fired { alarm1: false, alarm2: true }
+//! Fired { alarm1: true, alarm2: false }
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use chrono::Timelike as _;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    ds3231::{Alarm1, Alarm2, Ds3231},
+    gpio::{InputPin, Pull},
+    led::Green,
+    pin,
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+    // INT/SQW is open drain
+    let mut int = InputPin::new(pin!(0, 2), Pull::Up).unwrap();
+
+    task::block_on(async {
+        // start with the INT pin released
+        ds3231.clear_alarm_flags().await.unwrap();
+
+        let now = ds3231.get_time().await.unwrap();
+        let sec = ((now.second() + 10) % 60) as u8;
+        ds3231.set_alarm1(Alarm1::Second(sec)).await.unwrap();
+        ds3231.set_alarm2(Alarm2::EveryMinute).await.unwrap();
+        hprintln!("now: {}; alarm 1 @ second {}", now, sec).ok();
+
+        loop {
+            let fired = ds3231.wait_for_alarm(&mut int).await.unwrap();
+            hprintln!("{:?}", fired).ok();
+
+            if fired.alarm1 {
+                Green.on();
+                break;
+            }
+        }
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,59 @@
+//! Checks that the DS3231 keeps the century across `set_datetime` and `set_time`; panics if a
+//! check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! set_datetime: OK
+//! set_time: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use chrono::{NaiveDate, NaiveTime};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{ds3231::Ds3231, twim::Twim};
+use panic_semihosting as _; // panic handler
+
+// month / century register
+const MONTH: usize = 5;
+const CENTURY: u8 = 1 << 7;
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+
+    task::block_on(async {
+        let datetime = NaiveDate::from_ymd(2150, 6, 15).and_hms(12, 34, 56);
+        ds3231.set_datetime(datetime).await.unwrap();
+
+        let regs = ds3231.dump_registers().await.unwrap();
+        assert_ne!(regs[MONTH] & CENTURY, 0);
+        // NOTE the clock keeps running; allow for a seconds rollover
+        let read = ds3231.get_datetime().await.unwrap();
+        assert!(read >= datetime && read - datetime <= chrono::Duration::seconds(1));
+        hprintln!("set_datetime: OK").ok();
+
+        ds3231.set_time(NaiveTime::from_hms(8, 0, 0)).await.unwrap();
+        let regs = ds3231.dump_registers().await.unwrap();
+        assert_ne!(regs[MONTH] & CENTURY, 0);
+        assert_eq!(
+            ds3231.get_date().await.unwrap(),
+            NaiveDate::from_ymd(2150, 6, 15)
+        );
+        hprintln!("set_time: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
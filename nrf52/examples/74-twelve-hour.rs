@@ -0,0 +1,72 @@
+//! Drives the DS3231 in the 12-hour format; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! 00:30: OK
+//! 12:30: OK
+//! 23:30: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use chrono::{NaiveDate, NaiveTime, Timelike as _};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    ds3231::{Ds3231, HourFormat},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+// hours register
+const HOURS: usize = 2;
+const HOUR12: u8 = 1 << 6;
+const PM: u8 = 1 << 5;
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+    ds3231.set_hour_format(HourFormat::Twelve);
+
+    task::block_on(async {
+        // (time, expected hours register): BCD hour plus the format / PM flags
+        let cases = [
+            // midnight is 12 AM
+            ((0, 30), HOUR12 | 0x12),
+            // noon is 12 PM
+            ((12, 30), HOUR12 | PM | 0x12),
+            ((23, 30), HOUR12 | PM | 0x11),
+        ];
+
+        for ((hour, min), reg) in cases.iter().cloned() {
+            let time = NaiveTime::from_hms(hour, min, 0);
+            ds3231.set_time(time).await.unwrap();
+            let regs = ds3231.dump_registers().await.unwrap();
+            assert_eq!(regs[HOURS], reg);
+
+            // NOTE the clock keeps running; ignore the seconds
+            let read = ds3231.get_time().await.unwrap();
+            assert_eq!((read.hour(), read.minute()), (hour, min));
+            hprintln!("{:02}:{:02}: OK", hour, min).ok();
+        }
+
+        // `set_datetime` uses the same format
+        let datetime = NaiveDate::from_ymd(2020, 1, 1).and_hms(0, 30, 0);
+        ds3231.set_datetime(datetime).await.unwrap();
+        let regs = ds3231.dump_registers().await.unwrap();
+        assert_eq!(regs[HOURS], HOUR12 | 0x12);
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,73 @@
+//! Checks the encoding of the SCD30 measurement interval and then reads out the sensor every 30
+//! seconds; panics if a check fails
+//!
+//! Expected output (the numbers will vary):
+//!
+//! ```
+//! interval validation: OK
+//! interval: 30 s
+//! CO2: 600 ppm, T: 24.1 °C, RH: 40%
+//! (..)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    scd30::{self, Error, Scd30},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    check_validation();
+    hprintln!("interval validation: OK").ok();
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut scd30 = Scd30::new(twim);
+
+    task::block_on(async {
+        scd30.set_measurement_interval(30).await.unwrap();
+        let secs = scd30.get_measurement_interval().await.unwrap();
+        assert_eq!(secs, 30);
+        hprintln!("interval: {} s", secs).ok();
+
+        loop {
+            // NOTE the driver waits up to 33 seconds for a measurement
+            match scd30.get_measurement().await {
+                Ok(m) => {
+                    hprintln!("{}", m).ok();
+                }
+                Err(e) => {
+                    hprintln!("error: {:?}", e).ok();
+                }
+            }
+        }
+    })
+}
+
+fn check_validation() {
+    // out of range: 2 - 1800 seconds
+    match scd30::measurement_interval_command(1) {
+        Err(Error::InvalidInterval(1)) => {}
+        _ => panic!("accepted an interval of 1 s"),
+    }
+    match scd30::measurement_interval_command(2000) {
+        Err(Error::InvalidInterval(2000)) => {}
+        _ => panic!("accepted an interval of 2000 s"),
+    }
+
+    // command 0x4600; argument 30 (0x001E) plus its CRC
+    match scd30::measurement_interval_command(30) {
+        Ok(bytes) => assert_eq!(bytes, [0x46, 0x00, 0x00, 0x1E, 0xDD]),
+        Err(e) => panic!("rejected an interval of 30 s: {:?}", e),
+    }
+}
@@ -0,0 +1,65 @@
+//! Checks the encoding of the SCD30 forced recalibration and reports whether the automatic
+//! self-calibration is enabled; panics if a check fails
+//!
+//! NOTE this doesn't recalibrate the sensor: the calibration is stored in non-volatile memory and
+//! needs a known reference environment
+//!
+//! Expected output:
+//!
+//! ```
+//! recalibration validation: OK
+//! ASC enabled: true
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    scd30::{self, Error, Scd30},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    check_validation();
+    hprintln!("recalibration validation: OK").ok();
+
+    let twim = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut scd30 = Scd30::new(twim);
+
+    task::block_on(async {
+        let asc = scd30.get_auto_self_calibration().await.unwrap();
+        hprintln!("ASC enabled: {}", asc).ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
+
+fn check_validation() {
+    // out of range: 400 - 2000 ppm
+    match scd30::forced_recalibration_command(399) {
+        Err(Error::InvalidReference(399)) => {}
+        _ => panic!("accepted a reference of 399 ppm"),
+    }
+    match scd30::forced_recalibration_command(2001) {
+        Err(Error::InvalidReference(2001)) => {}
+        _ => panic!("accepted a reference of 2001 ppm"),
+    }
+
+    // command 0x5204; argument 400 (0x0190) plus its CRC
+    match scd30::forced_recalibration_command(400) {
+        Ok(bytes) => assert_eq!(bytes, [0x52, 0x04, 0x01, 0x90, 0x4C]),
+        Err(e) => panic!("rejected a reference of 400 ppm: {:?}", e),
+    }
+}
@@ -0,0 +1,61 @@
+//! Soft-resets the SCD30 and checks that it answers at its address (0x61) afterwards; panics if a
+//! check fails
+//!
+//! Expected output (the numbers will vary):
+//!
+//! ```
+//! command: OK
+//! reset: OK
+//! CO2: 600 ppm, T: 24.1 °C, RH: 40%
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    scd30::{self, Scd30},
+    timer::Timer,
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+const SCD30: u8 = 0x61;
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    // command 0xD304 written to the address of the sensor
+    assert_eq!(scd30::soft_reset_command(), (SCD30, [0xD3, 0x04]));
+    hprintln!("command: OK").ok();
+
+    let twim: &'static Mutex<Twim> = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut scd30 = Scd30::new(twim);
+    let timer = Timer::take();
+
+    task::block_on(async {
+        assert!(twim.lock().await.probe(SCD30).await.unwrap());
+        scd30.start_continuous_measurement(0).await.unwrap();
+
+        scd30.soft_reset(&timer).await.unwrap();
+
+        // the sensor is back: it acknowledges its address and answers commands
+        assert!(twim.lock().await.probe(SCD30).await.unwrap());
+        scd30.self_test().await.unwrap();
+        hprintln!("reset: OK").ok();
+
+        // the continuous measurements resumed
+        let m = scd30.get_measurement().await.unwrap();
+        hprintln!("{}", m).ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
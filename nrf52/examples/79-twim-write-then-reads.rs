@@ -0,0 +1,80 @@
+//! Reads two register ranges of the DS3231 after a single command write; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! write_then_reads: OK
+//! TooLong: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use chrono::NaiveDate;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    ds3231::Ds3231,
+    twim::{Error, Twim},
+};
+use panic_semihosting as _; // panic handler
+
+// I2C address of the DS3231
+const ADDRESS: u8 = 0b110_1000;
+// seconds register; the first one
+const SECONDS: u8 = 0x00;
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim: &'static _ = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+
+    task::block_on(async {
+        // NOTE far from a minute rollover so only the seconds can change between the reads
+        let datetime = NaiveDate::from_ymd(2020, 2, 29).and_hms(23, 59, 30);
+        ds3231.set_datetime(datetime).await.unwrap();
+
+        let mut twim = twim.lock().await;
+
+        // seconds, minutes, hours | day, date, month, year
+        let mut time = [0; 3];
+        let mut date = [0; 4];
+        twim.write_then_reads(ADDRESS, &[SECONDS], &mut [&mut time[..], &mut date[..]])
+            .await
+            .unwrap();
+
+        // the same range read into a single buffer
+        let mut all = [0; 7];
+        twim.write_then_read(ADDRESS, &[SECONDS], &mut all)
+            .await
+            .unwrap();
+
+        // BCD encoded
+        assert_eq!(time[1..], [0x59, 0x23]);
+        assert_eq!(date[1..], [0x29, 0x02, 0x20]);
+        assert_eq!(time[1..], all[1..3]);
+        assert_eq!(date, all[3..]);
+        hprintln!("write_then_reads: OK").ok();
+
+        // a buffer that doesn't fit in a single DMA transfer is rejected up front
+        let mut large = [0; 256];
+        match twim
+            .write_then_reads(ADDRESS, &[SECONDS], &mut [&mut time[..], &mut large[..]])
+            .await
+        {
+            Err(Error::TooLong(256)) => {}
+            res => panic!("{:?}", res),
+        }
+        hprintln!("TooLong: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -60,7 +60,10 @@ fn main() -> ! {
         let mut rx_buf = [0];
 
         loop {
-            rx.read(&mut rx_buf).await;
+            if rx.read(&mut rx_buf).await.is_err() {
+                // input was lost; wait for the next key press
+                continue;
+            }
 
             // carriage return;
             if rx_buf[0] == 13 {
@@ -0,0 +1,70 @@
+//! A task that gives the `reschedule` hint on every `poll` doesn't starve the tasks spawned after
+//! it; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! main task woken 100 times; the last task was polled 50 times
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::cell::Cell;
+
+use async_embedded::{task, unsync::Notify};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52 as _; // memory layout
+use panic_semihosting as _; // panic handler
+
+const WAKE_UPS: u32 = 100;
+
+#[entry]
+fn main() -> ! {
+    static mut N: Notify = Notify::new();
+    static mut C: Cell<u32> = Cell::new(0);
+
+    let notify: &'static _ = N;
+    let polls: &'static _ = C;
+
+    // wakes up the main task and asks the executor to start over on every `poll`
+    task::spawn(async move {
+        loop {
+            notify.notify();
+            task::reschedule();
+            task::r#yield().await;
+        }
+    });
+
+    task::spawn(async move {
+        loop {
+            polls.set(polls.get() + 1);
+            task::r#yield().await;
+        }
+    });
+
+    task::block_on(async {
+        for _ in 0..WAKE_UPS {
+            notify.notified().await;
+        }
+
+        // NOTE every task is polled at least once every two scans and the main task is polled at
+        // most twice per scan
+        let polls = polls.get();
+        hprintln!(
+            "main task woken {} times; the last task was polled {} times",
+            WAKE_UPS,
+            polls
+        )
+        .ok();
+        assert!(polls >= WAKE_UPS / 2 - 1);
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
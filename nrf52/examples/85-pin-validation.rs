@@ -0,0 +1,44 @@
+//! Checks that the NFC pins are only accepted when NFC has been disabled in the UICR; panics if a
+//! check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! NFC pins: OK
+//! ```
+
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    gpio::{Error, Pin},
+    pin,
+};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    // the NFC pins are valid GPIOs but can only be used if NFC has been disabled in the UICR
+    // NOTE(unsafe) single-instruction read of a read-only (at runtime) register
+    let nfc = unsafe { (*pac::UICR::ptr()).nfcpins.read().bits() & 1 != 0 };
+    for pin in [9, 10].iter().cloned() {
+        let pin = Pin::new(0, pin).unwrap();
+        if nfc {
+            assert_eq!(pin.check(), Err(Error::Nfc));
+        } else {
+            assert_eq!(pin.check(), Ok(pin));
+        }
+    }
+    // pins next to the NFC pins are never affected
+    assert!(pin!(0, 8).check().is_ok());
+    assert!(pin!(0, 11).check().is_ok());
+    hprintln!("NFC pins: OK").ok();
+
+    loop {
+        asm::bkpt();
+    }
+}
@@ -0,0 +1,67 @@
+//! Checks that dropping a pending `wait` leaves the timer idle and doesn't affect the next
+//! `wait`; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! cancelled waits: OK
+//! ```
+
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task::{self, Either};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::timer::{ext::DurationExt as _, Timer};
+use panic_semihosting as _; // panic handler
+
+#[entry]
+fn main() -> ! {
+    let timer = Timer::take();
+
+    task::block_on(async {
+        // the long wait loses the race and is dropped
+        let start = Timer::now();
+        match task::select(timer.wait(10.millis()), timer.wait(1.secs())).await {
+            Either::Left(()) => {}
+            Either::Right(()) => panic!("the long wait completed first"),
+        }
+        assert!(start.elapsed() < 20.millis());
+        assert!(idle());
+        check_wait(&timer).await;
+
+        // the dropped wait is the one the compare register was programmed for
+        match task::select(timer.wait(1.secs()), task::r#yield()).await {
+            Either::Left(()) => panic!("the wait completed before the yield"),
+            Either::Right(()) => {}
+        }
+        assert!(idle());
+        check_wait(&timer).await;
+
+        hprintln!("cancelled waits: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
+
+// no deadline is left behind: the compare interrupt is disabled
+fn idle() -> bool {
+    // NOTE(unsafe) single-instruction read of a register
+    let rtc = unsafe { &*pac::RTC0::ptr() };
+    rtc.intenset.read().compare0().bit_is_clear()
+}
+
+// the next `wait` is neither cut short by a stale compare event nor delayed
+async fn check_wait(timer: &Timer) {
+    let before = Timer::now();
+    timer.wait(20.millis()).await;
+    let elapsed = before.elapsed();
+    // NOTE the wait may be up to one tick shorter than requested due to rounding
+    assert!(elapsed >= 19.millis() && elapsed < 25.millis());
+    assert!(idle());
+}
@@ -0,0 +1,46 @@
+//! Issues 1000 back-to-back one-millisecond waits and checks that the total elapsed time is within
+//! one tick of one second; panics if the check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! 1000 x 1 ms: 32768 ticks
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::timer::{ext::DurationExt as _, Timer};
+use panic_semihosting as _; // panic handler
+
+const WAITS: u64 = 1_000;
+// one second
+const IDEAL: u64 = 32_768;
+
+#[entry]
+fn main() -> ! {
+    let timer = Timer::take();
+
+    task::block_on(async {
+        let start = Timer::now();
+        for _ in 0..WAITS {
+            timer.wait(1.millis()).await;
+        }
+        let elapsed = Timer::now().ticks() - start.ticks();
+
+        hprintln!("{} x 1 ms: {} ticks", WAITS, elapsed).ok();
+        // NOTE without carrying the rounding error each wait would be 32 ticks long and the total
+        // would be 2.3% short of the ideal
+        assert!(elapsed + 1 >= IDEAL && elapsed <= IDEAL + 1);
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -10,6 +10,7 @@
 //! sensors           displays the gas sensor data
 //! set date %Y-%m-%d changes the date
 //! set time %H:%M:%S changes the time
+//! sync %s           sets the date and time from a Unix timestamp
 //! > sensors
 //! CO2: 652ppm
 //! T: 26C
@@ -17,6 +18,11 @@
 //! > set time 18:49:30
 //! > date
 //! 2020-02-28 18:49:32
+//! > sync 1583002800
+//! > date
+//! 2020-02-29 19:00:01
+//! > sync 0
+//! timestamp out of range (2000 - 2199)
 //! ```
 
 #![deny(unsafe_code)]
@@ -32,7 +38,7 @@ use core::{
 };
 
 use async_embedded::{task, unsync::Mutex};
-use chrono::{Datelike as _, NaiveDate, NaiveTime};
+use chrono::{Datelike as _, NaiveDate, NaiveDateTime, NaiveTime};
 use cortex_m_rt::entry;
 use heapless::{consts, String, Vec};
 use nrf52::{
@@ -89,9 +95,10 @@ fn main() -> ! {
     let mut scd30 = Scd30::new(twim);
     task::spawn(async move {
         loop {
-            // TODO instead of continuously polling the sensor we should only read it out when new
-            // data is ready (there's a pin that signals that), roughly every 2 seconds.
-            // Alternatively, we could send this task to sleep for 2 seconds after new data is read
+            // NOTE if the RDY pin of the sensor is connected, `wait_for_measurement` avoids
+            // polling the sensor altogether
+            // NOTE `get_measurement` yields between polls so the RTC remains responsive while this
+            // task waits for new data
             let res = scd30.get_measurement().await;
 
             if let Ok(m) = res {
@@ -127,7 +134,10 @@ fn main() -> ! {
                 // Ideally, we want to use a large `rx_buf` and instead read its contents only when
                 // there has been no new data on the bus for a while
                 let mut rx_buf = [0];
-                rx.read(&mut rx_buf).await;
+                if rx.read(&mut rx_buf).await.is_err() {
+                    tx.write(b"input was lost; try again\n").await;
+                    continue 'prompt;
+                }
 
                 if input.push(rx_buf[0]).is_err() {
                     tx.write(b"input buffer is full\n").await;
@@ -152,7 +162,7 @@ fn main() -> ! {
                                             tx.write(b"error communicating with the RTC\n").await;
                                         }
 
-                                        Err(ds3231::Error::InvalidDate) => {
+                                        Err(ds3231::Error::InvalidDate(..)) => {
                                             tx.write(b"invalid date stored in the RTC\n").await;
                                         }
                                     }
@@ -172,6 +182,18 @@ fn main() -> ! {
                                     }
                                 }
 
+                                Command::Sync(res) => match res {
+                                    Ok(datetime) => {
+                                        if ds3231.set_datetime(datetime).await.is_err() {
+                                            tx.write(b"error communicating with the RTC\n").await;
+                                        }
+                                    }
+
+                                    Err(_) => {
+                                        tx.write(b"timestamp out of range (2000 - 2199)\n").await;
+                                    }
+                                },
+
                                 Command::Sensors => {
                                     tx_buf.clear();
 
@@ -194,6 +216,7 @@ date              display the current date and time
 sensors           displays the gas sensor data
 set date %Y-%m-%d changes the date
 set time %H:%M:%S changes the time
+sync %s           sets the date and time from a Unix timestamp
 ",
                                     )
                                     .await;
@@ -218,6 +241,7 @@ enum Command {
     Sensors,
     SetDate(NaiveDate),
     SetTime(NaiveTime),
+    Sync(Result<NaiveDateTime, ds3231::Error>),
 }
 
 impl FromStr for Command {
@@ -253,6 +277,9 @@ impl FromStr for Command {
                 .map_err(|_| Error)?;
 
             Command::SetTime(time)
+        } else if let Some(res) = ds3231::parse_sync(s) {
+            // NOTE an out of range timestamp is reported when the command is executed
+            Command::Sync(res)
         } else {
             return Err(Error);
         })
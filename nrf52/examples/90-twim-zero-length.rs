@@ -0,0 +1,58 @@
+//! Issues zero-length transfers to an address that ACKs (the DS3231) and to one that NACKs (no
+//! device); panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! zero-length writes: OK
+//! zero-length reads: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::twim::Twim;
+use panic_semihosting as _; // panic handler
+
+// I2C address of the DS3231
+const ACK: u8 = 0b110_1000;
+// no device answers at this address
+const NACK: u8 = 0b001_0010;
+
+#[entry]
+fn main() -> ! {
+    let mut twim = Twim::take();
+
+    task::block_on(async {
+        // START - ADDR - STOP
+        twim.write(ACK, &[]).await.unwrap();
+        match twim.write(NACK, &[]).await {
+            Err(e) if e.is_address_nack() => {}
+            res => panic!("{:?}", res),
+        }
+        assert_eq!(twim.probe(ACK).await.ok(), Some(true));
+        assert_eq!(twim.probe(NACK).await.ok(), Some(false));
+        hprintln!("zero-length writes: OK").ok();
+
+        // no bus traffic, whether there's a device or not
+        twim.read(ACK, &mut []).await.unwrap();
+        twim.read(NACK, &mut []).await.unwrap();
+        hprintln!("zero-length reads: OK").ok();
+
+        // the bus is left ready for the next transfer
+        let mut seconds = [0];
+        twim.write_then_read(ACK, &[0x00], &mut seconds)
+            .await
+            .unwrap();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,86 @@
+//! Polls an I2C read once, which starts the DMA transfer, and then drops it; the transfer is
+//! stopped and the peripheral is left idle. Panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! dropped an in-progress read: OK
+//! ```
+
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::twim::Twim;
+use panic_semihosting as _; // panic handler
+
+// I2C address of the DS3231
+const ADDRESS: u8 = 0b110_1000;
+// all the registers of the DS3231; ~2 ms @ 100 KHz
+const LEN: usize = 19;
+
+#[entry]
+fn main() -> ! {
+    let mut twim = Twim::take();
+
+    task::block_on(async {
+        let mut buf = [0xAA; LEN];
+        {
+            let mut read = twim.read(ADDRESS, &mut buf);
+            // NOTE(unsafe) `read` is not moved after this point
+            PollOnce(unsafe { Pin::new_unchecked(&mut read) }).await;
+            // the transfer is still in progress
+            assert!(!idle());
+            // the destructor stops the transfer
+        }
+        assert!(idle());
+
+        // the DMA no longer writes into `buf`
+        let snapshot = buf;
+        asm::delay(256_000); // ~4 ms @ 64 MHz
+        assert_eq!(buf, snapshot);
+
+        // the next transfer starts from a clean state
+        twim.read(ADDRESS, &mut buf).await.unwrap();
+        assert!(idle());
+        hprintln!("dropped an in-progress read: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
+
+// no transfer has been started or, if one was, its events have been handled
+fn idle() -> bool {
+    // NOTE(unsafe) reads of status registers
+    let twim = unsafe { &*pac::TWIM0::ptr() };
+    twim.events_rxstarted.read().bits() == 0
+        && twim.events_stopped.read().bits() == 0
+        && twim.errorsrc.read().bits() == 0
+}
+
+// Polls a future exactly once
+struct PollOnce<'a, F>(Pin<&'a mut F>);
+
+impl<F> Future for PollOnce<'_, F>
+where
+    F: Future,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let _ = self.0.as_mut().poll(cx);
+        Poll::Ready(())
+    }
+}
@@ -0,0 +1,70 @@
+//! Checks the byte layout of the `read_reg*` / `write_reg*` helpers against the DS3231 alarm
+//! registers, which store whatever is written to them; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! write_reg / read_reg: OK
+//! 8-bit registers: OK
+//! 16-bit registers: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::twim::Twim;
+use panic_semihosting as _; // panic handler
+
+// I2C address of the DS3231
+const ADDRESS: u8 = 0b110_1000;
+// alarm 1: seconds, minutes, hours and day / date
+const ALARM1: u8 = 0x07;
+// alarm 2: minutes, hours and day / date
+const ALARM2: u8 = 0x0b;
+
+#[entry]
+fn main() -> ! {
+    let mut twim = Twim::take();
+
+    task::block_on(async {
+        // the register address goes first; the data is written to consecutive registers
+        twim.write_reg(ADDRESS, ALARM1, &[0x56, 0x34, 0x12, 0x15])
+            .await
+            .unwrap();
+        let mut regs = [0; 4];
+        twim.read_reg(ADDRESS, ALARM1, &mut regs).await.unwrap();
+        assert_eq!(regs, [0x56, 0x34, 0x12, 0x15]);
+
+        // a shorter read starting at a later register
+        let mut regs = [0; 2];
+        twim.read_reg(ADDRESS, ALARM1 + 2, &mut regs).await.unwrap();
+        assert_eq!(regs, [0x12, 0x15]);
+        hprintln!("write_reg / read_reg: OK").ok();
+
+        // only the addressed register changes
+        twim.write_reg_u8(ADDRESS, ALARM1 + 1, 0x45).await.unwrap();
+        assert_eq!(twim.read_reg_u8(ADDRESS, ALARM1 + 1).await.unwrap(), 0x45);
+        let mut regs = [0; 4];
+        twim.read_reg(ADDRESS, ALARM1, &mut regs).await.unwrap();
+        assert_eq!(regs, [0x56, 0x45, 0x12, 0x15]);
+        hprintln!("8-bit registers: OK").ok();
+
+        // most significant byte at the lower address
+        twim.write_reg_u16(ADDRESS, ALARM2, 0x2307).await.unwrap();
+        let mut regs = [0; 2];
+        twim.read_reg(ADDRESS, ALARM2, &mut regs).await.unwrap();
+        assert_eq!(regs, [0x23, 0x07]);
+        assert_eq!(twim.read_reg_u16(ADDRESS, ALARM2).await.unwrap(), 0x2307);
+        hprintln!("16-bit registers: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
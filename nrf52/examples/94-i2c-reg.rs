@@ -0,0 +1,95 @@
+//! Checks the accessors generated by `i2c_reg!`: first the encodings on their own, then reads and
+//! writes of the DS3231 alarm registers; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! encodings: OK
+//! typed accessors: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    i2c_reg,
+    twim::{Register, Twim},
+};
+use panic_semihosting as _; // panic handler
+
+// I2C address of the DS3231
+const ADDRESS: u8 = 0b110_1000;
+
+i2c_reg! {
+    // alarm 1: seconds, minutes, hours and day / date
+    struct Alarm1: 0x07 => [u8; 4];
+    // alarm 1: minutes
+    struct Alarm1Minutes: 0x08 => bcd;
+    // alarm 1: minutes and hours
+    struct Alarm1Word: 0x08 => u16_be;
+    struct Alarm1WordLe: 0x08 => u16_le;
+    // alarm 2: day / date
+    struct Alarm2Day: 0x0d => u8;
+}
+
+#[entry]
+fn main() -> ! {
+    assert_eq!(Alarm1::ADDRESS, 0x07);
+    assert_eq!(Alarm1Minutes::encode(42), [0x42]);
+    assert_eq!(Alarm1Minutes::decode([0x59]), 59);
+    assert_eq!(Alarm1Word::encode(0x1234), [0x12, 0x34]);
+    assert_eq!(Alarm1Word::decode([0x12, 0x34]), 0x1234);
+    assert_eq!(Alarm1WordLe::encode(0x1234), [0x34, 0x12]);
+    assert_eq!(Alarm1WordLe::decode([0x12, 0x34]), 0x3412);
+    assert_eq!(Alarm2Day::encode(0x15), [0x15]);
+    hprintln!("encodings: OK").ok();
+
+    let mut twim = Twim::take();
+
+    task::block_on(async {
+        twim.write_register::<Alarm1>(ADDRESS, [0x56, 0x34, 0x12, 0x15])
+            .await
+            .unwrap();
+        assert_eq!(
+            twim.read_register::<Alarm1>(ADDRESS).await.unwrap(),
+            [0x56, 0x34, 0x12, 0x15]
+        );
+
+        // the registers overlap so writes through one accessor are seen by the others
+        twim.write_register::<Alarm1Minutes>(ADDRESS, 47)
+            .await
+            .unwrap();
+        assert_eq!(
+            twim.read_register::<Alarm1Minutes>(ADDRESS).await.unwrap(),
+            47
+        );
+        assert_eq!(
+            twim.read_register::<Alarm1Word>(ADDRESS).await.unwrap(),
+            0x4712
+        );
+
+        twim.write_register::<Alarm1WordLe>(ADDRESS, 0x0859)
+            .await
+            .unwrap();
+        assert_eq!(
+            twim.read_register::<Alarm1>(ADDRESS).await.unwrap(),
+            [0x56, 0x59, 0x08, 0x15]
+        );
+
+        twim.write_register::<Alarm2Day>(ADDRESS, 0x31)
+            .await
+            .unwrap();
+        assert_eq!(twim.read_reg_u8(ADDRESS, 0x0d).await.unwrap(), 0x31);
+        hprintln!("typed accessors: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
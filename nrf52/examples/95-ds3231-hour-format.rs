@@ -0,0 +1,78 @@
+//! Round-trips the boundary hours (midnight, noon) through `set_time` and `get_time` in both hour
+//! formats and checks how they are stored in the hours register; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! TwentyFour: OK
+//! Twelve: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::{task, unsync::Mutex};
+use chrono::{Duration, NaiveTime};
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::{
+    ds3231::{Ds3231, HourFormat},
+    twim::Twim,
+};
+use panic_semihosting as _; // panic handler
+
+// I2C address of the DS3231
+const ADDRESS: u8 = 0b110_1000;
+// hours register
+const HOURS: u8 = 0x02;
+
+// (hour, hours register in the 24-hour format, hours register in the 12-hour format)
+const HOURS_REGS: [(u32, u8, u8); 6] = [
+    // midnight is 12 AM
+    (0, 0x00, 0x52),
+    (1, 0x01, 0x41),
+    (11, 0x11, 0x51),
+    // noon is 12 PM
+    (12, 0x12, 0x72),
+    (13, 0x13, 0x61),
+    (23, 0x23, 0x71),
+];
+
+#[entry]
+fn main() -> ! {
+    static mut M: Option<Mutex<Twim>> = None;
+
+    let twim: &'static _ = M.get_or_insert(Mutex::new(Twim::take()));
+    let mut ds3231 = Ds3231::new(twim);
+
+    task::block_on(async {
+        for &format in [HourFormat::TwentyFour, HourFormat::Twelve].iter() {
+            ds3231.set_hour_format(format);
+
+            for &(hour, reg24, reg12) in HOURS_REGS.iter() {
+                // NOTE far from a minute rollover so only the seconds can change
+                let time = NaiveTime::from_hms(hour, 0, 30);
+                ds3231.set_time(time).await.unwrap();
+
+                let reg = twim.lock().await.read_reg_u8(ADDRESS, HOURS).await.unwrap();
+                let expected = match format {
+                    HourFormat::TwentyFour => reg24,
+                    HourFormat::Twelve => reg12,
+                };
+                assert_eq!(reg, expected);
+
+                let got = ds3231.get_time().await.unwrap();
+                assert!(got == time || got == time + Duration::seconds(1));
+            }
+
+            hprintln!("{:?}: OK", format).ok();
+        }
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
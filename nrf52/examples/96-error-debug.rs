@@ -0,0 +1,47 @@
+//! Checks that the `Debug` output of an I2C error reported by the hardware includes the decoded
+//! details; panics if the check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! bus error: Src(address NACK)
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use core::fmt::{Debug, Write as _};
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use heapless::{consts, String};
+use nrf52::twim::Twim;
+use panic_semihosting as _; // panic handler
+
+// no device answers at this address
+const NACK: u8 = 0b001_0010;
+
+#[entry]
+fn main() -> ! {
+    let mut twim = Twim::take();
+    task::block_on(async {
+        // an actual error reported by the hardware
+        let e = twim.read(NACK, &mut [0]).await.unwrap_err();
+        hprintln!("bus error: {:?}", e).ok();
+        check(e, "Src(address NACK)");
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
+
+fn check(e: impl Debug, expected: &str) {
+    let mut s = String::<consts::U64>::new();
+    write!(s, "{:?}", e).unwrap();
+    assert_eq!(&s[..], expected);
+}
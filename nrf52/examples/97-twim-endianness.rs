@@ -0,0 +1,66 @@
+//! Reads a known byte sequence, stored in the DS3231 alarm registers, with each of the
+//! multi-byte register readers; panics if a check fails
+//!
+//! Expected output:
+//!
+//! ```
+//! big endian: OK
+//! little endian: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::twim::Twim;
+use panic_semihosting as _; // panic handler
+
+// I2C address of the DS3231
+const ADDRESS: u8 = 0b110_1000;
+// alarm 1: seconds, minutes, hours and day / date
+const ALARM1: u8 = 0x07;
+
+#[entry]
+fn main() -> ! {
+    let mut twim = Twim::take();
+
+    task::block_on(async {
+        twim.write_reg(ADDRESS, ALARM1, &[0x56, 0x34, 0x12, 0x15])
+            .await
+            .unwrap();
+
+        // most significant byte first
+        assert_eq!(twim.read_reg_u8(ADDRESS, ALARM1).await.unwrap(), 0x56);
+        assert_eq!(twim.read_reg_u16(ADDRESS, ALARM1).await.unwrap(), 0x5634);
+        assert_eq!(
+            twim.read_reg_u16(ADDRESS, ALARM1 + 2).await.unwrap(),
+            0x1215
+        );
+        assert_eq!(
+            twim.read_reg_u32(ADDRESS, ALARM1).await.unwrap(),
+            0x5634_1215
+        );
+        hprintln!("big endian: OK").ok();
+
+        // least significant byte first
+        assert_eq!(twim.read_reg_u16_le(ADDRESS, ALARM1).await.unwrap(), 0x3456);
+        assert_eq!(
+            twim.read_reg_u16_le(ADDRESS, ALARM1 + 2).await.unwrap(),
+            0x1512
+        );
+        assert_eq!(
+            twim.read_reg_u32_le(ADDRESS, ALARM1).await.unwrap(),
+            0x1512_3456
+        );
+        hprintln!("little endian: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
@@ -0,0 +1,122 @@
+//! Moves more than 255 bytes in a single call, which takes several DMA transfers, to and from the
+//! DS3231; panics if a check fails
+//!
+//! The register pointer of the DS3231 wraps around after the last register so a long read returns
+//! the register map over and over; any byte lost or duplicated at a chunk boundary shifts the
+//! pattern
+//!
+//! None of the devices on the bus fail halfway through a transfer so an error in a later chunk is
+//! covered by the unit tests of the `twim` module
+//!
+//! Expected output:
+//!
+//! ```
+//! large reads: OK
+//! large write: OK
+//! errors: OK
+//! ```
+
+#![deny(unsafe_code)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+
+use async_embedded::task;
+use cortex_m::asm;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+use nrf52::twim::{Error, Twim};
+use panic_semihosting as _; // panic handler
+
+// I2C address of the DS3231
+const ADDRESS: u8 = 0b110_1000;
+// no device answers at this address
+const NACK: u8 = 0b001_0010;
+// number of registers
+const NREGS: usize = 19;
+// alarm registers and the control register; they don't change on their own
+const STATIC: [usize; 8] = [7, 8, 9, 10, 11, 12, 13, 14];
+
+#[entry]
+fn main() -> ! {
+    static mut BUF: [u8; 608] = [0; 608];
+
+    let buf: &'static mut _ = BUF;
+    let mut twim = Twim::take();
+
+    task::block_on(async {
+        twim.write_reg(ADDRESS, 7, &[0x56, 0x34, 0x12, 0x15, 0x45, 0x12, 0x15])
+            .await
+            .unwrap();
+        let mut regs = [0; NREGS];
+        twim.read_reg(ADDRESS, 0, &mut regs).await.unwrap();
+
+        // 3 chunks; 2 x 255 + 98
+        twim.read_reg(ADDRESS, 0, &mut buf[..]).await.unwrap();
+        check(&buf[..], &regs);
+        // an exact multiple of the chunk size
+        twim.read_reg(ADDRESS, 0, &mut buf[..510]).await.unwrap();
+        check(&buf[..510], &regs);
+        // nothing to read; only the register address is sent
+        twim.read_reg(ADDRESS, 0, &mut []).await.unwrap();
+        hprintln!("large reads: OK").ok();
+
+        // the register map 14 times; 2 chunks: 255 + 11. The alarm registers of the last copy
+        // straddle the boundary between the chunks and hold different values
+        let n = 14 * NREGS;
+        for (i, byte) in buf[..n].iter_mut().enumerate() {
+            *byte = regs[i % NREGS];
+        }
+        let last = (13 * NREGS + 7)..(13 * NREGS + 14);
+        buf[last.clone()].copy_from_slice(&[0x30, 0x45, 0x23, 0x28, 0x59, 0x23, 0x28]);
+        twim.write_reg(ADDRESS, 0, &buf[..n]).await.unwrap();
+
+        let mut alarms = [0; 7];
+        twim.read_reg(ADDRESS, 7, &mut alarms).await.unwrap();
+        assert_eq!(alarms, buf[last]);
+
+        // an exact multiple of the chunk size; 2 x 255. The alarm registers of the last copy sit
+        // at the end of the second chunk
+        let n = 2 * 255;
+        for (i, byte) in buf[..n].iter_mut().enumerate() {
+            *byte = regs[i % NREGS];
+        }
+        let last = (26 * NREGS + 7)..(26 * NREGS + 14);
+        buf[last.clone()].copy_from_slice(&[0x12, 0x34, 0x01, 0x01, 0x07, 0x06, 0x02]);
+        twim.write_reg(ADDRESS, 0, &buf[..n]).await.unwrap();
+
+        twim.read_reg(ADDRESS, 7, &mut alarms).await.unwrap();
+        assert_eq!(alarms, buf[last]);
+        hprintln!("large write: OK").ok();
+
+        // the error of the first chunk is reported; the other chunks are not attempted
+        let e = twim.read(NACK, &mut buf[..]).await.unwrap_err();
+        assert!(e.is_address_nack());
+        let e = twim.write(NACK, &buf[..300]).await.unwrap_err();
+        assert!(e.is_address_nack());
+        // a write that doesn't fit in one transfer can't be followed by a repeated START; nothing
+        // is sent
+        match twim
+            .write_then_read(ADDRESS, &buf[..300], &mut alarms)
+            .await
+        {
+            Err(Error::TooLong(300)) => {}
+            _ => panic!("expected `TooLong(300)`"),
+        }
+        hprintln!("errors: OK").ok();
+
+        loop {
+            asm::bkpt();
+        }
+    })
+}
+
+// `buf` holds copies of the register map `regs`
+fn check(buf: &[u8], regs: &[u8; NREGS]) {
+    for (i, byte) in buf.iter().enumerate() {
+        let reg = i % NREGS;
+        if STATIC.contains(&reg) {
+            assert_eq!(*byte, regs[reg], "byte {}", i);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Clock control
+//!
+//! At boot (`pre_init`) the LFCLK is started from the 32.768 KHz crystal and the HFCLK runs from
+//! the internal RC oscillator (HFINT). The high frequency crystal oscillator (HFXO) is more
+//! accurate (e.g. for high baud rates or radio operation) but draws more current so it should only
+//! be running while it's needed
+
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{self, AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use cortex_m::peripheral::NVIC;
+use pac::{Interrupt, CLOCK};
+
+use crate::{BorrowUnchecked as _, NotSync};
+
+const INTERRUPT: Interrupt = Interrupt::POWER_CLOCK;
+
+/// [singleton] An `async`-aware clock controller
+pub struct Clocks {
+    _not_sync: NotSync,
+}
+
+impl Clocks {
+    /// Takes the singleton instance of the clock controller
+    ///
+    /// This returns the `Some` variant only once
+    pub fn take() -> Self {
+        static TAKEN: AtomicBool = AtomicBool::new(false);
+
+        if TAKEN
+            .compare_exchange_weak(false, true, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            Self {
+                _not_sync: NotSync::new(),
+            }
+        } else {
+            panic!("`Clocks` has already been taken")
+        }
+    }
+
+    /// Starts the high frequency crystal oscillator (HFXO) and waits until it's stable
+    ///
+    /// The HFCLK switches from HFINT to HFXO once this completes
+    pub async fn start_hfclk(&mut self) {
+        if is_hfxo_running() {
+            return;
+        }
+
+        Start {
+            _clocks: self,
+            clock: Clock::High,
+            state: State::NotStarted,
+        }
+        .await
+    }
+
+    /// Stops the high frequency crystal oscillator
+    ///
+    /// The HFCLK switches back to HFINT
+    pub fn stop_hfclk(&mut self) {
+        CLOCK::borrow_unchecked(|clock| clock.tasks_hfclkstop.write(|w| unsafe { w.bits(1) }));
+    }
+
+    /// Starts the low frequency clock (LFCLK) and waits until it's running
+    ///
+    /// The LFCLK drives the `Timer`; it's started in `pre_init` so this is only needed after a
+    /// call to `stop_lfclk`
+    pub async fn start_lfclk(&mut self) {
+        if is_lfclk_running() {
+            return;
+        }
+
+        Start {
+            _clocks: self,
+            clock: Clock::Low,
+            state: State::NotStarted,
+        }
+        .await
+    }
+
+    /// Stops the low frequency clock
+    ///
+    /// NOTE the `Timer` won't make progress while the LFCLK is stopped
+    pub fn stop_lfclk(&mut self) {
+        CLOCK::borrow_unchecked(|clock| clock.tasks_lfclkstop.write(|w| unsafe { w.bits(1) }));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Clock {
+    High,
+    Low,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    NotStarted,
+    InProgress,
+    Finished,
+}
+
+struct Start<'c> {
+    _clocks: &'c mut Clocks,
+    clock: Clock,
+    state: State,
+}
+
+impl Future for Start<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let clock = self.clock;
+
+        match self.state {
+            State::NotStarted => {
+                CLOCK::borrow_unchecked(|clk| {
+                    // reset events
+                    match clock {
+                        Clock::High => clk.events_hfclkstarted.reset(),
+                        Clock::Low => clk.events_lfclkstarted.reset(),
+                    }
+
+                    // install the waker
+                    NVIC::mask(INTERRUPT);
+                    unsafe {
+                        *waker(clock) = Some(cx.waker().clone());
+                        // NOTE(compiler_fence) writing the waker must complete before the
+                        // interrupt is unmasked
+                        atomic::compiler_fence(Ordering::Release);
+                        NVIC::unmask(INTERRUPT);
+                    }
+
+                    match clock {
+                        Clock::High => {
+                            clk.intenset.write(|w| w.hfclkstarted().set_bit());
+                            clk.tasks_hfclkstart.write(|w| unsafe { w.bits(1) });
+                        }
+                        Clock::Low => {
+                            clk.intenset.write(|w| w.lfclkstarted().set_bit());
+                            clk.tasks_lfclkstart.write(|w| unsafe { w.bits(1) });
+                        }
+                    }
+                });
+
+                self.state = State::InProgress;
+
+                Poll::Pending
+            }
+
+            State::InProgress => CLOCK::borrow_unchecked(|clk| {
+                let started = match clock {
+                    Clock::High => clk.events_hfclkstarted.read().bits() != 0,
+                    Clock::Low => clk.events_lfclkstarted.read().bits() != 0,
+                };
+
+                if started {
+                    match clock {
+                        Clock::High => clk.events_hfclkstarted.reset(),
+                        Clock::Low => clk.events_lfclkstarted.reset(),
+                    }
+
+                    self.state = State::Finished;
+                    uninstall_waker(clock);
+
+                    Poll::Ready(())
+                } else {
+                    // spurious wake up; re-arm the one-shot interrupt
+                    unsafe {
+                        NVIC::unmask(INTERRUPT);
+                    }
+
+                    Poll::Pending
+                }
+            }),
+
+            State::Finished => unreachable!(),
+        }
+    }
+}
+
+// NOTE the clock keeps starting if the future is dropped; we only need to stop listening
+impl Drop for Start<'_> {
+    fn drop(&mut self) {
+        if self.state == State::InProgress {
+            uninstall_waker(self.clock);
+        }
+    }
+}
+
+static mut HF_WAKER: Option<Waker> = None;
+static mut LF_WAKER: Option<Waker> = None;
+
+unsafe fn waker(clock: Clock) -> &'static mut Option<Waker> {
+    match clock {
+        Clock::High => &mut HF_WAKER,
+        Clock::Low => &mut LF_WAKER,
+    }
+}
+
+fn uninstall_waker(clock: Clock) {
+    CLOCK::borrow_unchecked(|clk| match clock {
+        Clock::High => clk.intenclr.write(|w| w.hfclkstarted().set_bit()),
+        Clock::Low => clk.intenclr.write(|w| w.lfclkstarted().set_bit()),
+    });
+
+    NVIC::mask(INTERRUPT);
+    // NOTE(compiler_fence) the interrupt must be disabled before we take down the waker
+    atomic::compiler_fence(Ordering::SeqCst);
+    unsafe {
+        drop(waker(clock).take());
+
+        // the other waker may still need to be serviced
+        if HF_WAKER.is_some() || LF_WAKER.is_some() {
+            NVIC::unmask(INTERRUPT);
+        }
+    }
+}
+
+fn is_hfxo_running() -> bool {
+    // HFCLKSTAT: bit 0 = SRC (1 = Xtal); bit 16 = STATE (1 = running)
+    CLOCK::borrow_unchecked(|clock| clock.hfclkstat.read().bits() & (1 << 16 | 1) == (1 << 16 | 1))
+}
+
+fn is_lfclk_running() -> bool {
+    // LFCLKSTAT: bit 16 = STATE (1 = running)
+    CLOCK::borrow_unchecked(|clock| clock.lfclkstat.read().bits() & (1 << 16) != 0)
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+fn POWER_CLOCK() {
+    let mut ran_a_waker = false;
+    // NOTE(unsafe) the only other context that can access these static variables runs at lower
+    // priority
+    unsafe {
+        if let Some(waker) = HF_WAKER.as_ref() {
+            waker.wake_by_ref();
+            ran_a_waker = true;
+        }
+
+        if let Some(waker) = LF_WAKER.as_ref() {
+            waker.wake_by_ref();
+            ran_a_waker = true;
+        }
+    }
+
+    if ran_a_waker {
+        // avoid continuously re-entering this interrupt handler
+        NVIC::mask(INTERRUPT);
+    }
+}
@@ -0,0 +1,210 @@
+//! Drift of the DS3231 clock relative to the `Timer` clock
+//!
+//! Both clocks are driven by 32.768 KHz crystals so the measured drift is the difference between
+//! the errors of the two crystals. The DS3231 reports time with a resolution of one second; to
+//! work around that each sample is taken right when its seconds roll over, which leaves an error
+//! of about one millisecond per sample. Measure over a window of several minutes to get a
+//! resolution of a few ppm
+
+use core::time::Duration;
+
+use async_embedded::task;
+use chrono::NaiveDateTime;
+
+use crate::{
+    ds3231::{self, Ds3231},
+    timer::{self, Instant, Timer},
+};
+
+// the seconds should roll over within one second; give up after this long
+const EDGE_TIMEOUT: Duration = Duration::from_millis(1_500);
+
+/// Driver error
+#[derive(Debug)]
+pub enum Error {
+    /// The seconds of the DS3231 didn't roll over; its oscillator may be stopped
+    Stopped,
+
+    /// DS3231 error
+    Rtc(ds3231::Error),
+}
+
+impl From<ds3231::Error> for Error {
+    fn from(e: ds3231::Error) -> Self {
+        Error::Rtc(e)
+    }
+}
+
+/// The time of both clocks at the same instant
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    /// DS3231 time
+    pub rtc: NaiveDateTime,
+
+    /// `Timer` time
+    pub local: Instant,
+}
+
+/// Drift between two `Sample`s
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Drift {
+    /// Time elapsed between the samples, according to the `Timer`
+    pub window: Duration,
+
+    /// How much the DS3231 gained (positive) or lost (negative) on the `Timer`, in milliseconds
+    pub offset_ms: i64,
+
+    /// `offset_ms` relative to `window`, in parts per million
+    pub ppm: f32,
+}
+
+/// Computes the drift of the DS3231 between the `start` and `end` samples
+///
+/// Returns `None` if no time elapsed between the samples
+pub fn drift(start: Sample, end: Sample) -> Option<Drift> {
+    let window = end.local.duration_since(start.local);
+    let local_ms = window.as_millis() as i64;
+    if local_ms == 0 {
+        return None;
+    }
+
+    let rtc_ms = (end.rtc - start.rtc).num_milliseconds();
+    let offset_ms = rtc_ms - local_ms;
+
+    Some(Drift {
+        window,
+        offset_ms,
+        ppm: offset_ms as f32 * 1e6 / local_ms as f32,
+    })
+}
+
+/// Samples both clocks when the seconds of the DS3231 roll over
+///
+/// This polls the DS3231 (yielding between polls) for up to one second
+pub async fn sample(ds3231: &mut Ds3231<'_>) -> Result<Sample, Error> {
+    let deadline = timer::deadline(EDGE_TIMEOUT);
+    let before = ds3231.get_datetime().await?;
+
+    loop {
+        task::r#yield().await;
+
+        let rtc = ds3231.get_datetime().await?;
+        if rtc != before {
+            return Ok(Sample {
+                rtc,
+                local: Timer::now(),
+            });
+        }
+
+        if deadline.expired() {
+            return Err(Error::Stopped);
+        }
+    }
+}
+
+/// Tracks the drift of a DS3231 over a window that starts at the first `update`
+pub struct DriftMonitor {
+    start: Option<Sample>,
+}
+
+impl DriftMonitor {
+    /// Creates a new monitor
+    pub const fn new() -> Self {
+        Self { start: None }
+    }
+
+    /// Takes a new sample and returns the drift since the start of the window
+    ///
+    /// The first call starts the window and returns `None`. `Timer::now` must be called at least
+    /// once every 512 seconds while the window is open (this does it)
+    pub async fn update(&mut self, ds3231: &mut Ds3231<'_>) -> Result<Option<Drift>, Error> {
+        let sample = sample(ds3231).await?;
+
+        match self.start {
+            Some(start) => Ok(drift(start, sample)),
+            None => {
+                self.start = Some(sample);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Closes the window; the next `update` starts a new one
+    pub fn reset(&mut self) {
+        self.start = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    use super::{Drift, Sample};
+    use crate::timer::{ext::DurationExt as _, Instant};
+
+    // 32,768 Hz
+    const TICKS_PER_SEC: u64 = 32_768;
+
+    fn sample(rtc: NaiveDateTime, secs: u64) -> Sample {
+        Sample {
+            rtc,
+            local: Instant::from_ticks(secs * TICKS_PER_SEC),
+        }
+    }
+
+    fn at(min: u32, sec: u32, milli: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(2020, 1, 1).and_hms_milli(0, min, sec, milli)
+    }
+
+    #[test]
+    fn in_sync() {
+        let start = sample(at(0, 0, 0), 0);
+        let end = sample(at(1, 0, 0), 60);
+        assert_eq!(
+            super::drift(start, end),
+            Some(Drift {
+                window: 60.secs(),
+                offset_ms: 0,
+                ppm: 0.,
+            })
+        );
+    }
+
+    #[test]
+    fn gain_and_loss() {
+        // the window doesn't have to start at the beginning of the program
+        let start = sample(at(0, 0, 0), 5);
+
+        // 1 ms in 1,000 s
+        let end = sample(at(16, 40, 1), 1_005);
+        assert_eq!(
+            super::drift(start, end),
+            Some(Drift {
+                window: 1_000.secs(),
+                offset_ms: 1,
+                ppm: 1.,
+            })
+        );
+
+        // -2 ms in 1,000 s
+        let end = sample(at(16, 39, 998), 1_005);
+        assert_eq!(
+            super::drift(start, end),
+            Some(Drift {
+                window: 1_000.secs(),
+                offset_ms: -2,
+                ppm: -2.,
+            })
+        );
+    }
+
+    #[test]
+    fn empty_window() {
+        let start = sample(at(0, 0, 0), 10);
+        assert_eq!(super::drift(start, start), None);
+
+        // the samples are out of order
+        let end = sample(at(0, 1, 0), 5);
+        assert_eq!(super::drift(start, end), None);
+    }
+}
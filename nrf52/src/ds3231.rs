@@ -2,20 +2,66 @@
 
 // Reference: DS3231 datasheet (19-5170; Rev 10; 3/15)
 
+use core::fmt;
+
 use async_embedded::unsync::Mutex;
-use chrono::{Datelike as _, NaiveDate, NaiveDateTime, NaiveTime, Timelike as _};
+use chrono::{Datelike as _, NaiveDate, NaiveDateTime, NaiveTime, Timelike as _, Weekday};
 
-use crate::twim::{self, Twim};
+use crate::{
+    gpio::InputPin,
+    twim::{self, Twim},
+};
 
 const ADDRESS: u8 = 0b110_1000;
 
 // Address map
 const SECONDS: u8 = 0;
 const DATE: u8 = 4;
+const ALARM1: u8 = 7;
+const ALARM2: u8 = 0x0b;
+const CONTROL: u8 = 0x0e;
+const STATUS: u8 = 0x0f;
+const TEMP_MSB: u8 = 0x11;
+const TEMP_LSB: u8 = 0x12;
+
+/// Number of registers in the address map
+pub const NREGS: usize = TEMP_LSB as usize + 1;
+
+crate::i2c_reg! {
+    // seconds, minutes and hours
+    struct Time: SECONDS => [u8; 3];
+    // day, month / century and year
+    struct Date: DATE => [u8; 3];
+    // time, day of the week and date
+    struct DateTime: SECONDS => [u8; 7];
+    // seconds, minutes, hours and day / date
+    struct Alarm1: ALARM1 => [u8; 4];
+    // minutes, hours and day / date
+    struct Alarm2: ALARM2 => [u8; 3];
+    // control and status
+    struct ControlStatus: CONTROL => [u8; 2];
+    struct Status: STATUS => u8;
+    // the whole address map
+    struct AddressMap: SECONDS => [u8; NREGS];
+}
 
 /// DS3231 I2C driver
 pub struct Ds3231<'a> {
     twim: &'a Mutex<Twim>,
+    format: HourFormat,
+}
+
+/// Format in which the device stores the hour
+///
+/// This only affects how the hour is stored in the registers of the device; `get_time` decodes
+/// either format and always returns a 24-hour `NaiveTime`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HourFormat {
+    /// 0 - 23 (default)
+    TwentyFour,
+
+    /// 1 - 12 plus an AM / PM flag
+    Twelve,
 }
 
 // 12-hour format (AM / PM)
@@ -25,119 +71,531 @@ const PM: u8 = 1 << 5;
 
 const CENTURY: u8 = 1 << 7;
 
+// Alarm mask bit (A1Mx / A2Mx); when set the register is ignored when matching the alarm
+const AM: u8 = 1 << 7;
+// (alarm day / date register) match the day of the week rather than the date
+const DY: u8 = 1 << 6;
+
+// Control register
+// Interrupt control; drive INT/SQW low when an enabled alarm fires
+const INTCN: u8 = 1 << 2;
+// Alarm 1 interrupt enable
+const A1IE: u8 = 1 << 0;
+// Alarm 2 interrupt enable
+const A2IE: u8 = 1 << 1;
+
+// Status register
+// Alarm 1 flag
+const A1F: u8 = 1 << 0;
+// Alarm 2 flag
+const A2F: u8 = 1 << 1;
+
+/// When Alarm 1 fires
+///
+/// NOTE the day of the week follows the convention of `set_datetime`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alarm1 {
+    /// Once per second
+    EverySecond,
+
+    /// Once per minute, when the seconds match (0 - 59)
+    Second(u8),
+
+    /// Once per hour, when the minutes and seconds match
+    MinuteSecond(u8, u8),
+
+    /// Once per day, when the hours, minutes and seconds match
+    Daily(NaiveTime),
+
+    /// Once per month, when the date (1 - 31), hours, minutes and seconds match
+    Monthly(u8, NaiveTime),
+
+    /// Once per week, when the day of the week, hours, minutes and seconds match
+    Weekly(Weekday, NaiveTime),
+}
+
+/// When Alarm 2 fires
+///
+/// Alarm 2 has no seconds register: it always fires at second 00 and ignores the seconds of the
+/// given times
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alarm2 {
+    /// Once per minute
+    EveryMinute,
+
+    /// Once per hour, when the minutes match (0 - 59)
+    Minute(u8),
+
+    /// Once per day, when the hours and minutes match
+    Daily(NaiveTime),
+
+    /// Once per month, when the date (1 - 31), hours and minutes match
+    Monthly(u8, NaiveTime),
+
+    /// Once per week, when the day of the week, hours and minutes match
+    Weekly(Weekday, NaiveTime),
+}
+
+/// Alarms that had fired
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Fired {
+    /// Alarm 1 had fired
+    pub alarm1: bool,
+    /// Alarm 2 had fired
+    pub alarm2: bool,
+}
+
 /// Driver error
 #[derive(Debug)]
 pub enum Error {
-    /// The RTC cannot hold this date
-    InvalidDate,
+    /// The RTC cannot hold this date, or holds an invalid date
+    InvalidDate(DateField),
 
     /// I2C error
     Twim(twim::Error),
 }
 
+/// The offending field of an invalid date
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DateField {
+    /// The year is outside the 2000 - 2199 range
+    Year(i32),
+
+    /// The month is outside the 1 - 12 range
+    Month(u8),
+
+    /// The day doesn't exist in the month
+    Day(u8),
+}
+
 impl From<twim::Error> for Error {
     fn from(e: twim::Error) -> Error {
         Error::Twim(e)
     }
 }
 
+impl twim::BusError for Error {
+    fn twim_error(&self) -> Option<&twim::Error> {
+        match self {
+            Error::Twim(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl<'a> Ds3231<'a> {
     /// Creates a new driver
+    ///
+    /// Times are written in the 24-hour format; see `set_hour_format`
     pub fn new(twim: &'a Mutex<Twim>) -> Self {
-        Self { twim }
+        Self {
+            twim,
+            format: HourFormat::TwentyFour,
+        }
+    }
+
+    /// Changes the format that `set_time` and `set_alarm1` will use
+    ///
+    /// The time currently stored in the device is not converted; call `set_time` afterwards to
+    /// switch the format of the running clock
+    pub fn set_hour_format(&mut self, format: HourFormat) {
+        self.format = format;
     }
 
     /// Returns the current date
     pub async fn get_date(&mut self) -> Result<NaiveDate, Error> {
-        let mut buf = [0; 3];
-        self.twim
+        let regs = self
+            .twim
             .lock()
             .await
-            .write_then_read(ADDRESS, &[DATE], &mut buf)
+            .read_register::<Date>(ADDRESS)
             .await?;
 
-        date_from_regs(&buf)
+        date_from_regs(&regs)
     }
 
     /// Returns the current date and time
     pub async fn get_datetime(&mut self) -> Result<NaiveDateTime, Error> {
-        let mut buf = [0; 7];
-        self.twim
+        let regs = self
+            .twim
             .lock()
             .await
-            .write_then_read(ADDRESS, &[SECONDS], &mut buf)
+            .read_register::<DateTime>(ADDRESS)
             .await?;
 
-        let time = time_from_regs(&buf[..3]);
-        let date = date_from_regs(&buf[4..])?;
+        let time = time_from_regs(&regs[..3]);
+        let date = date_from_regs(&regs[4..])?;
 
         Ok(date.and_time(time))
     }
 
     /// Returns the current time
     pub async fn get_time(&mut self) -> Result<NaiveTime, twim::Error> {
-        let mut buf = [0; 3];
-        self.twim
+        let regs = self
+            .twim
             .lock()
             .await
-            .write_then_read(ADDRESS, &[SECONDS], &mut buf)
+            .read_register::<Time>(ADDRESS)
             .await?;
 
-        Ok(time_from_regs(&buf))
+        Ok(time_from_regs(&regs))
     }
 
-    /// Changes the current date
-    pub async fn set_date(&mut self, date: NaiveDate) -> Result<(), Error> {
-        let day = to_bcd(date.day() as u8);
-        let mut month = to_bcd(date.month() as u8);
-        let mut year = date.year();
-        if year < 2000 || year > 2199 {
-            return Err(Error::InvalidDate);
+    /// Returns the temperature of the device in Celsius, with a resolution of 0.25 C
+    ///
+    /// The device measures its temperature every 64 seconds
+    pub async fn get_temperature(&mut self) -> Result<f32, twim::Error> {
+        // NOTE MSB first
+        let raw = self
+            .twim
+            .lock()
+            .await
+            .read_reg_u16(ADDRESS, TEMP_MSB)
+            .await?;
+
+        Ok(f32::from(temperature_quarters(raw)) / 4.)
+    }
+
+    /// Configures Alarm 1
+    ///
+    /// This also clears any pending Alarm 1 flag and routes the alarm to the (active low) INT pin,
+    /// which disables the square wave output. Use `wait_for_alarm` to wait for the alarm
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a second, minute or date is out of range
+    pub async fn set_alarm1(&mut self, when: Alarm1) -> Result<(), twim::Error> {
+        let regs = alarm1_to_regs(when, self.format);
+
+        let mut twim = self.twim.lock().await;
+        twim.write_register::<Alarm1>(ADDRESS, regs).await?;
+
+        let [control, status] = twim.read_register::<ControlStatus>(ADDRESS).await?;
+        twim.write_register::<ControlStatus>(ADDRESS, [control | INTCN | A1IE, clear_a1f(status)])
+            .await
+    }
+
+    /// Configures Alarm 2
+    ///
+    /// This also clears any pending Alarm 2 flag and routes the alarm to the (active low) INT pin,
+    /// which disables the square wave output. Use `wait_for_alarm` to wait for the alarm
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a minute or date is out of range
+    pub async fn set_alarm2(&mut self, when: Alarm2) -> Result<(), twim::Error> {
+        let regs = alarm2_to_regs(when, self.format);
+
+        let mut twim = self.twim.lock().await;
+        twim.write_register::<Alarm2>(ADDRESS, regs).await?;
+
+        let [control, status] = twim.read_register::<ControlStatus>(ADDRESS).await?;
+        twim.write_register::<ControlStatus>(ADDRESS, [control | INTCN | A2IE, status & !A2F])
+            .await
+    }
+
+    /// Clears the flags of both alarms, which releases the INT pin
+    ///
+    /// Returns the alarms whose flag was set
+    pub async fn clear_alarm_flags(&mut self) -> Result<Fired, twim::Error> {
+        let mut twim = self.twim.lock().await;
+        let status = twim.read_register::<Status>(ADDRESS).await?;
+        let fired = Fired {
+            alarm1: status & A1F != 0,
+            alarm2: status & A2F != 0,
+        };
+        if fired.alarm1 || fired.alarm2 {
+            twim.write_register::<Status>(ADDRESS, status & !(A1F | A2F))
+                .await?;
         }
-        year -= 2000;
-        if year >= 100 {
-            month |= CENTURY;
-            year -= 100;
+        Ok(fired)
+    }
+
+    /// Clears the Alarm 1 flag, which releases the INT pin unless Alarm 2 has also fired
+    ///
+    /// Returns `true` if the flag was set
+    pub async fn clear_alarm1(&mut self) -> Result<bool, twim::Error> {
+        let mut twim = self.twim.lock().await;
+        let status = twim.read_register::<Status>(ADDRESS).await?;
+        let fired = status & A1F != 0;
+        if fired {
+            twim.write_register::<Status>(ADDRESS, clear_a1f(status))
+                .await?;
         }
-        let year = to_bcd(year as u8);
+        Ok(fired)
+    }
+
+    /// Waits until an enabled alarm fires and then clears the alarm flags
+    ///
+    /// `int` must be connected to the INT/SQW pin of the device. That pin is open drain so `int`
+    /// needs a pull-up resistor (e.g. `Pull::Up`). Returns immediately if an alarm has already
+    /// fired but its flag has not been cleared yet. Returns the alarms that fired
+    pub async fn wait_for_alarm(&mut self, int: &mut InputPin) -> Result<Fired, twim::Error> {
+        int.wait_for_low().await;
+        self.clear_alarm_flags().await
+    }
+
+    /// Reads out all the registers of the device, from `0x00` to `0x12`, in a single transaction
+    ///
+    /// Wrap the returned value in [`Registers`] to get a human readable `Debug` representation
+    pub async fn dump_registers(&mut self) -> Result<[u8; NREGS], Error> {
+        let regs = self
+            .twim
+            .lock()
+            .await
+            .read_register::<AddressMap>(ADDRESS)
+            .await?;
+
+        Ok(regs)
+    }
+
+    /// Changes the current date
+    ///
+    /// The century bit is set for years 2100 and later
+    pub async fn set_date(&mut self, date: NaiveDate) -> Result<(), Error> {
+        let regs = date_to_regs(date)?;
+
+        self.twim
+            .lock()
+            .await
+            .write_register::<Date>(ADDRESS, regs)
+            .await?;
+        Ok(())
+    }
+
+    /// Changes the current date and time in a single transaction
+    ///
+    /// Unlike calling `set_date` and then `set_time` this can't leave the clock in an
+    /// inconsistent state if the time rolls over the date between the two writes
+    pub async fn set_datetime(&mut self, datetime: NaiveDateTime) -> Result<(), Error> {
+        let [day, month, year] = date_to_regs(datetime.date())?;
+        let [sec, min, hour] = time_to_regs(datetime.time(), self.format);
+        // NOTE the day of the week is user-defined; we use 1 = Monday
+        let weekday = datetime.weekday().number_from_monday() as u8;
 
         self.twim
             .lock()
             .await
-            .write(ADDRESS, &[DATE, day, month, year])
+            .write_register::<DateTime>(ADDRESS, [sec, min, hour, weekday, day, month, year])
             .await?;
         Ok(())
     }
 
     /// Changes the current time
+    ///
+    /// Only the seconds, minutes and hours registers are written; the date, including the century
+    /// bit, is left untouched. Use `set_datetime` to change both the date and the time
     pub async fn set_time(&mut self, time: NaiveTime) -> Result<(), twim::Error> {
-        let sec = to_bcd(time.second() as u8);
-        let min = to_bcd(time.minute() as u8);
-        let hour = to_bcd(time.hour() as u8);
+        let regs = time_to_regs(time, self.format);
 
         self.twim
             .lock()
             .await
-            .write(ADDRESS, &[SECONDS, sec, min, hour])
+            .write_register::<Time>(ADDRESS, regs)
             .await
     }
 }
 
+/// Converts a Unix timestamp (seconds since 1970-01-01 00:00:00 UTC) into a date and time that
+/// the RTC can hold
+///
+/// Returns `InvalidDate(DateField::Year(..))` if the timestamp falls outside the 2000 - 2199
+/// range
+pub fn datetime_from_unix(secs: i64) -> Result<NaiveDateTime, Error> {
+    // 2000-01-01 00:00:00
+    const MIN: i64 = 946_684_800;
+    // 2200-01-01 00:00:00
+    const END: i64 = 7_258_118_400;
+
+    if secs < MIN || secs >= END {
+        // approximate year, for error reporting only
+        let year = 1970 + secs.div_euclid(31_556_952);
+        return Err(Error::InvalidDate(DateField::Year(year as i32)));
+    }
+
+    // NOTE the range check above guarantees this can't fail
+    Ok(NaiveDateTime::from_timestamp(secs, 0))
+}
+
+/// Parses a `sync <timestamp>` command, e.g. received over a serial console, where `<timestamp>`
+/// is a Unix timestamp
+///
+/// Returns `None` if `cmd` is not a well-formed `sync` command; otherwise returns the output of
+/// `datetime_from_unix`, which can be passed to `Ds3231::set_datetime`
+pub fn parse_sync(cmd: &str) -> Option<Result<NaiveDateTime, Error>> {
+    const CMD_SYNC: &str = "sync ";
+
+    let cmd = cmd.trim();
+    if !cmd.starts_with(CMD_SYNC) {
+        return None;
+    }
+
+    let secs = cmd[CMD_SYNC.len()..].trim_start().parse().ok()?;
+    Some(datetime_from_unix(secs))
+}
+
+/// Register dump whose `Debug` implementation decodes the most relevant fields
+///
+/// The decoding does not validate the register contents so it can be used to inspect a
+/// misbehaving device
+pub struct Registers(pub [u8; NREGS]);
+
+impl fmt::Debug for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let regs = &self.0;
+
+        let hour = hour_from_reg(regs[2]);
+        let month = regs[DATE as usize + 1];
+        let century = if month & CENTURY != 0 { 21 } else { 20 };
+        let quarters = temperature_quarters(u16::from_be_bytes([
+            regs[TEMP_MSB as usize],
+            regs[TEMP_LSB as usize],
+        ]));
+        let sign = if quarters < 0 { "-" } else { "" };
+        let quarters = quarters.abs();
+
+        f.debug_struct("Registers")
+            .field(
+                "time",
+                &format_args!(
+                    "{:02}:{:02}:{:02}",
+                    hour,
+                    from_bcd(regs[1]),
+                    from_bcd(regs[0])
+                ),
+            )
+            .field(
+                "date",
+                &format_args!(
+                    "{}{:02}-{:02}-{:02}",
+                    century,
+                    from_bcd(regs[DATE as usize + 2]),
+                    from_bcd(month & !CENTURY),
+                    from_bcd(regs[DATE as usize])
+                ),
+            )
+            .field("control", &format_args!("{:#010b}", regs[CONTROL as usize]))
+            .field("status", &format_args!("{:#010b}", regs[STATUS as usize]))
+            .field(
+                "temperature",
+                &format_args!("{}{}.{:02} C", sign, quarters / 4, (quarters % 4) * 25),
+            )
+            .finish()
+    }
+}
+
+// Decodes the temperature registers (MSB first) into quarters of degree Celsius
+//
+// The temperature is a 10-bit two's complement number, left aligned
+fn temperature_quarters(raw: u16) -> i16 {
+    raw as i16 >> 6
+}
+
+// New value of the status register that clears the Alarm 1 flag
+//
+// Writing a 1 to the alarm flags leaves them unchanged so the Alarm 2 flag is preserved. OSF
+// (bit 7) is also preserved; EN32kHz (bit 3) keeps its configuration; the rest are read-only
+fn clear_a1f(status: u8) -> u8 {
+    status & !A1F
+}
+
+// Encodes `when` into the Alarm 1 registers: seconds, minutes, hours and day / date
+fn alarm1_to_regs(when: Alarm1, format: HourFormat) -> [u8; 4] {
+    // Alarm 1 matches the same registers as Alarm 2 plus the seconds
+    let with_seconds = |time: NaiveTime, alarm2: Alarm2| {
+        let [min, hour, day] = alarm2_to_regs(alarm2, format);
+        [to_bcd(time.second() as u8), min, hour, day]
+    };
+
+    match when {
+        Alarm1::EverySecond => [AM, AM, AM, AM],
+        Alarm1::Second(sec) => [minute_or_second(sec), AM, AM, AM],
+        Alarm1::MinuteSecond(min, sec) => [minute_or_second(sec), minute_or_second(min), AM, AM],
+        Alarm1::Daily(time) => with_seconds(time, Alarm2::Daily(time)),
+        Alarm1::Monthly(date, time) => with_seconds(time, Alarm2::Monthly(date, time)),
+        Alarm1::Weekly(weekday, time) => with_seconds(time, Alarm2::Weekly(weekday, time)),
+    }
+}
+
+// Encodes `when` into the Alarm 2 registers: minutes, hours and day / date
+fn alarm2_to_regs(when: Alarm2, format: HourFormat) -> [u8; 3] {
+    let time = |time: NaiveTime| {
+        [
+            to_bcd(time.minute() as u8),
+            hour_to_reg(time.hour() as u8, format),
+        ]
+    };
+
+    match when {
+        Alarm2::EveryMinute => [AM, AM, AM],
+        Alarm2::Minute(min) => [minute_or_second(min), AM, AM],
+        Alarm2::Daily(t) => {
+            let [min, hour] = time(t);
+            [min, hour, AM]
+        }
+        Alarm2::Monthly(date, t) => {
+            assert!((1..=31).contains(&date), "date out of range");
+            let [min, hour] = time(t);
+            [min, hour, to_bcd(date)]
+        }
+        Alarm2::Weekly(weekday, t) => {
+            let [min, hour] = time(t);
+            [min, hour, DY | weekday.number_from_monday() as u8]
+        }
+    }
+}
+
+fn minute_or_second(x: u8) -> u8 {
+    assert!(x < 60, "minute or second out of range");
+    to_bcd(x)
+}
+
+fn time_to_regs(time: NaiveTime, format: HourFormat) -> [u8; 3] {
+    let sec = to_bcd(time.second() as u8);
+    let min = to_bcd(time.minute() as u8);
+    let hour = hour_to_reg(time.hour() as u8, format);
+
+    [sec, min, hour]
+}
+
 fn time_from_regs(regs: &[u8]) -> NaiveTime {
     let sec = from_bcd(regs[0]);
     let min = from_bcd(regs[1]);
-    let hour = if regs[2] & HOUR12 != 0 {
-        if regs[2] & PM != 0 {
-            from_bcd(12 + (regs[2] & !PM))
+    let hour = hour_from_reg(regs[2]);
+
+    NaiveTime::from_hms(hour.into(), min.into(), sec.into())
+}
+
+// Decodes the hours register (either format) into a 0 - 23 hour
+fn hour_from_reg(reg: u8) -> u8 {
+    if reg & HOUR12 != 0 {
+        // 12 AM is midnight; 12 PM is noon
+        let hour = from_bcd(reg & !(HOUR12 | PM)) % 12;
+        if reg & PM != 0 {
+            hour + 12
         } else {
-            from_bcd(regs[2])
+            hour
         }
     } else {
         // 24-hour format
-        from_bcd(regs[2])
-    };
+        from_bcd(reg)
+    }
+}
 
-    NaiveTime::from_hms(hour.into(), min.into(), sec.into())
+// Encodes a 0 - 23 `hour` into the hours register
+fn hour_to_reg(hour: u8, format: HourFormat) -> u8 {
+    match format {
+        HourFormat::TwentyFour => to_bcd(hour),
+        HourFormat::Twelve => {
+            let pm = if hour >= 12 { PM } else { 0 };
+            let hour = match hour % 12 {
+                0 => 12,
+                h => h,
+            };
+
+            HOUR12 | pm | to_bcd(hour)
+        }
+    }
 }
 
 fn date_from_regs(regs: &[u8]) -> Result<NaiveDate, Error> {
@@ -145,7 +603,30 @@ fn date_from_regs(regs: &[u8]) -> Result<NaiveDate, Error> {
     let month = from_bcd(regs[1] & !CENTURY);
     let year = i32::from(from_bcd(regs[2])) + if regs[1] & CENTURY != 0 { 2100 } else { 2000 };
 
-    NaiveDate::from_ymd_opt(year, month.into(), day.into()).ok_or(Error::InvalidDate)
+    NaiveDate::from_ymd_opt(year, month.into(), day.into()).ok_or_else(|| {
+        Error::InvalidDate(if month < 1 || month > 12 {
+            DateField::Month(month)
+        } else {
+            DateField::Day(day)
+        })
+    })
+}
+
+fn date_to_regs(date: NaiveDate) -> Result<[u8; 3], Error> {
+    let day = to_bcd(date.day() as u8);
+    let mut month = to_bcd(date.month() as u8);
+    let mut year = date.year();
+    if year < 2000 || year > 2199 {
+        return Err(Error::InvalidDate(DateField::Year(year)));
+    }
+    year -= 2000;
+    if year >= 100 {
+        month |= CENTURY;
+        year -= 100;
+    }
+    let year = to_bcd(year as u8);
+
+    Ok([day, month, year])
 }
 
 fn from_bcd(bcd: u8) -> u8 {
@@ -155,9 +636,113 @@ fn from_bcd(bcd: u8) -> u8 {
     10 * tens + units
 }
 
-#[allow(dead_code)]
 fn to_bcd(x: u8) -> u8 {
     let units = x % 10;
     let tens = x / 10;
     tens << 4 | units
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{DateField, Error, Registers};
+    use crate::twim;
+
+    #[test]
+    fn registers_debug() {
+        // 12:34:56 (24-hour format), 2020-06-15; alarms cleared; INTCN and RS2:1 set;
+        // OSF and EN32kHz set; 25.25 C
+        let mut regs = [
+            0x56, 0x34, 0x12, 0x03, 0x15, 0x06, 0x20, // time and date
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // alarms
+            0x1C, 0x88, 0x00, // control, status, aging offset
+            0x19, 0x40, // temperature
+        ];
+        assert_eq!(
+            format!("{:?}", Registers(regs)),
+            "Registers { time: 12:34:56, date: 2020-06-15, control: 0b00011100, \
+             status: 0b10001000, temperature: 25.25 C }",
+        );
+
+        // 3 PM (12-hour format); century bit set; -1.25 C
+        regs[2] = 0x63;
+        regs[5] |= 1 << 7;
+        regs[17] = 0xFE;
+        regs[18] = 0xC0;
+        assert_eq!(
+            format!("{:?}", Registers(regs)),
+            "Registers { time: 15:34:56, date: 2120-06-15, control: 0b00011100, \
+             status: 0b10001000, temperature: -1.25 C }",
+        );
+    }
+
+    #[test]
+    fn parse_sync_valid() {
+        let valid = [
+            (
+                "sync 1583002800",
+                NaiveDate::from_ymd(2020, 2, 29).and_hms(19, 0, 0),
+            ),
+            // the first and last second the RTC can hold
+            (
+                "sync 946684800",
+                NaiveDate::from_ymd(2000, 1, 1).and_hms(0, 0, 0),
+            ),
+            (
+                "sync 7258118399",
+                NaiveDate::from_ymd(2199, 12, 31).and_hms(23, 59, 59),
+            ),
+            // surrounding whitespace is ignored
+            (
+                "  sync   1583002800\r",
+                NaiveDate::from_ymd(2020, 2, 29).and_hms(19, 0, 0),
+            ),
+        ];
+        for &(cmd, expected) in valid.iter() {
+            match super::parse_sync(cmd) {
+                Some(Ok(datetime)) => assert_eq!(datetime, expected),
+                res => panic!("{}: {:?}", cmd, res),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_sync_out_of_range() {
+        // the error reports the (approximate) year of the timestamp
+        for &(cmd, year) in [
+            ("sync 0", 1970),
+            ("sync 946684799", 1999),
+            ("sync 7258118400", 2200),
+            ("sync -1", 1969),
+        ]
+        .iter()
+        {
+            match super::parse_sync(cmd) {
+                Some(Err(Error::InvalidDate(field))) => assert_eq!(field, DateField::Year(year)),
+                res => panic!("{}: {:?}", cmd, res),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_sync_malformed() {
+        // not `sync` commands
+        for &cmd in ["sync", "sync ", "sync abc", "sync 1.5", "date", "synced 0"].iter() {
+            assert!(super::parse_sync(cmd).is_none(), "{}", cmd);
+        }
+    }
+
+    #[test]
+    fn error_debug() {
+        // the I2C error is decoded
+        let e = Error::Twim(twim::Error::Src(0b010));
+        assert_eq!(format!("{:?}", e), "Twim(Src(address NACK))");
+
+        // the offending field of the date
+        let e = Error::InvalidDate(DateField::Year(2200));
+        assert_eq!(format!("{:?}", e), "InvalidDate(Year(2200))");
+        let e = Error::InvalidDate(DateField::Month(13));
+        assert_eq!(format!("{:?}", e), "InvalidDate(Month(13))");
+    }
+}
@@ -0,0 +1,344 @@
+//! Asynchronous AT24Cxx (I2C EEPROM) driver
+
+// Reference: Microchip AT24C02C (DS20005202) and AT24C256C (DS20005232) datasheets
+
+use core::{cmp, iter, marker::PhantomData, time::Duration};
+
+use async_embedded::unsync::Mutex;
+
+use crate::{
+    timer::Timer,
+    twim::{self, Twim},
+};
+
+/// Device address when the A2, A1 and A0 pins are tied to ground
+pub const ADDRESS: u8 = 0b101_0000;
+
+// largest read the TWIM can do in a single transfer
+const MAX_READ: usize = 255;
+
+// time between ACK polls while a write cycle is in progress
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+// the write cycle takes at most 5 ms; give up after twice that
+const MAX_POLLS: u8 = 10;
+
+/// A member of the AT24Cxx family
+pub trait Model {
+    /// Capacity in bytes
+    const SIZE: usize;
+
+    /// Size of a write page in bytes; a single write can't cross a page boundary
+    const PAGE_SIZE: usize;
+
+    /// Number of bytes used to send the memory address: 1 for devices up to 2 KiB, 2 otherwise
+    ///
+    /// Devices with a single address byte and more than 256 bytes take the upper bits of the
+    /// memory address in place of the A0 - A2 bits of the device address
+    const ADDRESS_BYTES: usize;
+}
+
+macro_rules! models {
+    ($($(#[$attr:meta])* $name:ident: $size:expr, $page:expr, $abytes:expr;)+) => {
+        $(
+            $(#[$attr])*
+            pub struct $name;
+
+            impl Model for $name {
+                const SIZE: usize = $size;
+                const PAGE_SIZE: usize = $page;
+                const ADDRESS_BYTES: usize = $abytes;
+            }
+        )+
+    }
+}
+
+models! {
+    /// 1 Kbit (128 B)
+    At24c01: 128, 8, 1;
+    /// 2 Kbit (256 B)
+    At24c02: 256, 8, 1;
+    /// 4 Kbit (512 B)
+    At24c04: 512, 16, 1;
+    /// 8 Kbit (1 KiB)
+    At24c08: 1024, 16, 1;
+    /// 16 Kbit (2 KiB)
+    At24c16: 2048, 16, 1;
+    /// 32 Kbit (4 KiB)
+    At24c32: 4096, 32, 2;
+    /// 64 Kbit (8 KiB)
+    At24c64: 8192, 32, 2;
+    /// 128 Kbit (16 KiB)
+    At24c128: 16384, 64, 2;
+    /// 256 Kbit (32 KiB)
+    At24c256: 32768, 64, 2;
+    /// 512 Kbit (64 KiB)
+    At24c512: 65536, 128, 2;
+}
+
+/// AT24Cxx I2C driver
+pub struct At24<'a, M>
+where
+    M: Model,
+{
+    twim: &'a Mutex<Twim>,
+    address: u8,
+    _model: PhantomData<M>,
+}
+
+/// Driver error
+#[derive(Debug)]
+pub enum Error {
+    /// The access goes past the end of the memory
+    OutOfBounds,
+
+    /// The device didn't finish its write cycle in time
+    Timeout,
+
+    /// I2C error
+    Twim(twim::Error),
+}
+
+impl From<twim::Error> for Error {
+    fn from(e: twim::Error) -> Self {
+        Error::Twim(e)
+    }
+}
+
+impl twim::BusError for Error {
+    fn twim_error(&self) -> Option<&twim::Error> {
+        match self {
+            Error::Twim(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, M> At24<'a, M>
+where
+    M: Model,
+{
+    /// Creates a new driver for the device with the specified address (see `ADDRESS`)
+    pub fn new(twim: &'a Mutex<Twim>, address: u8) -> Self {
+        Self {
+            twim,
+            address,
+            _model: PhantomData,
+        }
+    }
+
+    /// Fills `buf` with the contents of the memory starting at address `addr`
+    pub async fn read(&mut self, mut addr: usize, buf: &mut [u8]) -> Result<(), Error> {
+        check_bounds::<M>(addr, buf.len())?;
+
+        // NOTE the device auto-increments the memory address across pages (and blocks) so the
+        // reads are only split to fit the DMA transfer size
+        for chunk in buf.chunks_mut(MAX_READ) {
+            let (address, abytes) = locate::<M>(self.address, addr);
+            self.twim
+                .lock()
+                .await
+                .write_then_read(address, &abytes[..M::ADDRESS_BYTES], chunk)
+                .await?;
+            addr += chunk.len();
+        }
+
+        Ok(())
+    }
+
+    /// Writes `data` into the memory starting at address `addr`
+    ///
+    /// The write is split at page boundaries. After each page the device goes through an
+    /// internal write cycle (up to 5 ms) during which it doesn't respond; this operation waits
+    /// for the cycle to complete by polling the device, sleeping on `timer` between polls. The
+    /// bus is released while sleeping
+    pub async fn write(
+        &mut self,
+        timer: &mut Timer,
+        addr: usize,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        check_bounds::<M>(addr, data.len())?;
+
+        for (address, abytes, page) in page_writes::<M>(self.address, addr, data) {
+            self.twim
+                .lock()
+                .await
+                .write_chained(address, &[&abytes[..M::ADDRESS_BYTES], page])
+                .await?;
+            self.wait_for_write_cycle(timer, address).await?;
+        }
+
+        Ok(())
+    }
+
+    // NOTE the device NACKs its address until the write cycle is over
+    async fn wait_for_write_cycle(&mut self, timer: &mut Timer, address: u8) -> Result<(), Error> {
+        for _ in 0..MAX_POLLS {
+            timer.wait(POLL_INTERVAL).await;
+
+            if self.twim.lock().await.probe(address).await? {
+                return Ok(());
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+}
+
+// Returns the device address and the address bytes to use to access memory address `addr` of
+// the device at address `device`
+fn locate<M>(device: u8, addr: usize) -> (u8, [u8; 2])
+where
+    M: Model,
+{
+    if M::ADDRESS_BYTES == 1 {
+        // bits 8 - 10 of the memory address (the "block") replace the A0 - A2 bits
+        (device | (addr >> 8) as u8 & 0b111, [addr as u8, 0])
+    } else {
+        (device, (addr as u16).to_be_bytes())
+    }
+}
+
+// Splits the write of `data` at memory address `addr` at the page boundaries; yields the device
+// address, the address bytes and the data of each page write
+fn page_writes<'d, M>(
+    device: u8,
+    mut addr: usize,
+    mut data: &'d [u8],
+) -> impl Iterator<Item = (u8, [u8; 2], &'d [u8])>
+where
+    M: Model,
+{
+    iter::from_fn(move || {
+        if data.is_empty() {
+            return None;
+        }
+
+        let n = cmp::min(M::PAGE_SIZE - addr % M::PAGE_SIZE, data.len());
+        let (page, rest) = data.split_at(n);
+        let (address, abytes) = locate::<M>(device, addr);
+        addr += n;
+        data = rest;
+        Some((address, abytes, page))
+    })
+}
+
+fn check_bounds<M>(addr: usize, len: usize) -> Result<(), Error>
+where
+    M: Model,
+{
+    if addr.checked_add(len).map(|end| end <= M::SIZE) == Some(true) {
+        Ok(())
+    } else {
+        Err(Error::OutOfBounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::marker::PhantomData;
+
+    use super::{At24c04, At24c256, Model, ADDRESS};
+
+    // An AT24Cxx that's written through the frames the driver sends. Like the real device, a
+    // write that runs past the end of a page wraps around to the start of the same page
+    struct Mock<M> {
+        memory: Vec<u8>,
+        frames: usize,
+        _model: PhantomData<M>,
+    }
+
+    impl<M> Mock<M>
+    where
+        M: Model,
+    {
+        fn new() -> Self {
+            Self {
+                memory: vec![0xff; M::SIZE],
+                frames: 0,
+                _model: PhantomData,
+            }
+        }
+
+        // Writes `data` at `addr` the way `At24::write` does
+        fn write(&mut self, addr: usize, data: &[u8]) {
+            super::check_bounds::<M>(addr, data.len()).unwrap();
+
+            for (address, abytes, page) in super::page_writes::<M>(ADDRESS, addr, data) {
+                let frame = [&abytes[..M::ADDRESS_BYTES], page].concat();
+                self.frame(address, &frame);
+            }
+        }
+
+        fn frame(&mut self, address: u8, frame: &[u8]) {
+            let (addr, data) = if M::ADDRESS_BYTES == 1 {
+                assert_eq!(address & !0b111, ADDRESS);
+                let block = usize::from(address & 0b111);
+                (block << 8 | usize::from(frame[0]), &frame[1..])
+            } else {
+                assert_eq!(address, ADDRESS);
+                (
+                    usize::from(u16::from_be_bytes([frame[0], frame[1]])),
+                    &frame[2..],
+                )
+            };
+
+            let page = addr - addr % M::PAGE_SIZE;
+            for (i, byte) in data.iter().enumerate() {
+                let offset = (addr + i) % M::PAGE_SIZE;
+                self.memory[page + offset] = *byte;
+            }
+            self.frames += 1;
+        }
+    }
+
+    fn data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8).collect()
+    }
+
+    // a write across 2 page boundaries, the second one also a block boundary
+    #[test]
+    fn cross_page_one_address_byte() {
+        let mut eeprom = Mock::<At24c04>::new();
+        let data = data(40);
+        eeprom.write(0xf5, &data);
+
+        // 11 + 16 + 13 bytes
+        assert_eq!(eeprom.frames, 3);
+        assert_eq!(&eeprom.memory[0xf5..0xf5 + 40], &data[..]);
+        // nothing else was written
+        assert!(eeprom.memory[..0xf5].iter().all(|b| *b == 0xff));
+        assert!(eeprom.memory[0xf5 + 40..].iter().all(|b| *b == 0xff));
+    }
+
+    #[test]
+    fn cross_page_two_address_bytes() {
+        let mut eeprom = Mock::<At24c256>::new();
+        let data = data(70);
+        eeprom.write(0x7fbc, &data[..68]);
+        eeprom.write(60, &data);
+
+        // 4 + 64 bytes, ending at the end of the memory
+        assert_eq!(&eeprom.memory[0x7fbc..], &data[..68]);
+        // 4 + 64 + 2 bytes
+        assert_eq!(eeprom.frames, 5);
+        assert_eq!(&eeprom.memory[60..130], &data[..]);
+    }
+
+    #[test]
+    fn single_page() {
+        let mut eeprom = Mock::<At24c256>::new();
+        let data = data(64);
+        eeprom.write(128, &data);
+
+        assert_eq!(eeprom.frames, 1);
+        assert_eq!(&eeprom.memory[128..192], &data[..]);
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        assert!(super::check_bounds::<At24c04>(500, 12).is_ok());
+        assert!(super::check_bounds::<At24c04>(500, 13).is_err());
+        assert!(super::check_bounds::<At24c04>(usize::max_value(), 2).is_err());
+    }
+}
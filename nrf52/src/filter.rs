@@ -0,0 +1,183 @@
+//! Filters for smoothing sensor readings
+
+use generic_array::{typenum::Unsigned, ArrayLength, GenericArray};
+
+/// Exponentially weighted moving average
+///
+/// `avg = alpha * sample + (1 - alpha) * avg`; the first sample initializes the average
+pub struct Ewma {
+    alpha: f32,
+    avg: Option<f32>,
+}
+
+impl Ewma {
+    /// Creates a new filter
+    ///
+    /// `alpha` must be in the range `(0, 1]`; smaller values smooth more but react slower to
+    /// changes. `alpha = 1` disables filtering
+    pub fn new(alpha: f32) -> Self {
+        assert!(alpha > 0. && alpha <= 1.);
+
+        Self { alpha, avg: None }
+    }
+
+    /// Feeds a new `sample` into the filter and returns the filtered value
+    pub fn push(&mut self, sample: f32) -> f32 {
+        let avg = match self.avg {
+            Some(avg) => self.alpha * sample + (1. - self.alpha) * avg,
+            None => sample,
+        };
+        self.avg = Some(avg);
+        avg
+    }
+
+    /// Returns the filtered value, or `None` if no sample has been pushed yet
+    pub fn value(&self) -> Option<f32> {
+        self.avg
+    }
+
+    /// Forgets all the samples
+    pub fn reset(&mut self) {
+        self.avg = None;
+    }
+}
+
+/// Average of the last `N` samples
+///
+/// Until `N` samples have been pushed, this is the average of all the samples pushed so far
+pub struct MovingAverage<N>
+where
+    N: ArrayLength<f32>,
+{
+    samples: GenericArray<f32, N>,
+    // index of the next sample to overwrite
+    next: usize,
+    len: usize,
+}
+
+impl<N> MovingAverage<N>
+where
+    N: ArrayLength<f32>,
+{
+    /// Creates a new filter
+    pub fn new() -> Self {
+        assert!(N::USIZE != 0);
+
+        Self {
+            samples: GenericArray::default(),
+            next: 0,
+            len: 0,
+        }
+    }
+
+    /// Feeds a new `sample` into the filter and returns the filtered value
+    pub fn push(&mut self, sample: f32) -> f32 {
+        self.samples[self.next] = sample;
+        self.next = (self.next + 1) % N::USIZE;
+        if self.len < N::USIZE {
+            self.len += 1;
+        }
+
+        self.average()
+    }
+
+    /// Returns the filtered value, or `None` if no sample has been pushed yet
+    pub fn value(&self) -> Option<f32> {
+        if self.len == 0 {
+            None
+        } else {
+            Some(self.average())
+        }
+    }
+
+    /// Forgets all the samples
+    pub fn reset(&mut self) {
+        self.next = 0;
+        self.len = 0;
+    }
+
+    // NOTE the sum is recomputed every time (instead of keeping a running sum) to avoid
+    // accumulating rounding errors
+    fn average(&self) -> f32 {
+        // the first `len` samples are valid: either the buffer is full or it hasn't wrapped around
+        let sum: f32 = self.samples[..self.len].iter().sum();
+        sum / self.len as f32
+    }
+}
+
+impl<N> Default for MovingAverage<N>
+where
+    N: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::typenum::consts::U4;
+
+    use super::{Ewma, MovingAverage};
+
+    #[test]
+    fn ewma_warm_up() {
+        let mut f = Ewma::new(0.5);
+        assert_eq!(f.value(), None);
+
+        // the first sample is taken as is
+        assert_eq!(f.push(8.), 8.);
+        assert_eq!(f.push(0.), 4.);
+        assert_eq!(f.push(0.), 2.);
+        assert_eq!(f.push(4.), 3.);
+        assert_eq!(f.value(), Some(3.));
+
+        // the average starts over
+        f.reset();
+        assert_eq!(f.value(), None);
+        assert_eq!(f.push(-1.), -1.);
+    }
+
+    #[test]
+    fn ewma_no_filtering() {
+        let mut f = Ewma::new(1.);
+        for sample in &[3., -2., 7.] {
+            assert_eq!(f.push(*sample), *sample);
+        }
+    }
+
+    #[test]
+    fn moving_average_fills_up() {
+        let mut f = MovingAverage::<U4>::new();
+        assert_eq!(f.value(), None);
+
+        // average of the samples pushed so far
+        assert_eq!(f.push(4.), 4.);
+        assert_eq!(f.push(8.), 6.);
+        assert_eq!(f.push(0.), 4.);
+        assert_eq!(f.push(4.), 4.);
+        assert_eq!(f.value(), Some(4.));
+    }
+
+    #[test]
+    fn moving_average_wraps_around() {
+        let mut f = MovingAverage::<U4>::new();
+        for sample in &[1., 2., 3., 4.] {
+            f.push(*sample);
+        }
+
+        // the oldest sample is dropped: 1
+        assert_eq!(f.push(5.), 3.5);
+        // then 2, 3 and 4
+        assert_eq!(f.push(6.), 4.5);
+        assert_eq!(f.push(7.), 5.5);
+        assert_eq!(f.push(8.), 6.5);
+        // the buffer wrapped around twice
+        assert_eq!(f.push(9.), 7.5);
+
+        // the samples from before the reset don't count
+        f.reset();
+        assert_eq!(f.value(), None);
+        assert_eq!(f.push(2.), 2.);
+    }
+}
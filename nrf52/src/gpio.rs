@@ -0,0 +1,720 @@
+//! General Purpose Input / Output
+
+use core::{
+    future::Future,
+    pin::Pin as PinRef,
+    sync::atomic::{self, AtomicBool, AtomicU8, Ordering},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use async_embedded::task::TimedOut;
+use cortex_m::peripheral::NVIC;
+use pac::{p0::RegisterBlock, Interrupt, GPIOTE, P0, P1, PPI, TIMER1, UICR};
+
+use crate::{timer::Timer, BorrowUnchecked as _};
+
+/// A validated pin assignment
+///
+/// Use the [`pin!`](../macro.pin.html) macro to validate the assignment at compile time or
+/// `Pin::new` to validate it at runtime
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pin {
+    port: u8,
+    pin: u8,
+}
+
+/// Invalid pin assignment
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// The nRF52840 and the nRF52833 only have ports 0 and 1; the nRF52832 only has port 0
+    NoSuchPort,
+
+    /// Port 0 has 32 pins; port 1 has 16 pins on the nRF52840 and 10 pins on the nRF52833
+    NoSuchPin,
+
+    /// P0.00 and P0.01 are connected to the 32.768 KHz crystal that drives the LFCLK (see
+    /// `pre_init`)
+    Xtal,
+
+    /// P0.09 and P0.10 are configured as NFC antenna pins (see `UICR.NFCPINS`)
+    Nfc,
+}
+
+impl Pin {
+    /// Validates the pin assignment
+    ///
+    /// This does not check the NFC configuration, which is stored in the UICR; that's done by
+    /// `Pin::check` and by the drivers when a pin is handed to them
+    pub const fn new(port: u8, pin: u8) -> Result<Self, Error> {
+        match validate(port, pin) {
+            Some(e) => Err(e),
+            None => Ok(Self { port, pin }),
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn new_unchecked(port: u8, pin: u8) -> Self {
+        Self { port, pin }
+    }
+
+    #[doc(hidden)]
+    pub const fn is_valid(port: u8, pin: u8) -> bool {
+        match validate(port, pin) {
+            Some(_) => false,
+            None => true,
+        }
+    }
+
+    /// Checks that the pin can be routed to a peripheral given the current chip configuration
+    pub fn check(self) -> Result<Self, Error> {
+        let nfc = self.port == 0 && (self.pin == 9 || self.pin == 10);
+        // NOTE(borrow_unchecked) single-instruction read of a read-only (at runtime) register
+        // bit 0: PROTECT; 0 = pins are used as GPIOs, 1 = pins are used as the NFC antenna
+        if nfc && UICR::borrow_unchecked(|uicr| uicr.nfcpins.read().bits() & 1 != 0) {
+            Err(Error::Nfc)
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Returns the port number
+    pub fn port(self) -> u8 {
+        self.port
+    }
+
+    /// Returns the pin number
+    pub fn pin(self) -> u8 {
+        self.pin
+    }
+
+    /// The `PORT` bit of the `PSEL.*` registers
+    pub(crate) fn psel_port(self) -> bool {
+        self.port != 0
+    }
+
+    pub(crate) fn gpio_port(self) -> Port {
+        if self.port == 0 {
+            Port::P0
+        } else {
+            Port::P1
+        }
+    }
+}
+
+/// Logic level of a pin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PinState {
+    /// Logic low
+    Low,
+
+    /// Logic high
+    High,
+}
+
+/// A GPIO port
+// NOTE(borrow_unchecked) all writes are single-instruction, atomic operations on stateless
+// (write-1-to-set / write-1-to-clear) registers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Port {
+    /// Port 0 (P0.00 - P0.31)
+    P0,
+
+    /// Port 1 (P1.00 - P1.15)
+    P1,
+}
+
+impl Port {
+    /// Configures the pins in `mask` as outputs
+    pub fn set_outputs(self, mask: u32) {
+        self.borrow(|port| port.dirset.write(|w| unsafe { w.bits(mask) }))
+    }
+
+    /// Configures the pins in `mask` as inputs
+    pub fn clear_outputs(self, mask: u32) {
+        self.borrow(|port| port.dirclr.write(|w| unsafe { w.bits(mask) }))
+    }
+
+    /// Drives the `pin` of this port to the given `state`
+    pub fn write(self, pin: u8, state: PinState) {
+        match state {
+            PinState::High => self.write_mask(1 << pin, 0),
+            PinState::Low => self.write_mask(0, 1 << pin),
+        }
+    }
+
+    /// Drives the pins in `set` high and then the pins in `clear` low
+    ///
+    /// This takes two register writes (`OUTSET` then `OUTCLR`) so the pins in `clear` change
+    /// state one bus cycle (~16 ns @ 64 MHz) after the pins in `set`. Pins in the same mask change
+    /// state simultaneously
+    pub fn write_mask(self, set: u32, clear: u32) {
+        self.borrow(|port| {
+            if set != 0 {
+                port.outset.write(|w| unsafe { w.bits(set) });
+            }
+
+            if clear != 0 {
+                port.outclr.write(|w| unsafe { w.bits(clear) });
+            }
+        })
+    }
+
+    fn borrow<T>(self, f: impl FnOnce(&RegisterBlock) -> T) -> T {
+        match self {
+            Port::P0 => P0::borrow_unchecked(|p0| f(p0)),
+            Port::P1 => P1::borrow_unchecked(|p1| f(p1)),
+        }
+    }
+}
+
+/// Internal pull resistor
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pull {
+    /// No pull resistor
+    None,
+
+    /// Pull-down resistor
+    Down,
+
+    /// Pull-up resistor
+    Up,
+}
+
+/// Signal transition
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edge {
+    /// Low to high transition
+    Rising,
+
+    /// High to low transition
+    Falling,
+
+    /// Either transition
+    Any,
+}
+
+/// Number of GPIOTE channels
+const NCHANNELS: usize = 8;
+
+// bitmask of the GPIOTE channels that are in use
+static CHANNELS: AtomicU8 = AtomicU8::new(0);
+
+/// An input pin whose transitions can be `await`-ed
+///
+/// Each `InputPin` uses one of the 8 GPIOTE channels; the channel is released when the
+/// `InputPin` is dropped
+pub struct InputPin {
+    pin: Pin,
+    channel: usize,
+}
+
+impl InputPin {
+    /// Configures `pin` as an input with the given `pull` resistor
+    ///
+    /// # Panics
+    ///
+    /// This function panics if all the GPIOTE channels are in use
+    pub fn new(pin: Pin, pull: Pull) -> Result<Self, Error> {
+        let pin = pin.check()?;
+        let channel = alloc_channel().expect("all GPIOTE channels are in use");
+
+        let pull = match pull {
+            Pull::None => 0,
+            Pull::Down => 1,
+            Pull::Up => 3,
+        };
+        // DIR = input, INPUT = connect, PULL = `pull`
+        port(pin).borrow(|port| {
+            port.pin_cnf[usize::from(pin.pin)].write(|w| unsafe { w.bits(pull << 2) })
+        });
+        // MODE = event, POLARITY = none (set by `wait_*`)
+        GPIOTE::borrow_unchecked(|gpiote| {
+            gpiote.config[channel].write(|w| unsafe { w.bits(config(pin, 0)) })
+        });
+        // NOTE(unsafe) the `GPIOTE` handler only wakes tasks whose channel interrupt is enabled
+        unsafe { NVIC::unmask(Interrupt::GPIOTE) }
+
+        Ok(Self { pin, channel })
+    }
+
+    /// Returns `true` if the pin is currently driven high
+    pub fn is_high(&self) -> bool {
+        // NOTE(borrow_unchecked) single-instruction read of a read-only register
+        port(self.pin).borrow(|port| port.in_.read().bits() & (1 << self.pin.pin) != 0)
+    }
+
+    /// Returns `true` if the pin is currently driven low
+    pub fn is_low(&self) -> bool {
+        !self.is_high()
+    }
+
+    /// Waits for the next `edge`
+    pub async fn wait_for_edge(&mut self, edge: Edge) {
+        self.wait(edge, None).await
+    }
+
+    /// Waits until the pin is high; returns immediately if the pin is already high
+    pub async fn wait_for_high(&mut self) {
+        self.wait(Edge::Rising, Some(true)).await
+    }
+
+    /// Waits until the pin is low; returns immediately if the pin is already low
+    pub async fn wait_for_low(&mut self) {
+        self.wait(Edge::Falling, Some(false)).await
+    }
+
+    /// Measures the width of the next pulse at `level`, e.g. the next high pulse (rising edge to
+    /// falling edge) when `level` is `PinState::High`
+    ///
+    /// If a pulse is in progress when this is called it's skipped since its start was missed.
+    /// Both edges are timestamped in hardware (GPIOTE -> PPI -> TIMER1 capture) so the measurement
+    /// is not affected by interrupt or scheduling latency; the resolution is 1 us. Returns
+    /// `Err(TimedOut)` if the pulse hasn't ended within `timeout` (see `Timer::timeout_at`)
+    ///
+    /// # Panics
+    ///
+    /// This function panics if another pulse measurement is in progress. This uses TIMER1 and PPI
+    /// channel 0, which must not be used for anything else
+    pub async fn measure_pulse(
+        &mut self,
+        timer: &mut Timer,
+        level: PinState,
+        timeout: Duration,
+    ) -> Result<Duration, TimedOut> {
+        let deadline = Timer::now() + timeout;
+        let high = level == PinState::High;
+
+        loop {
+            // wait for the pulse in progress, if any, to end
+            if high {
+                timer.timeout_at(deadline, self.wait_for_low()).await?;
+            } else {
+                timer.timeout_at(deadline, self.wait_for_high()).await?;
+            }
+
+            let capture = Capture::start(self);
+
+            // NOTE the level is checked *after* the capture has been armed; if the pulse started
+            // in between, its leading edge was missed so we try again with the next pulse
+            if capture.pin.is_high() != high {
+                timer.timeout_at(deadline, capture).await?;
+
+                // NOTE(borrow_unchecked) single-instruction read; the capture has completed
+                let micros = TIMER1::borrow_unchecked(|timer| timer.cc[0].read().bits());
+                return Ok(Duration::from_micros(micros.into()));
+            }
+        }
+    }
+
+    async fn wait(&mut self, edge: Edge, level: Option<bool>) {
+        struct Wait<'a> {
+            _pin: &'a mut InputPin,
+            channel: usize,
+            installed_waker: bool,
+        }
+
+        impl<'a> Future for Wait<'a> {
+            type Output = ();
+
+            fn poll(mut self: PinRef<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                let channel = self.channel;
+
+                if has_fired(channel) {
+                    if self.installed_waker {
+                        uninstall_waker(channel);
+                        self.installed_waker = false;
+                    }
+
+                    Poll::Ready(())
+                } else {
+                    if !self.installed_waker {
+                        unsafe {
+                            WAKERS[channel] = Some(cx.waker().clone());
+                        }
+                        self.installed_waker = true;
+                    }
+
+                    // NOTE(compiler_fence) `WAKERS` write must complete before we enable the
+                    // interrupt
+                    atomic::compiler_fence(Ordering::Release);
+                    // (re-)arm the one-shot interrupt
+                    GPIOTE::borrow_unchecked(|gpiote| {
+                        gpiote.intenset.write(|w| unsafe { w.bits(1 << channel) })
+                    });
+
+                    Poll::Pending
+                }
+            }
+        }
+
+        impl Drop for Wait<'_> {
+            fn drop(&mut self) {
+                if self.installed_waker {
+                    uninstall_waker(self.channel);
+                }
+            }
+        }
+
+        let polarity = match edge {
+            Edge::Rising => 1,
+            Edge::Falling => 2,
+            Edge::Any => 3,
+        };
+        let channel = self.channel;
+        GPIOTE::borrow_unchecked(|gpiote| {
+            gpiote.config[channel].write(|w| unsafe { w.bits(config(self.pin, polarity)) });
+            // NOTE changing the configuration can itself generate an event; discard it and any
+            // event that happened before this call
+            gpiote.events_in[channel].reset();
+        });
+
+        // NOTE the level is checked *after* the event has been armed so that a transition that
+        // happens between the check and the `await` is not missed
+        if let Some(level) = level {
+            if self.is_high() == level {
+                return;
+            }
+        }
+
+        Wait {
+            _pin: self,
+            channel,
+            installed_waker: false,
+        }
+        .await
+    }
+}
+
+// static configuration of the pulse capture (see `InputPin::measure_pulse`)
+const PPI_CHANNEL: usize = 0;
+
+// a pulse measurement is in progress; TIMER1 and PPI channel 0 are in use
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+// Resolves once both edges of a pulse have been captured; tears down the capture when dropped
+//
+// Both edges (GPIOTE "toggle" events) go through a PPI channel to TIMER1's CAPTURE[0] task, with
+// a fork to its START task. The first edge captures the counter while the timer is stopped (0)
+// and starts the timer; the second edge captures the width of the pulse
+struct Capture<'a> {
+    pin: &'a mut InputPin,
+    installed_waker: bool,
+}
+
+impl<'a> Capture<'a> {
+    fn start(pin: &'a mut InputPin) -> Self {
+        if CAPTURING.swap(true, Ordering::Acquire) {
+            panic!("a pulse measurement is already in progress")
+        }
+
+        let channel = pin.channel;
+        TIMER1::borrow_unchecked(|timer| {
+            timer.tasks_stop.write(|w| unsafe { w.bits(1) });
+            timer.tasks_clear.write(|w| unsafe { w.bits(1) });
+            // MODE = timer, BITMODE = 32-bit, PRESCALER = 4 (16 MHz / 2^4 = 1 MHz)
+            timer.mode.write(|w| unsafe { w.bits(0) });
+            timer.bitmode.write(|w| unsafe { w.bits(3) });
+            timer.prescaler.write(|w| unsafe { w.bits(4) });
+            timer.cc[0].write(|w| unsafe { w.bits(0) });
+
+            GPIOTE::borrow_unchecked(|gpiote| {
+                PPI::borrow_unchecked(|ppi| {
+                    let ch = &ppi.ch[PPI_CHANNEL];
+                    ch.eep.write(|w| unsafe {
+                        w.bits(&gpiote.events_in[channel] as *const _ as u32)
+                    });
+                    ch.tep
+                        .write(|w| unsafe { w.bits(&timer.tasks_capture[0] as *const _ as u32) });
+                    ppi.fork[PPI_CHANNEL]
+                        .tep
+                        .write(|w| unsafe { w.bits(&timer.tasks_start as *const _ as u32) });
+                    ppi.chenset.write(|w| unsafe { w.bits(1 << PPI_CHANNEL) });
+                });
+
+                // POLARITY = toggle
+                gpiote.config[channel].write(|w| unsafe { w.bits(config(pin.pin, 3)) });
+                // NOTE changing the configuration can itself generate an event; discard it
+                gpiote.events_in[channel].reset();
+            });
+        });
+
+        Self {
+            pin,
+            installed_waker: false,
+        }
+    }
+}
+
+impl Future for Capture<'_> {
+    type Output = ();
+
+    fn poll(mut self: PinRef<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let channel = self.pin.channel;
+
+        // NOTE the event is cleared *before* the capture register is checked so that an edge
+        // that happens in between is not missed
+        GPIOTE::borrow_unchecked(|gpiote| gpiote.events_in[channel].reset());
+
+        if TIMER1::borrow_unchecked(|timer| timer.cc[0].read().bits()) != 0 {
+            if self.installed_waker {
+                uninstall_waker(channel);
+                self.installed_waker = false;
+            }
+
+            Poll::Ready(())
+        } else {
+            if !self.installed_waker {
+                unsafe {
+                    WAKERS[channel] = Some(cx.waker().clone());
+                }
+                self.installed_waker = true;
+            }
+
+            // NOTE(compiler_fence) `WAKERS` write must complete before we enable the interrupt
+            atomic::compiler_fence(Ordering::Release);
+            // (re-)arm the one-shot interrupt
+            GPIOTE::borrow_unchecked(|gpiote| {
+                gpiote.intenset.write(|w| unsafe { w.bits(1 << channel) })
+            });
+
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Capture<'_> {
+    fn drop(&mut self) {
+        let channel = self.pin.channel;
+        if self.installed_waker {
+            uninstall_waker(channel);
+        }
+
+        PPI::borrow_unchecked(|ppi| ppi.chenclr.write(|w| unsafe { w.bits(1 << PPI_CHANNEL) }));
+        TIMER1::borrow_unchecked(|timer| timer.tasks_stop.write(|w| unsafe { w.bits(1) }));
+        GPIOTE::borrow_unchecked(|gpiote| {
+            gpiote.config[channel].write(|w| unsafe { w.bits(config(self.pin.pin, 0)) });
+            gpiote.events_in[channel].reset();
+        });
+
+        CAPTURING.store(false, Ordering::Release);
+    }
+}
+
+impl Drop for InputPin {
+    fn drop(&mut self) {
+        let channel = self.channel;
+        GPIOTE::borrow_unchecked(|gpiote| {
+            gpiote.intenclr.write(|w| unsafe { w.bits(1 << channel) });
+            gpiote.config[channel].reset();
+            gpiote.events_in[channel].reset();
+        });
+        CHANNELS.fetch_and(!(1 << channel), Ordering::Release);
+    }
+}
+
+// NOTE(unsafe) each element is only accessed by the `InputPin` that owns the channel and by the
+// `GPIOTE` handler; the handler only accesses the elements whose channel interrupt is enabled
+static mut WAKERS: [Option<Waker>; NCHANNELS] = [None, None, None, None, None, None, None, None];
+
+#[allow(non_snake_case)]
+#[no_mangle]
+fn GPIOTE() {
+    GPIOTE::borrow_unchecked(|gpiote| {
+        let enabled = gpiote.intenset.read().bits();
+        for channel in 0..NCHANNELS {
+            if enabled & (1 << channel) != 0 && gpiote.events_in[channel].read().bits() != 0 {
+                // one shot interrupt -- leave the event set; the task will clear it
+                gpiote.intenclr.write(|w| unsafe { w.bits(1 << channel) });
+
+                if let Some(waker) = unsafe { WAKERS[channel].as_ref() } {
+                    waker.wake_by_ref();
+                }
+            }
+        }
+    })
+}
+
+fn alloc_channel() -> Option<usize> {
+    let mut used = CHANNELS.load(Ordering::Relaxed);
+    loop {
+        let channel = (!used).trailing_zeros() as usize;
+        if channel >= NCHANNELS {
+            return None;
+        }
+
+        match CHANNELS.compare_exchange_weak(
+            used,
+            used | (1 << channel),
+            Ordering::Acquire,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => return Some(channel),
+            Err(current) => used = current,
+        }
+    }
+}
+
+// GPIOTE.CONFIG value in event mode
+fn config(pin: Pin, polarity: u32) -> u32 {
+    // MODE = event | PSEL | PORT | POLARITY
+    1 | u32::from(pin.pin) << 8 | u32::from(pin.psel_port()) << 13 | polarity << 16
+}
+
+fn has_fired(channel: usize) -> bool {
+    GPIOTE::borrow_unchecked(|gpiote| {
+        if gpiote.events_in[channel].read().bits() != 0 {
+            gpiote.events_in[channel].reset();
+            true
+        } else {
+            false
+        }
+    })
+}
+
+fn uninstall_waker(channel: usize) {
+    GPIOTE::borrow_unchecked(|gpiote| gpiote.intenclr.write(|w| unsafe { w.bits(1 << channel) }));
+    // NOTE(compiler_fence) the interrupt must be disabled before we take down the waker
+    atomic::compiler_fence(Ordering::SeqCst);
+    drop(unsafe { WAKERS[channel].take() });
+}
+
+fn port(pin: Pin) -> Port {
+    if pin.port == 0 {
+        Port::P0
+    } else {
+        Port::P1
+    }
+}
+
+/// Computes the (`set`, `clear`) masks that drive `pins` to `value`
+///
+/// Bit `i` of `value` is written to pin `pins[i]`; the masks can then be passed to
+/// `Port::write_mask`
+pub fn masks(pins: &[u8], value: u32) -> (u32, u32) {
+    let mut set = 0;
+    let mut clear = 0;
+    for (i, pin) in pins.iter().enumerate() {
+        if value & (1 << i) != 0 {
+            set |= 1 << pin;
+        } else {
+            clear |= 1 << pin;
+        }
+    }
+    (set, clear)
+}
+
+// number of ports and number of pins of port 1
+#[cfg(feature = "nrf52840")]
+const PORTS: u8 = 2;
+#[cfg(feature = "nrf52840")]
+const P1_PINS: u8 = 16;
+#[cfg(feature = "nrf52833")]
+const PORTS: u8 = 2;
+#[cfg(feature = "nrf52833")]
+const P1_PINS: u8 = 10;
+#[cfg(feature = "nrf52832")]
+const PORTS: u8 = 1;
+#[cfg(feature = "nrf52832")]
+const P1_PINS: u8 = 0;
+
+const fn validate(port: u8, pin: u8) -> Option<Error> {
+    if port >= PORTS {
+        Some(Error::NoSuchPort)
+    } else if (port == 0 && pin > 31) || (port == 1 && pin >= P1_PINS) {
+        Some(Error::NoSuchPin)
+    } else if port == 0 && pin < 2 {
+        Some(Error::Xtal)
+    } else {
+        None
+    }
+}
+
+/// Validates a pin assignment at compile time
+///
+/// `pin!(0, 20)` evaluates to P0.20. An invalid pin assignment, e.g. `pin!(1, 16)`, is rejected
+/// with an "attempt to compute `0_usize - 1_usize`, which would overflow" error
+///
+/// See [`gpio::Error`](gpio/enum.Error.html) for the list of rejected assignments
+#[macro_export]
+macro_rules! pin {
+    ($port:expr, $pin:expr) => {{
+        const _: [(); 0 - !$crate::gpio::Pin::is_valid($port, $pin) as usize] = [];
+        $crate::gpio::Pin::new_unchecked($port, $pin)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Pin};
+
+    #[test]
+    fn pin_validation() {
+        let p0_20 = Pin::new(0, 20).unwrap();
+        assert_eq!((p0_20.port(), p0_20.pin()), (0, 20));
+
+        // port 0 only has 32 pins
+        assert!(Pin::new(0, 31).is_ok());
+        assert_eq!(Pin::new(0, 32), Err(Error::NoSuchPin));
+        assert_eq!(Pin::new(0, 255), Err(Error::NoSuchPin));
+
+        // no chip has a third port
+        assert_eq!(Pin::new(2, 0), Err(Error::NoSuchPort));
+
+        // the LFCLK crystal pins
+        assert_eq!(Pin::new(0, 0), Err(Error::Xtal));
+        assert_eq!(Pin::new(0, 1), Err(Error::Xtal));
+
+        // `pin!` rejects the same assignments
+        assert!(Pin::is_valid(0, 20));
+        assert!(!Pin::is_valid(0, 32));
+        assert!(!Pin::is_valid(2, 0));
+        assert!(!Pin::is_valid(0, 1));
+    }
+
+    #[test]
+    fn masks() {
+        // the bus of example 11
+        let pins = [10, 11, 12, 13];
+
+        assert_eq!(super::masks(&pins, 0b0000), (0, 0b1111 << 10));
+        assert_eq!(super::masks(&pins, 0b1111), (0b1111 << 10, 0));
+        assert_eq!(super::masks(&pins, 0b0101), (0b0101 << 10, 0b1010 << 10));
+
+        // bits above the width of the bus are ignored
+        assert_eq!(super::masks(&pins, 0b1_0110), super::masks(&pins, 0b0110));
+
+        // the pins don't need to be contiguous nor in order
+        assert_eq!(
+            super::masks(&[31, 0, 7], 0b011),
+            ((1 << 31) | (1 << 0), 1 << 7)
+        );
+
+        // every pin of the bus is either set or cleared, never both
+        for value in 0..16 {
+            let (set, clear) = super::masks(&pins, value);
+            assert_eq!(set & clear, 0);
+            assert_eq!(set | clear, 0b1111 << 10);
+        }
+
+        // no pins, no masks
+        assert_eq!(super::masks(&[], 0xFFFF_FFFF), (0, 0));
+    }
+
+    // NOTE the ports of the chip selected by the Cargo features
+    #[test]
+    fn port1() {
+        #[cfg(feature = "nrf52840")]
+        {
+            assert!(Pin::new(1, 15).is_ok());
+            assert_eq!(Pin::new(1, 16), Err(Error::NoSuchPin));
+        }
+        #[cfg(feature = "nrf52833")]
+        {
+            assert!(Pin::new(1, 9).is_ok());
+            assert_eq!(Pin::new(1, 10), Err(Error::NoSuchPin));
+        }
+        #[cfg(feature = "nrf52832")]
+        assert_eq!(Pin::new(1, 0), Err(Error::NoSuchPort));
+    }
+}
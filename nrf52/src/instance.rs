@@ -0,0 +1,68 @@
+//! Peripheral instances shared by several drivers
+//!
+//! The SPIM, SPIS, TWIM and TWIS peripherals with the same ID are a single SERIAL instance: they
+//! share their registers and their interrupt. Only one protocol per SERIAL instance can be active;
+//! a driver claims its instance when it's taken and taking a second driver on the same instance
+//! fails with `InUse`
+//!
+//! | Instance  | Drivers                                              |
+//! |-----------|------------------------------------------------------|
+//! | `Serial0` | `twim::Twim` (the default after reset), `spim::Spim` |
+//! | `Serial1` | `spim::Spim`                                         |
+//! | `Serial2` | `spim::Spim` (default)                               |
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use pac::Interrupt;
+
+/// A SERIAL instance
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Serial {
+    /// SPIM0 / TWIM0
+    Serial0,
+    /// SPIM1 / TWIM1
+    Serial1,
+    /// SPIM2
+    Serial2,
+}
+
+impl Serial {
+    pub(crate) fn interrupt(self) -> Interrupt {
+        match self {
+            Serial::Serial0 => Interrupt::SPIM0_SPIS0_TWIM0_TWIS0_SPI0_TWI0,
+            Serial::Serial1 => Interrupt::SPIM1_SPIS1_TWIM1_TWIS1_SPI1_TWI1,
+            Serial::Serial2 => Interrupt::SPIM2_SPIS2_SPI2,
+        }
+    }
+}
+
+/// The SERIAL instance is already in use by another driver
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InUse;
+
+/// Driver that owns a SERIAL instance
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Owner {
+    Twim = 1,
+    Spim = 2,
+}
+
+// 0 = free; otherwise an `Owner`
+static OWNERS: [AtomicU8; 3] = [AtomicU8::new(0), AtomicU8::new(0), AtomicU8::new(0)];
+
+/// Claims the `serial` instance for the `owner` driver
+pub(crate) fn claim(serial: Serial, owner: Owner) -> Result<(), InUse> {
+    OWNERS[serial as usize]
+        .compare_exchange(0, owner as u8, Ordering::Relaxed, Ordering::Relaxed)
+        .map(drop)
+        .map_err(|_| InUse)
+}
+
+/// Returns the driver that owns the `serial` instance, if any
+pub(crate) fn owner(serial: Serial) -> Option<Owner> {
+    match OWNERS[serial as usize].load(Ordering::Relaxed) {
+        1 => Some(Owner::Twim),
+        2 => Some(Owner::Spim),
+        _ => None,
+    }
+}
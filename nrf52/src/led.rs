@@ -3,9 +3,24 @@
 // NOTE(borrow_unchecked) all writes are single-instruction, atomic operations
 // on a stateless register
 
+use core::time::Duration;
+
 use pac::P0;
 
-use crate::BorrowUnchecked as _;
+use crate::{timer::Timer, BorrowUnchecked as _};
+
+/// "SOS" in Morse code, one element per time unit (`true` = LED on)
+///
+/// dot = 1 unit on; dash = 3 units on; 1 unit off between elements, 3 units between letters and
+/// 7 units between repetitions
+pub const SOS: &[bool] = &[
+    // S
+    true, false, true, false, true, false, false, false, //
+    // O
+    true, true, true, false, true, true, true, false, true, true, true, false, false, false, //
+    // S
+    true, false, true, false, true, false, false, false, false, false, false, false,
+];
 
 // NOTE called from `pre_init`
 pub(crate) fn init() {
@@ -64,3 +79,24 @@ impl Blue {
         P0::borrow_unchecked(|p0| p0.outclr.write(|w| w.pin15().set_bit()))
     }
 }
+
+/// Blinks the red LED forever following `pattern`, one element every `period`
+///
+/// Meant to be the last thing a task does after hitting an unrecoverable error, e.g.
+/// `led::panic_blink(&mut timer, led::SOS, Duration::from_millis(200)).await`. The other tasks
+/// keep running
+pub async fn panic_blink(timer: &mut Timer, pattern: &[bool], period: Duration) -> ! {
+    // an empty pattern would never yield and starve all the other tasks
+    assert!(!pattern.is_empty());
+
+    loop {
+        for on in pattern {
+            if *on {
+                Red.on();
+            } else {
+                Red.off();
+            }
+            timer.wait(period).await;
+        }
+    }
+}
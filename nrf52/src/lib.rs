@@ -1,19 +1,57 @@
 //! Asynchronous HAL for the nRF52840
+//!
+//! The nRF52833 and nRF52832 are also supported through the `nrf52833` and `nrf52832` features
+//! (`nrf52840` is the default); see the README for the differences between the chips
 
 #![deny(missing_docs)]
 #![deny(rust_2018_idioms)]
 #![deny(warnings)]
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 use core::{marker::PhantomData, mem};
 
 use cortex_m_rt::pre_init;
 
+#[cfg(not(any(feature = "nrf52840", feature = "nrf52833", feature = "nrf52832")))]
+compile_error!("no chip selected; enable one of the `nrf52840`, `nrf52833` or `nrf52832` features");
+
+#[cfg(any(
+    all(feature = "nrf52840", feature = "nrf52833"),
+    all(feature = "nrf52840", feature = "nrf52832"),
+    all(feature = "nrf52833", feature = "nrf52832"),
+))]
+compile_error!("more than one chip selected; disable the default features to deselect `nrf52840`");
+
+// records an event in the trace buffer when the "trace" feature is enabled
+macro_rules! trace {
+    ($id:expr) => {
+        #[cfg(feature = "trace")]
+        crate::trace::record($id);
+    };
+}
+
+pub mod clock;
+pub mod drift;
 pub mod ds3231;
+pub mod eeprom;
+pub mod filter;
+pub mod gpio;
+pub mod instance;
 pub mod led;
+#[cfg(feature = "panic-led")]
+mod panic_led;
+pub mod power;
+#[cfg(feature = "nrf52840")]
+pub mod qspi;
+pub mod saadc;
 pub mod scd30;
+pub mod sensirion;
 pub mod serial;
+pub mod spim;
 pub mod timer;
+pub mod token;
+#[cfg(feature = "trace")]
+pub mod trace;
 pub mod twim;
 
 pub use timer::Timer;
@@ -47,6 +85,13 @@ unsafe fn pre_init() {
     // TWIM
     twim::init();
 
+    // SAADC
+    saadc::init();
+
+    // QSPI
+    #[cfg(feature = "nrf52840")]
+    qspi::init();
+
     // start the RTC
     timer::init();
 
@@ -73,7 +118,9 @@ macro_rules! borrow_unchecked {
     }
 }
 
-borrow_unchecked!(CLOCK, P0, RTC0, TWIM0, UARTE0);
+borrow_unchecked!(
+    CLOCK, GPIOTE, P0, P1, PPI, QSPI, RTC0, SAADC, SPIM0, SPIM1, SPIM2, TIMER1, TWIM0, UARTE0, UICR
+);
 
 struct NotSync {
     _inner: PhantomData<*mut ()>,
@@ -89,13 +136,47 @@ impl NotSync {
 
 unsafe impl Send for NotSync {}
 
-fn slice_in_ram(slice: &[u8]) -> bool {
-    const RAM_START: usize = 0x2000_0000;
-    const RAM_SIZE: usize = 128 * 1024;
+// NOTE must match the memory map generated by `build.rs`
+const RAM_START: usize = 0x2000_0000;
+#[cfg(feature = "nrf52840")]
+const RAM_SIZE: usize = 256 * 1024;
+#[cfg(feature = "nrf52833")]
+const RAM_SIZE: usize = 128 * 1024;
+#[cfg(feature = "nrf52832")]
+const RAM_SIZE: usize = 64 * 1024;
+
+/// Returns `true` if `slice` lies in the RAM of the target chip
+///
+/// EasyDMA can only access RAM; the drivers copy slices that are not in RAM (e.g. in Flash) into
+/// a buffer on the stack before handing them to the DMA
+pub fn slice_in_ram(slice: &[u8]) -> bool {
+    in_ram(slice.as_ptr() as usize, slice.len())
+}
+
+// whether the `len` bytes that start at address `start` lie in RAM
+fn in_ram(start: usize, len: usize) -> bool {
     const RAM_END: usize = RAM_START + RAM_SIZE;
 
-    let start = slice.as_ptr() as usize;
-    let end = start + slice.len();
+    RAM_START <= start && start + len <= RAM_END
+}
 
-    RAM_START <= start && end < RAM_END
+#[cfg(test)]
+mod tests {
+    use super::{RAM_SIZE, RAM_START};
+
+    // NOTE the memory map of the chip selected by the Cargo features
+    #[test]
+    fn in_ram() {
+        let end = RAM_START + RAM_SIZE;
+        assert!(super::in_ram(RAM_START, RAM_SIZE));
+        assert!(super::in_ram(end - 4, 4));
+        // crosses the end of RAM
+        assert!(!super::in_ram(end - 4, 8));
+        // past the end of RAM of this chip; RAM on a bigger one
+        assert!(!super::in_ram(end, 4));
+        // right before the start of RAM
+        assert!(!super::in_ram(RAM_START - 4, 4));
+        // Flash
+        assert!(!super::in_ram(0x0000_1000, 4));
+    }
 }
@@ -0,0 +1,43 @@
+//! Panic handler that signals the crash on the red LED
+//!
+//! Enabled with the `panic-led` feature. The handler turns the red LED on, fast-blinks it a few
+//! times and then traps with `UDF`, leaving the LED on
+//!
+//! NOTE the handler doesn't trust any software state (the executor, the timers or the `led`
+//! module): it disables the interrupts and drives the pin with raw register writes. The blinking
+//! is timed with busy loops so it assumes the core runs at 64 MHz. A panic that happens while the
+//! panic handler itself runs, or a fault that's not a panic (e.g. a stack overflow that ends in
+//! the `HardFault` handler), is not signaled
+
+use core::panic::PanicInfo;
+
+use cortex_m::{asm, interrupt};
+use pac::P0;
+
+use crate::BorrowUnchecked as _;
+
+// ~100 ms at 64 MHz
+const HALF_PERIOD: u32 = 6_400_000;
+const BLINKS: u32 = 10;
+
+#[panic_handler]
+fn panic(_: &PanicInfo<'_>) -> ! {
+    interrupt::disable();
+
+    // NOTE(borrow_unchecked) nothing else runs from now on
+    P0::borrow_unchecked(|p0| {
+        // the red LED (P0.13) is active low; (re)configure the pin as an output in case the panic
+        // happened before `init` or something else changed it
+        p0.outclr.write(|w| w.pin13().set_bit());
+        p0.dirset.write(|w| w.pin13().set_bit());
+
+        for _ in 0..BLINKS {
+            asm::delay(HALF_PERIOD);
+            p0.outset.write(|w| w.pin13().set_bit());
+            asm::delay(HALF_PERIOD);
+            p0.outclr.write(|w| w.pin13().set_bit());
+        }
+    });
+
+    asm::udf()
+}
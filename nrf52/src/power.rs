@@ -0,0 +1,69 @@
+//! Power management
+//!
+//! An enabled peripheral keeps its clock sources requested even when it's not transferring any
+//! data; for the UARTE and TWIM that means the HFCLK stays on while the core sleeps (`WFE`). This
+//! adds hundreds of microamps to the idle current (see the "Current consumption" section of the
+//! nRF52840 Product Specification). `LowPower` disables those peripherals when the executor goes
+//! idle and they have no transfer in flight; the drivers re-enable them at the start of the next
+//! transfer
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use async_embedded::task;
+
+use crate::{serial, twim};
+
+const UARTE: u8 = 1 << 0;
+const TWIM: u8 = 1 << 1;
+
+// peripherals managed by the idle hook
+static MANAGED: AtomicU8 = AtomicU8::new(0);
+
+/// Low power configuration
+///
+/// # Example
+///
+/// ``` ignore
+/// LowPower { uarte: true, twim: true }.register();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct LowPower {
+    /// Disable the UARTE while no serial transfer is in progress
+    ///
+    /// NOTE a pending `Rx::read` counts as a transfer in progress; while the UARTE is disabled
+    /// incoming bytes are lost
+    pub uarte: bool,
+
+    /// Disable the TWIM while no I2C transfer is in progress
+    pub twim: bool,
+}
+
+impl LowPower {
+    /// Registers this configuration as the executor's idle hook
+    ///
+    /// This replaces any previously registered idle hook
+    pub fn register(self) {
+        let mut managed = 0;
+        if self.uarte {
+            managed |= UARTE;
+        }
+        if self.twim {
+            managed |= TWIM;
+        }
+        MANAGED.store(managed, Ordering::Relaxed);
+
+        task::set_idle_hook(idle);
+    }
+}
+
+fn idle() {
+    let managed = MANAGED.load(Ordering::Relaxed);
+
+    if managed & UARTE != 0 && serial::is_idle() {
+        serial::disable();
+    }
+
+    if managed & TWIM != 0 && twim::is_idle() {
+        twim::disable();
+    }
+}
@@ -0,0 +1,580 @@
+//! Quad SPI interface to the external flash memory
+//!
+//! The default configuration matches the MX25R6435F (8 MiB) found on the nRF52840-DK
+
+// Reference: nRF52840 Product Specification v1.1, section 6.19 (QSPI)
+
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{self, AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use cortex_m::peripheral::NVIC;
+use pac::{Interrupt, QSPI};
+
+use crate::{BorrowUnchecked as _, NotSync};
+
+/// Size of the external flash in bytes
+pub const CAPACITY: usize = 8 * 1024 * 1024;
+
+/// Size of the smallest erasable region in bytes
+pub const SECTOR_SIZE: usize = 4 * 1024;
+
+// NOTE called from `pre_init`
+pub(crate) fn init() {
+    const SCK_PIN: u8 = 19;
+    const CSN_PIN: u8 = 17;
+    const IO_PINS: [u8; 4] = [20, 21, 22, 23];
+    const QSPI_PORT: bool = false; // 0
+
+    pac::P0::borrow_unchecked(|p0| {
+        for pin in [SCK_PIN, CSN_PIN].iter().chain(IO_PINS.iter()) {
+            p0.pin_cnf[*pin as usize].write(|w| w.drive().h0h1());
+        }
+    });
+
+    QSPI::borrow_unchecked(|qspi| {
+        qspi.psel.sck.write(|w| unsafe {
+            w.pin()
+                .bits(SCK_PIN)
+                .port()
+                .bit(QSPI_PORT)
+                .connect()
+                .connected()
+        });
+        qspi.psel.csn.write(|w| unsafe {
+            w.pin()
+                .bits(CSN_PIN)
+                .port()
+                .bit(QSPI_PORT)
+                .connect()
+                .connected()
+        });
+        qspi.psel.io0.write(|w| unsafe {
+            w.pin()
+                .bits(IO_PINS[0])
+                .port()
+                .bit(QSPI_PORT)
+                .connect()
+                .connected()
+        });
+        qspi.psel.io1.write(|w| unsafe {
+            w.pin()
+                .bits(IO_PINS[1])
+                .port()
+                .bit(QSPI_PORT)
+                .connect()
+                .connected()
+        });
+        qspi.psel.io2.write(|w| unsafe {
+            w.pin()
+                .bits(IO_PINS[2])
+                .port()
+                .bit(QSPI_PORT)
+                .connect()
+                .connected()
+        });
+        qspi.psel.io3.write(|w| unsafe {
+            w.pin()
+                .bits(IO_PINS[3])
+                .port()
+                .bit(QSPI_PORT)
+                .connect()
+                .connected()
+        });
+
+        // NOTE single-line opcodes (FASTREAD, PP) don't require the Quad Enable bit of the flash
+        // status register to be set
+        // READOC = FASTREAD, WRITEOC = PP, ADDRMODE = 24-bit, DPMENABLE = 0, PPSIZE = 256 bytes
+        qspi.ifconfig0.write(|w| unsafe { w.bits(0) });
+
+        // SCKDELAY = 1 (62.5 ns), SPIMODE = MODE0, SCKFREQ = 1 (32 MHz / (1 + 1) = 16 MHz)
+        qspi.ifconfig1
+            .write(|w| unsafe { w.bits(1 << 28 | 1) });
+
+        qspi.enable.write(|w| w.enable().enabled());
+
+        // activate the interface; this is a one-time operation that only takes a few microseconds
+        qspi.tasks_activate.write(|w| unsafe { w.bits(1) });
+        while qspi.events_ready.read().bits() == 0 {
+            // busy wait
+            continue;
+        }
+        qspi.events_ready.reset();
+
+        qspi.intenset.write(|w| w.ready().set_bit());
+    });
+}
+
+/// [singleton] An `async`-aware interface to the external flash
+pub struct Qspi {
+    _not_sync: NotSync,
+}
+
+/// Erasable regions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Erase {
+    /// 4 KiB sector
+    Sector,
+
+    /// 64 KiB block
+    Block,
+
+    /// The whole chip; this can take more than a minute
+    Chip,
+}
+
+impl Erase {
+    fn len(self) -> usize {
+        match self {
+            Erase::Sector => SECTOR_SIZE,
+            Erase::Block => 64 * 1024,
+            Erase::Chip => CAPACITY,
+        }
+    }
+
+    // value of the `ERASE.LEN` register
+    fn bits(self) -> u32 {
+        match self {
+            Erase::Sector => 0,
+            Erase::Block => 1,
+            Erase::Chip => 2,
+        }
+    }
+}
+
+const INTERRUPT: Interrupt = Interrupt::QSPI;
+
+// EasyDMA transfers must be word aligned
+const ALIGN: usize = 4;
+// size of the bounce buffer used for buffers that are not word-aligned or not in RAM
+const BUFSZ: usize = 256;
+
+#[repr(align(4))]
+struct Aligned([u8; BUFSZ]);
+
+impl Qspi {
+    /// Takes the singleton instance of the QSPI interface
+    ///
+    /// This returns the `Some` variant only once
+    pub fn take() -> Self {
+        // NOTE peripheral initialization is done in `#[pre_init]`
+
+        static TAKEN: AtomicBool = AtomicBool::new(false);
+
+        if TAKEN
+            .compare_exchange_weak(false, true, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            Self {
+                _not_sync: NotSync::new(),
+            }
+        } else {
+            panic!("`Qspi` has already been taken")
+        }
+    }
+
+    /// Reads the JEDEC ID (manufacturer ID, memory type, memory density) of the flash
+    pub async fn jedec_id(&mut self) -> [u8; 3] {
+        // READ IDENTIFICATION
+        const RDID: u32 = 0x9f;
+        // opcode + 3 bytes of data
+        const LENGTH: u32 = 4;
+
+        Op {
+            _qspi: self,
+            start: Some(|qspi: &QSPI| {
+                // keep IO2 (WP#) and IO3 (HOLD#) high during the transfer
+                const LIO2: u32 = 1 << 12;
+                const LIO3: u32 = 1 << 13;
+
+                // writing this register starts the custom instruction
+                qspi.cinstrconf
+                    .write(|w| unsafe { w.bits(RDID | LENGTH << 8 | LIO2 | LIO3) });
+            }),
+            state: State::NotStarted,
+        }
+        .await;
+
+        let dat = QSPI::borrow_unchecked(|qspi| qspi.cinstrdat0.read().bits());
+        [dat as u8, (dat >> 8) as u8, (dat >> 16) as u8]
+    }
+
+    /// Fills `buf` with the contents of the flash starting at `address`
+    ///
+    /// Both `address` and the length of `buf` must be a multiple of 4
+    pub async fn read(&mut self, address: usize, buf: &mut [u8]) -> Result<(), Error> {
+        check(address, buf.len())?;
+
+        if is_aligned(buf) {
+            self.read_aligned(address, buf).await;
+        } else {
+            let mut bounce = Aligned([0; BUFSZ]);
+            for (i, chunk) in buf.chunks_mut(BUFSZ).enumerate() {
+                let n = chunk.len();
+                self.read_aligned(address + i * BUFSZ, &mut bounce.0[..n])
+                    .await;
+                chunk.copy_from_slice(&bounce.0[..n]);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Programs `bytes` into the flash starting at `address`
+    ///
+    /// Both `address` and the length of `bytes` must be a multiple of 4. The target region must
+    /// have been erased beforehand
+    pub async fn write(&mut self, address: usize, bytes: &[u8]) -> Result<(), Error> {
+        check(address, bytes.len())?;
+
+        if is_aligned(bytes) && crate::slice_in_ram(bytes) {
+            self.write_aligned(address, bytes).await;
+        } else {
+            let mut bounce = Aligned([0; BUFSZ]);
+            for (i, chunk) in bytes.chunks(BUFSZ).enumerate() {
+                let n = chunk.len();
+                bounce.0[..n].copy_from_slice(chunk);
+                self.write_aligned(address + i * BUFSZ, &bounce.0[..n])
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Erases (sets to `0xff`) the region that starts at `address`
+    ///
+    /// `address` must be aligned to the size of the erased region
+    pub async fn erase(&mut self, address: usize, region: Erase) -> Result<(), Error> {
+        if address % region.len() != 0 {
+            return Err(Error::Alignment);
+        }
+
+        if address >= CAPACITY {
+            return Err(Error::OutOfBounds);
+        }
+
+        Op {
+            _qspi: self,
+            start: Some(move |qspi: &QSPI| {
+                qspi.erase.ptr.write(|w| unsafe { w.bits(address as u32) });
+                qspi.erase.len.write(|w| unsafe { w.bits(region.bits()) });
+                qspi.tasks_erasestart.write(|w| unsafe { w.bits(1) });
+            }),
+            state: State::NotStarted,
+        }
+        .await;
+
+        Ok(())
+    }
+
+    // NOTE `buf` is word aligned
+    async fn read_aligned(&mut self, address: usize, buf: &mut [u8]) {
+        let ptr = buf.as_mut_ptr() as u32;
+        let len = buf.len() as u32;
+
+        Op {
+            _qspi: self,
+            start: Some(move |qspi: &QSPI| {
+                qspi.read.src.write(|w| unsafe { w.bits(address as u32) });
+                qspi.read.dst.write(|w| unsafe { w.bits(ptr) });
+                qspi.read.cnt.write(|w| unsafe { w.bits(len) });
+                qspi.tasks_readstart.write(|w| unsafe { w.bits(1) });
+            }),
+            state: State::NotStarted,
+        }
+        .await
+    }
+
+    // NOTE `bytes` is word aligned and points into RAM
+    async fn write_aligned(&mut self, address: usize, bytes: &[u8]) {
+        let ptr = bytes.as_ptr() as u32;
+        let len = bytes.len() as u32;
+
+        Op {
+            _qspi: self,
+            start: Some(move |qspi: &QSPI| {
+                qspi.write.dst.write(|w| unsafe { w.bits(address as u32) });
+                qspi.write.src.write(|w| unsafe { w.bits(ptr) });
+                qspi.write.cnt.write(|w| unsafe { w.bits(len) });
+                qspi.tasks_writestart.write(|w| unsafe { w.bits(1) });
+            }),
+            state: State::NotStarted,
+        }
+        .await
+    }
+}
+
+/// A single QSPI operation that completes with a `READY` event
+struct Op<'q, F>
+where
+    F: FnOnce(&QSPI),
+{
+    _qspi: &'q mut Qspi,
+    start: Option<F>,
+    state: State,
+}
+
+impl<F> Future for Op<'_, F>
+where
+    F: FnOnce(&QSPI) + Unpin,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        match self.state {
+            State::NotStarted => {
+                let start = self.start.take().expect("UNREACHABLE");
+
+                QSPI::borrow_unchecked(|qspi| {
+                    NVIC::mask(INTERRUPT);
+
+                    qspi.events_ready.reset();
+
+                    // install the waker
+                    unsafe {
+                        WAKER = Some(cx.waker().clone());
+                    }
+
+                    // here we finish transferring the buffer to the DMA; all previous memory
+                    // operations on the buffer should be finished before then, thus the compiler
+                    // fence
+                    atomic::compiler_fence(Ordering::Release);
+                    start(qspi);
+
+                    // updating the `WAKER` needs to be complete before unmasking the interrupt;
+                    // the compiler fence above takes care of that
+                    unsafe { NVIC::unmask(INTERRUPT) }
+                });
+
+                self.state = State::InProgress;
+
+                Poll::Pending
+            }
+
+            State::InProgress => QSPI::borrow_unchecked(|qspi| {
+                if qspi.events_ready.read().bits() != 0 {
+                    // buffer has been handed back to us; any future operation on the buffer
+                    // should not be reordered to before this point
+                    atomic::compiler_fence(Ordering::Acquire);
+
+                    qspi.events_ready.reset();
+
+                    // uninstall the waker
+                    NVIC::mask(INTERRUPT);
+                    // NOTE(compiler_fence) the interrupt must be disabled before we take down
+                    // the waker
+                    atomic::compiler_fence(Ordering::SeqCst);
+                    drop(unsafe { WAKER.take() });
+
+                    self.state = State::Finished;
+
+                    Poll::Ready(())
+                } else {
+                    // spurious wake up; re-arm the one-shot interrupt
+                    unsafe {
+                        NVIC::unmask(INTERRUPT);
+                    }
+
+                    Poll::Pending
+                }
+            }),
+
+            State::Finished => unreachable!(),
+        }
+    }
+}
+
+impl<F> Drop for Op<'_, F>
+where
+    F: FnOnce(&QSPI),
+{
+    fn drop(&mut self) {
+        if self.state == State::InProgress {
+            // QSPI operations cannot be aborted; wait for the DMA to release the buffer
+            QSPI::borrow_unchecked(|qspi| {
+                NVIC::mask(INTERRUPT);
+
+                while qspi.events_ready.read().bits() == 0 {
+                    // busy wait
+                    continue;
+                }
+                qspi.events_ready.reset();
+
+                atomic::compiler_fence(Ordering::SeqCst);
+                drop(unsafe { WAKER.take() });
+            })
+        }
+    }
+}
+
+static mut WAKER: Option<Waker> = None;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+fn QSPI() {
+    // NOTE(unsafe) the only other context that can access this static variable
+    // runs at lower priority
+    if let Some(waker) = unsafe { WAKER.as_ref() } {
+        waker.wake_by_ref();
+
+        // avoid continuously re-entering this interrupt handler
+        NVIC::mask(INTERRUPT);
+    } else {
+        // reachable if the user manually pends this interrupt
+    }
+}
+
+fn check(address: usize, len: usize) -> Result<(), Error> {
+    if address % ALIGN != 0 || len % ALIGN != 0 {
+        Err(Error::Alignment)
+    } else if address + len > CAPACITY {
+        Err(Error::OutOfBounds)
+    } else {
+        Ok(())
+    }
+}
+
+fn is_aligned(buf: &[u8]) -> bool {
+    buf.as_ptr() as usize % ALIGN == 0
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    NotStarted,
+    InProgress,
+    Finished,
+}
+
+/// QSPI error
+#[derive(Debug)]
+pub enum Error {
+    /// The address or the length of the buffer is not properly aligned
+    Alignment,
+
+    /// The operation goes past the end of the flash
+    OutOfBounds,
+}
+
+#[cfg(feature = "embedded-storage")]
+mod storage {
+    use core::sync::atomic::{self, Ordering};
+
+    use embedded_storage::nor_flash::{NorFlash, ReadNorFlash};
+    use pac::QSPI;
+
+    use super::{check, is_aligned, Aligned, Error, Qspi, BUFSZ, CAPACITY, SECTOR_SIZE};
+    use crate::BorrowUnchecked as _;
+
+    // NOTE the `embedded-storage` traits are blocking: these implementations busy wait for the
+    // `READY` event instead of yielding to the executor
+    fn blocking(start: impl FnOnce(&QSPI)) {
+        QSPI::borrow_unchecked(|qspi| {
+            qspi.events_ready.reset();
+            atomic::compiler_fence(Ordering::Release);
+            start(qspi);
+
+            while qspi.events_ready.read().bits() == 0 {
+                // busy wait
+                continue;
+            }
+            qspi.events_ready.reset();
+            atomic::compiler_fence(Ordering::Acquire);
+        })
+    }
+
+    fn read_aligned(address: usize, buf: &mut [u8]) {
+        blocking(|qspi| {
+            qspi.read.src.write(|w| unsafe { w.bits(address as u32) });
+            qspi.read.dst.write(|w| unsafe { w.bits(buf.as_mut_ptr() as u32) });
+            qspi.read.cnt.write(|w| unsafe { w.bits(buf.len() as u32) });
+            qspi.tasks_readstart.write(|w| unsafe { w.bits(1) });
+        })
+    }
+
+    fn write_aligned(address: usize, bytes: &[u8]) {
+        blocking(|qspi| {
+            qspi.write.dst.write(|w| unsafe { w.bits(address as u32) });
+            qspi.write.src.write(|w| unsafe { w.bits(bytes.as_ptr() as u32) });
+            qspi.write.cnt.write(|w| unsafe { w.bits(bytes.len() as u32) });
+            qspi.tasks_writestart.write(|w| unsafe { w.bits(1) });
+        })
+    }
+
+    impl ReadNorFlash for Qspi {
+        type Error = Error;
+
+        const READ_SIZE: usize = 4;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Error> {
+            let address = offset as usize;
+            check(address, bytes.len())?;
+
+            let mut bounce = Aligned([0; BUFSZ]);
+            for (i, chunk) in bytes.chunks_mut(BUFSZ).enumerate() {
+                let n = chunk.len();
+                if is_aligned(chunk) {
+                    read_aligned(address + i * BUFSZ, chunk);
+                } else {
+                    read_aligned(address + i * BUFSZ, &mut bounce.0[..n]);
+                    chunk.copy_from_slice(&bounce.0[..n]);
+                }
+            }
+
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            CAPACITY
+        }
+    }
+
+    impl NorFlash for Qspi {
+        const WRITE_SIZE: usize = 4;
+
+        const ERASE_SIZE: usize = SECTOR_SIZE;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Error> {
+            let (from, to) = (from as usize, to as usize);
+            if from % SECTOR_SIZE != 0 || to % SECTOR_SIZE != 0 {
+                return Err(Error::Alignment);
+            }
+
+            if to > CAPACITY {
+                return Err(Error::OutOfBounds);
+            }
+
+            for address in (from..to).step_by(SECTOR_SIZE) {
+                blocking(|qspi| {
+                    qspi.erase.ptr.write(|w| unsafe { w.bits(address as u32) });
+                    // 4 KiB sector
+                    qspi.erase.len.write(|w| unsafe { w.bits(0) });
+                    qspi.tasks_erasestart.write(|w| unsafe { w.bits(1) });
+                });
+            }
+
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Error> {
+            let address = offset as usize;
+            check(address, bytes.len())?;
+
+            let mut bounce = Aligned([0; BUFSZ]);
+            for (i, chunk) in bytes.chunks(BUFSZ).enumerate() {
+                if is_aligned(chunk) && crate::slice_in_ram(chunk) {
+                    write_aligned(address + i * BUFSZ, chunk);
+                } else {
+                    let n = chunk.len();
+                    bounce.0[..n].copy_from_slice(chunk);
+                    write_aligned(address + i * BUFSZ, &bounce.0[..n]);
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! Successive approximation analog-to-digital converter
+//!
+//! Conversions use a resolution of 12 bits. A single-ended conversion measures the positive input
+//! against ground and returns a value between 0 and 4095 (noise can push it slightly below 0); a
+//! differential conversion measures the positive input against the negative input and returns a
+//! value between -2048 and 2047
+
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{self, AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use cortex_m::peripheral::NVIC;
+use pac::Interrupt;
+
+use crate::{BorrowUnchecked as _, NotSync};
+
+// NOTE called from `pre_init`
+pub(crate) fn init() {
+    pac::SAADC::borrow_unchecked(|saadc| {
+        // RESOLUTION = 2 = 12 bits; no oversampling
+        saadc.resolution.write(|w| unsafe { w.bits(2) });
+        saadc.oversample.write(|w| unsafe { w.bits(0) });
+
+        // INTEN: bit 1 = END
+        saadc.intenset.write(|w| unsafe { w.bits(1 << 1) });
+    });
+}
+
+const INTERRUPT: Interrupt = Interrupt::SAADC;
+// full scale of single-ended conversions: 2^12
+const FULL_SCALE: i32 = 1 << 12;
+// voltage of the internal reference, in millivolts
+const INTERNAL_REFERENCE_MV: i32 = 600;
+
+/// Analog input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    /// AIN0 (P0.02)
+    Ain0,
+    /// AIN1 (P0.03)
+    Ain1,
+    /// AIN2 (P0.04)
+    Ain2,
+    /// AIN3 (P0.05)
+    Ain3,
+    /// AIN4 (P0.28)
+    Ain4,
+    /// AIN5 (P0.29)
+    Ain5,
+    /// AIN6 (P0.30)
+    Ain6,
+    /// AIN7 (P0.31)
+    Ain7,
+    /// The supply voltage, VDD
+    Vdd,
+    /// The high voltage supply, VDDH, divided by 5
+    #[cfg(not(feature = "nrf52832"))]
+    VddhDiv5,
+}
+
+impl Input {
+    // PSELP / PSELN value
+    fn psel(self) -> u32 {
+        match self {
+            Input::Ain0 => 1,
+            Input::Ain1 => 2,
+            Input::Ain2 => 3,
+            Input::Ain3 => 4,
+            Input::Ain4 => 5,
+            Input::Ain5 => 6,
+            Input::Ain6 => 7,
+            Input::Ain7 => 8,
+            Input::Vdd => 9,
+            #[cfg(not(feature = "nrf52832"))]
+            Input::VddhDiv5 => 0x0d,
+        }
+    }
+}
+
+/// Gain applied to the input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gain {
+    /// 1/6 (default); with the internal reference the input range is 0 - 3.6 V
+    Gain1_6,
+    /// 1/5
+    Gain1_5,
+    /// 1/4
+    Gain1_4,
+    /// 1/3
+    Gain1_3,
+    /// 1/2
+    Gain1_2,
+    /// 1
+    Gain1,
+    /// 2
+    Gain2,
+    /// 4
+    Gain4,
+}
+
+impl Gain {
+    // (numerator, denominator)
+    fn ratio(self) -> (i32, i32) {
+        match self {
+            Gain::Gain1_6 => (1, 6),
+            Gain::Gain1_5 => (1, 5),
+            Gain::Gain1_4 => (1, 4),
+            Gain::Gain1_3 => (1, 3),
+            Gain::Gain1_2 => (1, 2),
+            Gain::Gain1 => (1, 1),
+            Gain::Gain2 => (2, 1),
+            Gain::Gain4 => (4, 1),
+        }
+    }
+}
+
+/// Reference voltage
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reference {
+    /// Internal 0.6 V reference (default)
+    Internal,
+    /// VDD / 4; the conversion is then ratiometric to the supply voltage
+    Vdd1_4,
+}
+
+/// Acquisition time; sources with a higher impedance need a longer acquisition time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AcqTime {
+    /// 3 us; source impedance up to 10 kOhm
+    Us3,
+    /// 5 us; up to 40 kOhm
+    Us5,
+    /// 10 us (default); up to 100 kOhm
+    Us10,
+    /// 15 us; up to 200 kOhm
+    Us15,
+    /// 20 us; up to 400 kOhm
+    Us20,
+    /// 40 us; up to 800 kOhm
+    Us40,
+}
+
+/// Channel configuration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Channel {
+    /// Positive input
+    pub positive: Input,
+
+    /// Negative input; `None` makes this a single-ended channel
+    pub negative: Option<Input>,
+
+    /// Gain
+    pub gain: Gain,
+
+    /// Reference voltage
+    pub reference: Reference,
+
+    /// Acquisition time
+    pub acq_time: AcqTime,
+}
+
+impl Channel {
+    /// A single-ended channel with the default settings: 1/6 gain, internal reference and 10 us
+    /// acquisition time
+    pub const fn single_ended(input: Input) -> Self {
+        Self {
+            positive: input,
+            negative: None,
+            gain: Gain::Gain1_6,
+            reference: Reference::Internal,
+            acq_time: AcqTime::Us10,
+        }
+    }
+
+    /// A differential channel with the default settings (see `single_ended`)
+    pub const fn differential(positive: Input, negative: Input) -> Self {
+        Self {
+            negative: Some(negative),
+            ..Self::single_ended(positive)
+        }
+    }
+
+    /// Converts a sample taken on this channel to millivolts
+    ///
+    /// Returns `None` if the channel uses the `Vdd1_4` reference; the result would depend on the
+    /// (unknown) supply voltage
+    pub fn millivolts(&self, sample: i16) -> Option<i32> {
+        if self.reference != Reference::Internal {
+            return None;
+        }
+
+        // V = sample * reference / gain / full scale; differential conversions have half the
+        // full scale
+        let (num, den) = self.gain.ratio();
+        let full_scale = if self.negative.is_some() {
+            FULL_SCALE / 2
+        } else {
+            FULL_SCALE
+        };
+
+        Some(i32::from(sample) * INTERNAL_REFERENCE_MV * den / (num * full_scale))
+    }
+
+    // CH[n].CONFIG value; no resistor ladders; no burst
+    // NOTE the variants of `Gain`, `Reference` and `AcqTime` are declared in register value order
+    fn config(&self) -> u32 {
+        // CONFIG bits: 8..=10 = GAIN, 12 = REFSEL, 16..=18 = TACQ, 20 = MODE (1 = differential)
+        let gain = self.gain as u32;
+        let refsel = self.reference as u32;
+        let tacq = self.acq_time as u32;
+        let mode = self.negative.is_some() as u32;
+
+        gain << 8 | refsel << 12 | tacq << 16 | mode << 20
+    }
+}
+
+/// [singleton] An `async`-aware analog-to-digital converter
+pub struct Saadc {
+    _not_sync: NotSync,
+}
+
+impl Saadc {
+    /// Takes the singleton instance of the ADC
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the ADC has already been taken
+    pub fn take() -> Self {
+        // NOTE peripheral initialization is done in `#[pre_init]`
+
+        static TAKEN: AtomicBool = AtomicBool::new(false);
+
+        if TAKEN
+            .compare_exchange_weak(false, true, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            panic!("`Saadc` has already been taken")
+        }
+
+        pac::SAADC::borrow_unchecked(|saadc| saadc.enable.write(|w| w.enable().enabled()));
+
+        Self {
+            _not_sync: NotSync::new(),
+        }
+    }
+
+    /// Takes a single sample of the given `channel`
+    ///
+    /// See the module documentation for the range of the returned value and
+    /// `Channel::millivolts` to convert it to a voltage
+    pub async fn read(&mut self, channel: Channel) -> i16 {
+        struct Read<'t> {
+            _saadc: &'t mut Saadc,
+            channel: Channel,
+            // NOTE the DMA writes the sample into this field; the future is pinned while the
+            // conversion is in progress
+            sample: i16,
+            state: State,
+        }
+
+        impl Future for Read<'_> {
+            type Output = i16;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<i16> {
+                match self.state {
+                    State::NotStarted => {
+                        pac::SAADC::borrow_unchecked(|saadc| {
+                            let ch = &saadc.ch[0];
+                            ch.config
+                                .write(|w| unsafe { w.bits(self.channel.config()) });
+                            ch.pselp
+                                .write(|w| unsafe { w.bits(self.channel.positive.psel()) });
+                            // NOTE single-ended channels ignore PSELN; disconnect it anyway
+                            let pseln = self.channel.negative.map(Input::psel).unwrap_or(0);
+                            ch.pseln.write(|w| unsafe { w.bits(pseln) });
+
+                            // one-sample buffer
+                            let ptr = &mut self.sample as *mut i16;
+                            saadc.result.ptr.write(|w| unsafe { w.bits(ptr as u32) });
+                            saadc.result.maxcnt.write(|w| unsafe { w.bits(1) });
+
+                            saadc.events_started.reset();
+                            saadc.events_end.reset();
+
+                            // install the waker
+                            NVIC::mask(INTERRUPT);
+                            unsafe {
+                                WAKER = Some(cx.waker().clone());
+                                // NOTE(compiler_fence) writing the waker must complete before
+                                // the interrupt is unmasked
+                                atomic::compiler_fence(Ordering::Release);
+                                NVIC::unmask(INTERRUPT);
+                            }
+
+                            // semantically this completes the transfer of the buffer to the DMA
+                            atomic::compiler_fence(Ordering::Release);
+                            saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+                            // NOTE the buffer is latched within a few clock cycles
+                            while saadc.events_started.read().bits() == 0 {
+                                continue;
+                            }
+                            saadc.events_started.reset();
+                            saadc.tasks_sample.write(|w| unsafe { w.bits(1) });
+                        });
+
+                        self.state = State::InProgress;
+
+                        Poll::Pending
+                    }
+
+                    State::InProgress => pac::SAADC::borrow_unchecked(|saadc| {
+                        if saadc.events_end.read().bits() != 0 {
+                            // the DMA has released the buffer; reads of it must not be reordered
+                            // to before this point
+                            atomic::compiler_fence(Ordering::Acquire);
+                            saadc.events_end.reset();
+
+                            // uninstall the waker
+                            NVIC::mask(INTERRUPT);
+                            // NOTE(compiler_fence) the interrupt must be disabled before we take
+                            // down the waker
+                            atomic::compiler_fence(Ordering::SeqCst);
+                            drop(unsafe { WAKER.take() });
+
+                            self.state = State::Finished;
+
+                            Poll::Ready(self.sample)
+                        } else {
+                            // spurious wake up; re-arm the one-shot interrupt
+                            unsafe {
+                                NVIC::unmask(INTERRUPT);
+                            }
+
+                            Poll::Pending
+                        }
+                    }),
+
+                    State::Finished => unreachable!(),
+                }
+            }
+        }
+
+        // NOTE a `Read` future can be dropped before it completes, e.g. when it loses a `select`
+        // against a timeout. The DMA must release the buffer before the future is freed
+        impl Drop for Read<'_> {
+            fn drop(&mut self) {
+                if self.state == State::InProgress {
+                    // uninstall the waker
+                    NVIC::mask(INTERRUPT);
+                    // NOTE(compiler_fence) the interrupt must be disabled before we take down the
+                    // waker
+                    atomic::compiler_fence(Ordering::SeqCst);
+                    drop(unsafe { WAKER.take() });
+
+                    pac::SAADC::borrow_unchecked(|saadc| {
+                        saadc.events_stopped.reset();
+                        saadc.tasks_stop.write(|w| unsafe { w.bits(1) });
+                        while saadc.events_stopped.read().bits() == 0 {
+                            continue;
+                        }
+                        saadc.events_stopped.reset();
+                        saadc.events_end.reset();
+                    });
+                    NVIC::unpend(INTERRUPT);
+
+                    // NOTE(compiler_fence) the DMA has released the buffer
+                    atomic::compiler_fence(Ordering::Acquire);
+                }
+            }
+        }
+
+        Read {
+            _saadc: self,
+            channel,
+            sample: 0,
+            state: State::NotStarted,
+        }
+        .await
+    }
+}
+
+static mut WAKER: Option<Waker> = None;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+fn SAADC() {
+    // NOTE(unsafe) the only other context that can access this static variable
+    // runs at lower priority
+    if let Some(waker) = unsafe { WAKER.as_ref() } {
+        waker.wake_by_ref();
+
+        // avoid continuously re-entering this interrupt handler
+        NVIC::mask(INTERRUPT);
+    } else {
+        // reachable if the user manually pends this interrupt
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    NotStarted,
+    InProgress,
+    Finished,
+}
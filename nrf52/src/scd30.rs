@@ -3,9 +3,21 @@
 // Reference: Interface Description Sensirion SCD30 Sensor Module (Version
 // 0.94–D1 –June 2019)
 
-use async_embedded::unsync::Mutex;
+use core::{fmt, time::Duration};
 
-use crate::twim::{self, Twim};
+use async_embedded::{
+    task,
+    unsync::{Channel, Mutex},
+};
+use heapless::{consts, ArrayLength, Vec};
+
+use crate::{
+    ds3231::Ds3231,
+    gpio::InputPin,
+    sensirion,
+    timer::{self, Timer},
+    twim::{self, Twim},
+};
 
 /// Sensor measurement
 #[derive(Clone, Copy)]
@@ -20,11 +32,80 @@ pub struct Measurement {
     pub t: f32,
 }
 
+impl Measurement {
+    /// Returns the temperature in Fahrenheit
+    ///
+    /// The Celsius reading, `t`, is the one reported by the sensor; this is converted on each call
+    pub fn temperature_fahrenheit(&self) -> f32 {
+        self.t * 9. / 5. + 32.
+    }
+}
+
+/// Formats the measurement as, e.g., `CO2: 652 ppm, T: 26.3 °C, RH: 23%`
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the precision matches the accuracy of the sensor
+        write!(
+            f,
+            "CO2: {:.0} ppm, T: {:.1} °C, RH: {:.0}%",
+            self.co2, self.t, self.rh
+        )
+    }
+}
+
 const ADDRESS: u8 = 0x61;
 
+// Commands
+const START_CONTINUOUS_MEASUREMENT: u16 = 0x0010;
+const STOP_CONTINUOUS_MEASUREMENT: u16 = 0x0104;
+const GET_DATA_READY: u16 = 0x0202;
+const READ_MEASUREMENT: u16 = 0x0300;
+const FIRMWARE_VERSION: u16 = 0xd100;
+const SET_TEMPERATURE_OFFSET: u16 = 0x5403;
+const SET_MEASUREMENT_INTERVAL: u16 = 0x4600;
+const SET_FORCED_RECALIBRATION: u16 = 0x5204;
+const SET_AUTO_SELF_CALIBRATION: u16 = 0x5306;
+const SOFT_RESET: u16 = 0xd304;
+
+// valid ambient pressures, in mbar; 0 disables the compensation
+const MIN_PRESSURE: u16 = 700;
+const MAX_PRESSURE: u16 = 1400;
+
+// the offset is an unsigned number of hundredths of a degree
+const MAX_TEMPERATURE_OFFSET: f32 = 655.35;
+// smallest offset change written by `compensate_once`; the sensor stores the offset in
+// non-volatile memory
+const OFFSET_DEADBAND: f32 = 0.1;
+
+// valid measurement intervals, in seconds
+const MIN_INTERVAL: u16 = 2;
+const MAX_INTERVAL: u16 = 1800;
+// the sensor needs about 2 seconds to re-initialize after a soft reset
+const RESET_TIME: Duration = Duration::from_secs(2);
+
+// valid forced recalibration references, in ppm
+const MIN_REFERENCE: u16 = 400;
+const MAX_REFERENCE: u16 = 2000;
+// by default the sensor produces a new measurement every 2 seconds
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(MIN_INTERVAL as u64);
+
+// how long past the measurement interval we wait for a new measurement
+//
+// NOTE waiting on the RDY pin (see `Scd30::with_rdy_pin`) lets the bus idle and the device sleep
+// between measurements; polling the sensor keeps both busy for the whole interval
+const DATA_READY_MARGIN: Duration = Duration::from_secs(3);
+
 /// SCD30 I2C driver
 pub struct Scd30<'a> {
     twim: &'a Mutex<Twim>,
+    // a measurement has been read out
+    measured: bool,
+    // the last measurement was already waiting when `get_measurement` was called
+    missed: bool,
+    // data ready pin; see `with_rdy_pin`
+    rdy: Option<(InputPin, &'a Timer)>,
+    // the measurement interval of the sensor, as far as we know
+    interval: Duration,
 }
 
 /// Driver error
@@ -33,6 +114,18 @@ pub enum Error {
     /// Checksum error
     Checksum,
 
+    /// The measurement interval, in seconds, is outside the 2 - 1800 range
+    InvalidInterval(u16),
+
+    /// The recalibration reference, in ppm, is outside the 400 - 2000 range
+    InvalidReference(u16),
+
+    /// The ambient pressure, in mbar, is neither 0 nor in the 700 - 1400 range
+    InvalidPressure(u16),
+
+    /// The sensor didn't produce a new measurement in time
+    Timeout,
+
     /// I2C error
     Twim(twim::Error),
 }
@@ -43,57 +136,633 @@ impl From<twim::Error> for Error {
     }
 }
 
+impl From<sensirion::Error> for Error {
+    fn from(e: sensirion::Error) -> Self {
+        match e {
+            sensirion::Error::Checksum { .. } => Error::Checksum,
+            sensirion::Error::Twim(e) => Error::Twim(e),
+        }
+    }
+}
+
+impl twim::BusError for Error {
+    fn twim_error(&self) -> Option<&twim::Error> {
+        match self {
+            Error::Twim(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl<'a> Scd30<'a> {
     /// Creates a new driver
+    ///
+    /// The driver polls the sensor to find out when a new measurement is ready; see
+    /// `with_rdy_pin` for a more efficient alternative
     pub fn new(twim: &'a Mutex<Twim>) -> Self {
-        Self { twim }
+        Self {
+            twim,
+            measured: false,
+            missed: false,
+            rdy: None,
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Creates a new driver that waits on the RDY pin of the sensor instead of polling it
+    ///
+    /// `rdy` must be connected to the RDY pin, which the sensor drives high while a measurement
+    /// is ready to be read out. The sensor produces a measurement every 2 seconds; with this
+    /// driver the bus is not used, and the device can sleep, between measurements. `timer` bounds
+    /// the wait (see `get_measurement`)
+    pub fn with_rdy_pin(twim: &'a Mutex<Twim>, rdy: InputPin, timer: &'a Timer) -> Self {
+        Self {
+            rdy: Some((rdy, timer)),
+            ..Self::new(twim)
+        }
+    }
+
+    /// Returns the RDY pin, if any; the driver falls back to polling the sensor
+    pub fn release_rdy_pin(&mut self) -> Option<InputPin> {
+        self.rdy.take().map(|(rdy, _)| rdy)
     }
 
     /// Returns the last sensor measurement
+    ///
+    /// This waits for a new measurement to be ready; if the sensor doesn't produce one within the
+    /// measurement interval plus 3 seconds `Error::Timeout` is returned
     pub async fn get_measurement(&mut self) -> Result<Measurement, Error> {
-        while !self.data_ready().await? {
-            continue;
+        let waited = if let Some((rdy, timer)) = self.rdy.as_mut() {
+            wait_for_rdy(rdy, timer, self.interval).await?
+        } else {
+            let deadline = timer::deadline(self.interval + DATA_READY_MARGIN);
+            let mut waited = false;
+            while !self.data_ready().await? {
+                if deadline.expired() {
+                    return Err(Error::Timeout);
+                }
+
+                waited = true;
+                // `data_ready` releases the bus when it returns; give other tasks, like other
+                // drivers sharing the bus, a chance to run before we poll the sensor again
+                task::r#yield().await;
+            }
+            waited
+        };
+
+        // NOTE the sensor holds a single measurement; a new one overwrites the previous one
+        self.missed = self.measured && !waited;
+        self.measured = true;
+
+        self.read_measurement().await
+    }
+
+    /// Returns the next sensor measurement, waiting on the RDY pin instead of polling the sensor
+    ///
+    /// Like `get_measurement` on a driver created with `with_rdy_pin`, but `rdy` is only borrowed
+    /// for the duration of this call
+    pub async fn wait_for_measurement(
+        &mut self,
+        rdy: &mut InputPin,
+        timer: &Timer,
+    ) -> Result<Measurement, Error> {
+        let waited = wait_for_rdy(rdy, timer, self.interval).await?;
+
+        // NOTE the sensor holds a single measurement; a new one overwrites the previous one
+        self.missed = self.measured && !waited;
+        self.measured = true;
+
+        self.read_measurement().await
+    }
+
+    /// Returns the last sensor measurement if new data is ready, or `None` otherwise
+    ///
+    /// Unlike `get_measurement` this checks the sensor only once; the caller decides when to try
+    /// again
+    pub async fn try_get_measurement(&mut self) -> Result<Option<Measurement>, Error> {
+        let ready = match self.rdy.as_ref() {
+            // NOTE no need to use the bus
+            Some((rdy, _)) => rdy.is_high(),
+            None => self.data_ready().await?,
+        };
+
+        if ready {
+            self.read_measurement().await.map(Some)
+        } else {
+            Ok(None)
         }
+    }
+
+    async fn read_measurement(&mut self) -> Result<Measurement, Error> {
+        let words = self.read::<consts::U6>(READ_MEASUREMENT).await?;
+
+        // each value is a big endian `f32` split in two words
+        let float = |i: usize| f32::from_bits(u32::from(words[i]) << 16 | u32::from(words[i + 1]));
+        let co2 = float(0);
+        let t = float(2);
+        let rh = float(4);
+
+        Ok(Measurement { co2, t, rh })
+    }
+
+    /// Returns `true` if measurements may have been missed between the last two calls to
+    /// `get_measurement` (or `wait_for_measurement`)
+    ///
+    /// This is a heuristic: the last measurement was already waiting to be read out when
+    /// `get_measurement` was called, so the sensor may have produced (and overwritten) more than
+    /// one since the previous read-out. If this keeps returning `true` the measurements are being
+    /// read out less often than the sensor produces them
+    pub fn missed_since_last(&self) -> bool {
+        self.missed
+    }
 
-        let mut buf = [0; 18];
-        {
-            let mut twim = self.twim.lock().await;
-            twim.write(ADDRESS, &[0x03, 0x00]).await?;
-            twim.read(ADDRESS, &mut buf).await?;
-            drop(twim);
+    /// Turns this driver into one that reports the mean of the last `window` measurements
+    ///
+    /// `N` is the capacity of the averaging buffer; `window` can be changed at runtime but must be
+    /// in the range `1..=N`
+    pub fn averaged<N>(self, window: usize) -> Averaged<'a, N>
+    where
+        N: ArrayLength<Measurement>,
+    {
+        assert!(window != 0 && window <= N::to_usize());
+
+        Averaged {
+            scd30: self,
+            window,
+            samples: Vec::new(),
+            next: 0,
         }
+    }
 
-        for chunk in buf.chunks(3) {
-            if !crc_check(&chunk[..2], chunk[2]) {
-                return Err(Error::Checksum);
+    /// Continuously reads out the sensor and sends the measurements, or errors, into `sink`
+    ///
+    /// After each read-out the task sleeps for `interval`. This is meant to be `spawn`-ed as a
+    /// task; consumers just need to `recv` from the `sink`. Returns when the `sink` is closed
+    pub async fn run<N>(
+        mut self,
+        timer: &mut Timer,
+        interval: Duration,
+        sink: &Channel<Result<Measurement, Error>, N>,
+    ) where
+        N: generic_array::ArrayLength<Result<Measurement, Error>>,
+    {
+        loop {
+            let res = self.get_measurement().await;
+            if sink.send(res).await.is_err() {
+                return;
             }
+            timer.wait(interval).await;
         }
+    }
 
-        let co2 = f32::from_le_bytes([buf[4], buf[3], buf[1], buf[0]]);
-        let t = f32::from_le_bytes([buf[10], buf[9], buf[7], buf[6]]);
-        let rh = f32::from_le_bytes([buf[16], buf[15], buf[13], buf[12]]);
+    /// Returns the firmware version of the sensor as a (major, minor) pair
+    pub async fn firmware_version(&mut self) -> Result<(u8, u8), Error> {
+        let [major, minor] = self.read::<consts::U1>(FIRMWARE_VERSION).await?[0].to_be_bytes();
 
-        Ok(Measurement { co2, t, rh })
+        Ok((major, minor))
+    }
+
+    /// Starts the continuous measurements, compensated for the given ambient pressure
+    ///
+    /// `pressure_mbar` must be in the 700 - 1400 mbar range; 0 disables the pressure compensation.
+    /// Otherwise `Error::InvalidPressure` is returned and nothing is sent to the sensor. The sensor
+    /// remembers this setting across power cycles; calling this again while the measurements are
+    /// running updates the pressure
+    pub async fn start_continuous_measurement(&mut self, pressure_mbar: u16) -> Result<(), Error> {
+        let command = start_continuous_measurement_command(pressure_mbar)?;
+
+        self.twim.lock().await.write(ADDRESS, &command).await?;
+
+        Ok(())
+    }
+
+    /// Stops the continuous measurements
+    ///
+    /// NOTE `get_measurement` times out while the measurements are stopped
+    pub async fn stop_continuous_measurement(&mut self) -> Result<(), Error> {
+        self.twim
+            .lock()
+            .await
+            .write(ADDRESS, &STOP_CONTINUOUS_MEASUREMENT.to_be_bytes())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the measurement interval of the sensor, in seconds
+    ///
+    /// The sensor stores the interval in non-volatile memory. The driver assumes the default
+    /// interval (2 seconds) until this or `set_measurement_interval` is called
+    pub async fn get_measurement_interval(&mut self) -> Result<u16, Error> {
+        let secs = self.read::<consts::U1>(SET_MEASUREMENT_INTERVAL).await?[0];
+        self.interval = Duration::from_secs(secs.into());
+
+        Ok(secs)
+    }
+
+    /// Sets the interval, in seconds, at which the sensor produces measurements
+    ///
+    /// The interval must be in the 2 - 1800 range; otherwise `Error::InvalidInterval` is returned
+    /// and nothing is sent to the sensor. The sensor stores the interval in non-volatile memory
+    pub async fn set_measurement_interval(&mut self, secs: u16) -> Result<(), Error> {
+        let command = measurement_interval_command(secs)?;
+
+        self.twim.lock().await.write(ADDRESS, &command).await?;
+        self.interval = Duration::from_secs(secs.into());
+
+        Ok(())
+    }
+
+    /// Recalibrates the CO2 readings against a known CO2 concentration, in ppm
+    ///
+    /// The reference must be in the 400 - 2000 ppm range (e.g. 400 ppm for fresh outdoor air);
+    /// otherwise `Error::InvalidReference` is returned and nothing is sent to the sensor. For
+    /// accurate results the sensor must have been measuring continuously in the reference
+    /// environment for at least 2 minutes. The sensor stores the calibration in non-volatile memory
+    pub async fn force_recalibration(&mut self, ppm: u16) -> Result<(), Error> {
+        let command = forced_recalibration_command(ppm)?;
+
+        self.twim.lock().await.write(ADDRESS, &command).await?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the automatic self-calibration (ASC) is enabled
+    pub async fn get_auto_self_calibration(&mut self) -> Result<bool, Error> {
+        Ok(self.read::<consts::U1>(SET_AUTO_SELF_CALIBRATION).await?[0] != 0)
+    }
+
+    /// Enables or disables the automatic self-calibration (ASC)
+    ///
+    /// With ASC the sensor recalibrates itself assuming it's exposed to fresh air (400 ppm) for at
+    /// least 1 hour a day. The sensor stores this setting in non-volatile memory
+    pub async fn set_auto_self_calibration(&mut self, enabled: bool) -> Result<(), Error> {
+        let mut twim = self.twim.lock().await;
+        sensirion::write_word(
+            &mut twim,
+            ADDRESS,
+            SET_AUTO_SELF_CALIBRATION,
+            enabled.into(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the temperature offset, in Celsius, that the sensor subtracts from its readings
+    pub async fn temperature_offset(&mut self) -> Result<f32, Error> {
+        let ticks = self.read::<consts::U1>(SET_TEMPERATURE_OFFSET).await?[0];
+
+        Ok(f32::from(ticks) / 100.)
+    }
+
+    /// Sets the temperature offset, in Celsius, that the sensor subtracts from its readings to
+    /// compensate for self-heating
+    ///
+    /// The offset is clamped to the 0 - 655.35 C range; the resolution is 0.01 C. The sensor stores
+    /// the offset in non-volatile memory
+    pub async fn set_temperature_offset(&mut self, offset: f32) -> Result<(), Error> {
+        let offset = offset.max(0.).min(MAX_TEMPERATURE_OFFSET);
+        let ticks = (offset * 100. + 0.5) as u16;
+
+        let mut twim = self.twim.lock().await;
+        sensirion::write_word(&mut twim, ADDRESS, SET_TEMPERATURE_OFFSET, ticks).await?;
+
+        Ok(())
+    }
+
+    /// Adjusts the temperature offset so that the sensor agrees with the temperature of the
+    /// DS3231 `rtc`
+    ///
+    /// This reads out a measurement and the temperature of the RTC and computes the new offset
+    /// with `compensated_offset`. The offset is only written if it changes by at least 0.1 C.
+    /// Returns the offset in use
+    pub async fn compensate_once(&mut self, rtc: &mut Ds3231<'_>) -> Result<f32, Error> {
+        let measured = self.get_measurement().await?.t;
+        let reference = rtc.get_temperature().await?;
+        let current = self.temperature_offset().await?;
+
+        let offset = compensated_offset(current, measured, reference);
+        // NOTE `f32::abs` is not available in `core`
+        let change = offset - current;
+        if -OFFSET_DEADBAND < change && change < OFFSET_DEADBAND {
+            return Ok(current);
+        }
+
+        self.set_temperature_offset(offset).await?;
+        Ok(offset)
+    }
+
+    /// Runs `compensate_once` every `interval`, until it fails; returns the error
+    ///
+    /// The DS3231 measures its temperature every 64 seconds and each update of the offset is
+    /// written to non-volatile memory so `interval` should be several minutes long. The two
+    /// drivers share the I2C bus; it's only locked for the duration of each transfer. This is
+    /// meant to be `spawn`-ed as a task
+    pub async fn compensate(
+        &mut self,
+        rtc: &mut Ds3231<'_>,
+        timer: &Timer,
+        interval: Duration,
+    ) -> Error {
+        loop {
+            if let Err(e) = self.compensate_once(rtc).await {
+                return e;
+            }
+
+            timer.wait(interval).await;
+        }
+    }
+
+    /// Checks that the sensor is responsive and that its responses pass the checksum
+    ///
+    /// This reads the firmware version and the data ready status. An unresponsive sensor (e.g.
+    /// disconnected or unpowered) is reported as an `Error::Twim`; a corrupted response is
+    /// reported as `Error::Checksum`
+    pub async fn self_test(&mut self) -> Result<(), Error> {
+        self.firmware_version().await?;
+        self.data_ready().await?;
+
+        Ok(())
+    }
+
+    /// Restarts the sensor, e.g. to recover it from a bad state after a bus glitch
+    ///
+    /// The sensor doesn't respond while it re-initializes so this waits about 2 seconds, using
+    /// `timer`, before returning; the bus is released in the meantime. The settings stored in
+    /// non-volatile memory (e.g. the measurement interval) are kept and the continuous
+    /// measurements resume if they were running
+    pub async fn soft_reset(&mut self, timer: &Timer) -> Result<(), Error> {
+        let (address, command) = soft_reset_command();
+        self.twim.lock().await.write(address, &command).await?;
+        // any measurement that was ready has been lost
+        self.measured = false;
+        self.missed = false;
+
+        timer.wait(RESET_TIME).await;
+
+        Ok(())
     }
 
     async fn data_ready(&mut self) -> Result<bool, Error> {
-        let mut buf = [0; 3];
-        {
-            let mut twim = self.twim.lock().await;
-            twim.write(ADDRESS, &[0x02, 0x02]).await?;
-            twim.read(ADDRESS, &mut buf).await?;
-            drop(twim);
+        let words = self.read::<consts::U1>(GET_DATA_READY).await?;
+
+        Ok(words[0] == 1)
+    }
+
+    // Sends `command` and reads back `N` words, validating their checksums
+    async fn read<N>(&mut self, command: u16) -> Result<Vec<u16, N>, Error>
+    where
+        N: ArrayLength<u16>,
+    {
+        let mut twim = self.twim.lock().await;
+        Ok(sensirion::read_words(&mut twim, ADDRESS, command).await?)
+    }
+}
+
+/// Encodes the soft reset command as the (I2C address, bytes) of the write that `soft_reset`
+/// issues
+pub fn soft_reset_command() -> (u8, [u8; 2]) {
+    (ADDRESS, SOFT_RESET.to_be_bytes())
+}
+
+/// Encodes the command that sets the measurement interval to `secs` seconds
+///
+/// Returns `Error::InvalidInterval` if `secs` is outside the 2 - 1800 range
+pub fn measurement_interval_command(secs: u16) -> Result<[u8; 5], Error> {
+    if secs < MIN_INTERVAL || secs > MAX_INTERVAL {
+        return Err(Error::InvalidInterval(secs));
+    }
+
+    Ok(sensirion::encode_write(SET_MEASUREMENT_INTERVAL, secs))
+}
+
+/// Encodes the command that starts the continuous measurements, compensated for an ambient
+/// pressure of `pressure_mbar` mbar
+///
+/// Returns `Error::InvalidPressure` if `pressure_mbar` is neither 0 nor in the 700 - 1400 range
+pub fn start_continuous_measurement_command(pressure_mbar: u16) -> Result<[u8; 5], Error> {
+    if pressure_mbar != 0 && (pressure_mbar < MIN_PRESSURE || pressure_mbar > MAX_PRESSURE) {
+        return Err(Error::InvalidPressure(pressure_mbar));
+    }
+
+    Ok(sensirion::encode_write(
+        START_CONTINUOUS_MEASUREMENT,
+        pressure_mbar,
+    ))
+}
+
+/// Encodes the command that recalibrates the sensor against a reference of `ppm` ppm of CO2
+///
+/// Returns `Error::InvalidReference` if `ppm` is outside the 400 - 2000 range
+pub fn forced_recalibration_command(ppm: u16) -> Result<[u8; 5], Error> {
+    if ppm < MIN_REFERENCE || ppm > MAX_REFERENCE {
+        return Err(Error::InvalidReference(ppm));
+    }
+
+    Ok(sensirion::encode_write(SET_FORCED_RECALIBRATION, ppm))
+}
+
+/// Computes the temperature offset that makes the sensor report the `reference` temperature
+///
+/// `measured` is a temperature reported by the sensor while the offset was `current`. The result
+/// is clamped to the range the sensor supports (0 - 655.35 C): the offset can only lower the
+/// readings so a sensor that reads colder than the reference is left uncorrected
+pub fn compensated_offset(current: f32, measured: f32, reference: f32) -> f32 {
+    // the sensor reports its raw temperature minus the offset
+    let raw = measured + current;
+
+    (raw - reference).max(0.).min(MAX_TEMPERATURE_OFFSET)
+}
+
+/// SCD30 driver that reports the mean of the last few measurements; see `Scd30::averaged`
+pub struct Averaged<'a, N>
+where
+    N: ArrayLength<Measurement>,
+{
+    scd30: Scd30<'a>,
+    window: usize,
+    // ring buffer; `next` is the slot that will be overwritten once the buffer is full
+    samples: Vec<Measurement, N>,
+    next: usize,
+}
+
+impl<'a, N> Averaged<'a, N>
+where
+    N: ArrayLength<Measurement>,
+{
+    /// Reads out a new measurement and returns the mean (of each field) of the last `window`
+    /// measurements
+    ///
+    /// Failed read-outs (e.g. `Error::Checksum`) are not added to the window; their error is
+    /// returned instead. Until `window` measurements have been read out the mean is over fewer
+    /// measurements
+    pub async fn get_measurement(&mut self) -> Result<Measurement, Error> {
+        let m = self.scd30.get_measurement().await?;
+
+        if self.samples.len() < self.window {
+            // NOTE(unwrap) `window <= N`
+            self.samples.push(m).ok().unwrap();
+        } else {
+            self.samples[self.next] = m;
+            self.next = (self.next + 1) % self.window;
         }
 
-        if !crc_check(&buf[..2], buf[2]) {
-            return Err(Error::Checksum);
+        let n = self.samples.len() as f32;
+        let mut sum = Measurement {
+            co2: 0.,
+            rh: 0.,
+            t: 0.,
+        };
+        for m in &self.samples {
+            sum.co2 += m.co2;
+            sum.rh += m.rh;
+            sum.t += m.t;
         }
 
-        Ok(buf[1] == 1)
+        Ok(Measurement {
+            co2: sum.co2 / n,
+            rh: sum.rh / n,
+            t: sum.t / n,
+        })
+    }
+
+    /// Changes the size of the window; this discards the measurements read out so far
+    ///
+    /// `window` must be in the range `1..=N`
+    pub fn set_window(&mut self, window: usize) {
+        assert!(window != 0 && window <= N::to_usize());
+
+        self.window = window;
+        self.samples.clear();
+        self.next = 0;
     }
+
+    /// Returns the underlying driver
+    pub fn into_inner(self) -> Scd30<'a> {
+        self.scd30
+    }
+}
+
+// Waits until the RDY pin goes high; returns `true` if the measurement was not ready yet
+//
+// NOTE the pin may already be high on entry; `wait_for_high` returns immediately in that case
+async fn wait_for_rdy(
+    rdy: &mut InputPin,
+    timer: &Timer,
+    interval: Duration,
+) -> Result<bool, Error> {
+    let waited = rdy.is_low();
+    timer
+        .timeout_at(
+            Timer::now() + interval + DATA_READY_MARGIN,
+            rdy.wait_for_high(),
+        )
+        .await
+        .map_err(|_| Error::Timeout)?;
+
+    Ok(waited)
 }
 
-// TODO
-fn crc_check(_bytes: &[u8], _crc: u8) -> bool {
-    true
+#[cfg(test)]
+mod tests {
+    use super::{Error, Measurement};
+    use crate::twim;
+
+    // the results are not exact: `f32` arithmetic
+    fn close(a: f32, b: f32) -> bool {
+        -0.001 < a - b && a - b < 0.001
+    }
+
+    #[test]
+    fn compensated_offset() {
+        // the sensor reads 2 C above the reference with no offset
+        assert!(close(super::compensated_offset(0., 25., 23.), 2.));
+        // the current offset is already right
+        assert!(close(super::compensated_offset(2., 23., 23.), 2.));
+        // the current offset over-compensates by 0.5 C
+        assert!(close(super::compensated_offset(2., 22.5, 23.), 1.5));
+    }
+
+    #[test]
+    fn compensated_offset_is_clamped() {
+        // the sensor reads colder than the reference; the offset can't be negative
+        assert_eq!(super::compensated_offset(0., 20., 23.), 0.);
+        assert_eq!(super::compensated_offset(1., 20., 23.), 0.);
+        // nor larger than what the sensor can store
+        assert_eq!(super::compensated_offset(650., 100., 0.), 655.35);
+    }
+
+    #[test]
+    fn pressure_validation() {
+        // "start continuous measurement" (0x0010) with an ambient pressure of 1013 mbar (0x03F5)
+        match super::start_continuous_measurement_command(1013) {
+            Ok(bytes) => assert_eq!(bytes, [0x00, 0x10, 0x03, 0xF5, 0xDB]),
+            Err(e) => panic!("rejected a pressure of 1013 mbar: {:?}", e),
+        }
+        // 0 disables the pressure compensation
+        assert!(super::start_continuous_measurement_command(0).is_ok());
+
+        // out of range: 700 - 1400 mbar
+        for &pressure in &[1, 699, 1401] {
+            match super::start_continuous_measurement_command(pressure) {
+                Err(Error::InvalidPressure(p)) if p == pressure => {}
+                _ => panic!("accepted a pressure of {} mbar", pressure),
+            }
+        }
+    }
+
+    #[test]
+    fn display() {
+        let m = Measurement {
+            co2: 652.4,
+            t: 26.27,
+            rh: 23.4,
+        };
+        assert_eq!(format!("{}", m), "CO2: 652 ppm, T: 26.3 °C, RH: 23%");
+
+        // the ends of the range of the sensor
+        let m = Measurement {
+            co2: 40_000.,
+            t: -39.96,
+            rh: 99.7,
+        };
+        assert_eq!(format!("{}", m), "CO2: 40000 ppm, T: -40.0 °C, RH: 100%");
+    }
+
+    #[test]
+    fn temperature_fahrenheit() {
+        // (Celsius, Fahrenheit)
+        let temperatures = [
+            // the lower end of the range of the sensor; both scales meet here
+            (-40., -40.),
+            (-17.5, 0.5),
+            (-10., 14.),
+            (0., 32.),
+            (21.5, 70.7),
+            (37., 98.6),
+            // the upper end of the range of the sensor
+            (70., 158.),
+        ];
+
+        for &(c, f) in temperatures.iter() {
+            let m = Measurement {
+                co2: 400.,
+                rh: 50.,
+                t: c,
+            };
+
+            // NOTE not exact; e.g. 70.7 can't be represented as a `f32`
+            assert!(close(m.temperature_fahrenheit(), f), "{} C", c);
+        }
+    }
+
+    #[test]
+    fn error_debug() {
+        // the I2C error is decoded
+        let e = Error::Twim(twim::Error::Src(0b100));
+        assert_eq!(format!("{:?}", e), "Twim(Src(data NACK))");
+        let e = Error::InvalidPressure(1500);
+        assert_eq!(format!("{:?}", e), "InvalidPressure(1500)");
+    }
 }
@@ -0,0 +1,187 @@
+//! Sensirion I2C protocol
+//!
+//! Sensirion sensors (e.g. the SCD30) answer commands with a sequence of 16-bit words, most
+//! significant byte first, each followed by its CRC-8. Command arguments use the same format
+
+use heapless::{ArrayLength, Vec};
+
+use crate::twim::{self, Twim};
+
+/// Maximum number of words `read_words` can read in a single call
+pub const MAX_WORDS: usize = 32;
+
+/// Error returned by `read_words` and `decode_words`
+#[derive(Debug)]
+pub enum Error {
+    /// The checksum of a word doesn't match
+    Checksum {
+        /// Index of the first corrupted word
+        word: usize,
+    },
+
+    /// I2C error
+    Twim(twim::Error),
+}
+
+impl From<twim::Error> for Error {
+    fn from(e: twim::Error) -> Self {
+        Error::Twim(e)
+    }
+}
+
+impl twim::BusError for Error {
+    fn twim_error(&self) -> Option<&twim::Error> {
+        match self {
+            Error::Twim(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Sends `command` to the device at `address` and reads back `N` words, validating their
+/// checksums
+///
+/// The command and the response are two separate transfers because Sensirion sensors don't
+/// support repeated STARTs
+///
+/// # Panics
+///
+/// This function panics if `N` is larger than `MAX_WORDS`
+pub async fn read_words<N>(twim: &mut Twim, address: u8, command: u16) -> Result<Vec<u16, N>, Error>
+where
+    N: ArrayLength<u16>,
+{
+    let n = N::to_usize();
+    assert!(n <= MAX_WORDS, "can't read more than `MAX_WORDS` words");
+
+    let mut buf = [0; 3 * MAX_WORDS];
+    let buf = &mut buf[..3 * n];
+    twim.write(address, &command.to_be_bytes()).await?;
+    twim.read(address, buf).await?;
+
+    decode_words(buf)
+}
+
+/// Sends `command` to the device at `address` followed by the `argument` word and its CRC
+pub async fn write_word(
+    twim: &mut Twim,
+    address: u8,
+    command: u16,
+    argument: u16,
+) -> Result<(), twim::Error> {
+    twim.write(address, &encode_write(command, argument)).await
+}
+
+/// Encodes `command` followed by the `argument` word and its CRC, as sent by `write_word`
+pub fn encode_write(command: u16, argument: u16) -> [u8; 5] {
+    let [c0, c1] = command.to_be_bytes();
+    let [a0, a1] = argument.to_be_bytes();
+
+    [c0, c1, a0, a1, crc8(&[a0, a1])]
+}
+
+/// Decodes a response made of (2-byte word, CRC) triplets, validating the checksum of each word
+///
+/// Trailing bytes that don't make up a whole triplet are ignored, as are the words that don't fit
+/// in the returned `Vec`
+pub fn decode_words<N>(buf: &[u8]) -> Result<Vec<u16, N>, Error>
+where
+    N: ArrayLength<u16>,
+{
+    let mut words = Vec::new();
+    for (word, triplet) in buf.chunks_exact(3).enumerate() {
+        if crc8(&triplet[..2]) != triplet[2] {
+            return Err(Error::Checksum { word });
+        }
+
+        if words
+            .push(u16::from_be_bytes([triplet[0], triplet[1]]))
+            .is_err()
+        {
+            break;
+        }
+    }
+
+    Ok(words)
+}
+
+/// Computes the CRC-8 of `bytes`
+///
+/// Polynomial = 0x31 (x^8 + x^5 + x^4 + 1), initialization = 0xFF, no final XOR; e.g. the CRC of
+/// `[0xBE, 0xEF]` is 0x92
+pub fn crc8(bytes: &[u8]) -> u8 {
+    const POLYNOMIAL: u8 = 0x31;
+
+    let mut acc = 0xff;
+    for byte in bytes {
+        acc ^= byte;
+        for _ in 0..8 {
+            acc = if acc & 0x80 != 0 {
+                (acc << 1) ^ POLYNOMIAL
+            } else {
+                acc << 1
+            };
+        }
+    }
+
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use heapless::consts;
+
+    use super::Error;
+
+    #[test]
+    fn crc8_known_good() {
+        // the example of the datasheet
+        assert_eq!(super::crc8(&[0xBE, 0xEF]), 0x92);
+        assert_eq!(super::crc8(&[0x00, 0x00]), 0x81);
+        // 1013 mbar; the argument of the SCD30 "start continuous measurement" command
+        assert_eq!(super::crc8(&[0x03, 0xF5]), 0xDB);
+    }
+
+    #[test]
+    fn crc8_corrupted() {
+        // any single bit flip changes the CRC
+        for bit in 0..16 {
+            let word = 0xBEEF_u16 ^ (1 << bit);
+            assert_ne!(super::crc8(&word.to_be_bytes()), 0x92);
+        }
+
+        // a corrupted word is rejected when the response is decoded
+        match super::decode_words::<consts::U1>(&[0xBE, 0xEE, 0x92]) {
+            Err(Error::Checksum { word: 0 }) => {}
+            res => panic!("{:?}", res),
+        }
+    }
+
+    #[test]
+    fn decode() {
+        // 0xBEEF, 0x0001 and 0x1234 with their CRCs
+        let mut response = [0xBE, 0xEF, 0x92, 0x00, 0x01, 0xB0, 0x12, 0x34, 0x37];
+        let words = super::decode_words::<consts::U3>(&response).unwrap();
+        assert_eq!(&words[..], &[0xBEEF, 0x0001, 0x1234]);
+
+        // the words that don't fit are ignored
+        let words = super::decode_words::<consts::U2>(&response).unwrap();
+        assert_eq!(&words[..], &[0xBEEF, 0x0001]);
+
+        // flip a bit of the second word
+        response[4] ^= 1;
+        match super::decode_words::<consts::U3>(&response) {
+            Err(Error::Checksum { word: 1 }) => {}
+            res => panic!("{:?}", res),
+        }
+    }
+
+    #[test]
+    fn encode() {
+        // SCD30 "start continuous measurement" (0x0010) with an argument of 1013 (0x03F5)
+        assert_eq!(
+            super::encode_write(0x0010, 1013),
+            [0x00, 0x10, 0x03, 0xF5, 0xDB]
+        );
+    }
+}
@@ -3,26 +3,46 @@
 // Based on https://github.com/nrf-rs/nrf52-hal/commit/f05d471996c63f605cab43aa76c8fd990b852460
 
 use core::{
+    cmp,
     future::Future,
     pin::Pin,
     sync::atomic::{self, AtomicBool, Ordering},
     task::{Context, Poll, Waker},
+    time::Duration,
 };
 
+use async_embedded::{
+    task::{self, Either},
+    unsync::{Channel, Mutex},
+};
 use cortex_m::peripheral::NVIC;
+use heapless::{ArrayLength, String, Vec};
 use pac::{Interrupt, UARTE0};
 
-use crate::{BorrowUnchecked as _, NotSync};
+use crate::{
+    gpio::{self, PinState, Port},
+    timer::Timer,
+    token::Token,
+    BorrowUnchecked as _, NotSync,
+};
+
+// default pins; wired to the interface chip on the DK
+const TX_PIN: u8 = 6;
+const RX_PIN: u8 = 8;
 
 // NOTE called from `pre_init`
 pub(crate) fn init() {
     use pac::uarte0::baudrate::BAUDRATE_A;
 
     pac::UARTE0::borrow_unchecked(|uarte| {
-        const TX_PIN: u8 = 6;
-        const RX_PIN: u8 = 8;
         const UARTE_PORT: bool = false; // 0
 
+        // keep the TX line idle (high) while the UARTE is disabled
+        pac::P0::borrow_unchecked(|p0| {
+            p0.outset.write(|w| unsafe { w.bits(1 << TX_PIN) });
+            p0.dirset.write(|w| unsafe { w.bits(1 << TX_PIN) });
+        });
+
         // Select pins
         uarte.psel.rxd.write(|w| unsafe {
             w.pin()
@@ -57,6 +77,80 @@ pub(crate) fn init() {
     });
 }
 
+/// Configuration of the serial interface
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    /// Baud rate; `Baudrate::Baud9600` by default
+    pub baudrate: Baudrate,
+
+    /// Transmit (TXD) pin; P0.06 by default
+    pub tx: gpio::Pin,
+
+    /// Receive (RXD) pin; P0.08 by default
+    pub rx: gpio::Pin,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            baudrate: Baudrate::Baud9600,
+            tx: crate::pin!(0, TX_PIN),
+            rx: crate::pin!(0, RX_PIN),
+        }
+    }
+}
+
+/// Baud rate of the serial interface
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Baudrate {
+    Baud1200,
+    Baud2400,
+    Baud4800,
+    Baud9600,
+    Baud14400,
+    Baud19200,
+    Baud28800,
+    Baud31250,
+    Baud38400,
+    Baud56000,
+    Baud57600,
+    Baud76800,
+    Baud115200,
+    Baud230400,
+    Baud250000,
+    Baud460800,
+    Baud921600,
+    Baud1M,
+}
+
+impl From<Baudrate> for pac::uarte0::baudrate::BAUDRATE_A {
+    fn from(baudrate: Baudrate) -> Self {
+        use pac::uarte0::baudrate::BAUDRATE_A;
+
+        match baudrate {
+            Baudrate::Baud1200 => BAUDRATE_A::BAUD1200,
+            Baudrate::Baud2400 => BAUDRATE_A::BAUD2400,
+            Baudrate::Baud4800 => BAUDRATE_A::BAUD4800,
+            Baudrate::Baud9600 => BAUDRATE_A::BAUD9600,
+            Baudrate::Baud14400 => BAUDRATE_A::BAUD14400,
+            Baudrate::Baud19200 => BAUDRATE_A::BAUD19200,
+            Baudrate::Baud28800 => BAUDRATE_A::BAUD28800,
+            Baudrate::Baud31250 => BAUDRATE_A::BAUD31250,
+            Baudrate::Baud38400 => BAUDRATE_A::BAUD38400,
+            Baudrate::Baud56000 => BAUDRATE_A::BAUD56000,
+            Baudrate::Baud57600 => BAUDRATE_A::BAUD57600,
+            Baudrate::Baud76800 => BAUDRATE_A::BAUD76800,
+            Baudrate::Baud115200 => BAUDRATE_A::BAUD115200,
+            Baudrate::Baud230400 => BAUDRATE_A::BAUD230400,
+            Baudrate::Baud250000 => BAUDRATE_A::BAUD250000,
+            Baudrate::Baud460800 => BAUDRATE_A::BAUD460800,
+            Baudrate::Baud921600 => BAUDRATE_A::BAUD921600,
+            Baudrate::Baud1M => BAUDRATE_A::BAUD1M,
+        }
+    }
+}
+
 const INTERRUPT: Interrupt = Interrupt::UARTE0_UART0;
 
 /// Takes the singleton instance of the serial interface
@@ -86,6 +180,91 @@ pub fn take() -> (Tx, Rx) {
     }
 }
 
+/// Like `take` but first applies the given `config`uration
+///
+/// `take` uses the default configuration (see `Config::default`). Returns an error if one of
+/// the pins can't be used (see `gpio::Pin::check`); in that case the interface is not taken
+pub fn take_with(config: Config) -> Result<(Tx, Rx), gpio::Error> {
+    let tx_pin = config.tx.check()?;
+    let rx_pin = config.rx.check()?;
+
+    let (tx, rx) = take();
+
+    // NOTE no transfer can be in progress before the interface is taken
+    UARTE0::borrow_unchecked(|uarte| {
+        // the pins can only be changed while the peripheral is disabled
+        uarte.enable.write(|w| w.enable().disabled());
+
+        // release the default TX pin
+        Port::P0.clear_outputs(1 << TX_PIN);
+
+        // keep the TX line idle (high) while the UARTE is disabled
+        let port = tx_pin.gpio_port();
+        port.write(tx_pin.pin(), PinState::High);
+        port.set_outputs(1 << tx_pin.pin());
+
+        uarte.psel.txd.write(|w| unsafe {
+            w.pin()
+                .bits(tx_pin.pin())
+                .port()
+                .bit(tx_pin.psel_port())
+                .connect()
+                .connected()
+        });
+        uarte.psel.rxd.write(|w| unsafe {
+            w.pin()
+                .bits(rx_pin.pin())
+                .port()
+                .bit(rx_pin.psel_port())
+                .connect()
+                .connected()
+        });
+
+        uarte
+            .baudrate
+            .write(|w| w.baudrate().variant(config.baudrate.into()));
+
+        uarte.enable.write(|w| w.enable().enabled());
+    });
+
+    Ok((tx, rx))
+}
+
+/// Gives up both halves of the interface in exchange for direct access to the UARTE; see the
+/// `token` module
+pub fn into_token(_tx: Tx, _rx: Rx) -> Token<UARTE0> {
+    Token::new()
+}
+
+/// Gets the interface back from the UARTE token
+pub fn from_token(_token: Token<UARTE0>) -> (Tx, Rx) {
+    (
+        Tx {
+            _not_sync: NotSync::new(),
+        },
+        Rx {
+            _not_sync: NotSync::new(),
+        },
+    )
+}
+
+/// Enables or disables the 9-bit mode used by `Rx::read9` and `Tx::write9`
+///
+/// Taking both halves of the interface ensures that no transfer is in progress
+///
+/// The UARTE has no native 9-bit mode; the 9th bit of each frame is emulated with the parity bit.
+/// While the mode is enabled every frame carries a parity bit so `read` and `write` shouldn't be
+/// used
+pub fn set_nine_bit(_tx: &mut Tx, _rx: &mut Rx, enabled: bool) {
+    NINE_BIT.store(enabled, Ordering::Relaxed);
+    set_config(enabled, false);
+}
+
+static NINE_BIT: AtomicBool = AtomicBool::new(false);
+
+// the transmitter has been started and not stopped since; see `Tx::flush`
+static TX_ACTIVE: AtomicBool = AtomicBool::new(false);
+
 /// [Singleton] Receiver component of the serial interface
 pub struct Rx {
     _not_sync: NotSync,
@@ -93,35 +272,125 @@ pub struct Rx {
 
 impl Rx {
     /// *Completely* fills the given `buffer` with bytes received over the serial interface
+    ///
+    /// Returns an error if the UARTE reported a reception error. In that case only part of `buf`
+    /// has been filled (see `read_partial`) and some of the incoming bytes have been lost. An
+    /// `Overrun` error can also be caused by bytes that arrived *before* this call, while no read
+    /// was in progress; use larger buffers (or read more often) to avoid it
+    pub async fn read(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        self.read_partial(buf).await.map_err(|e| e.error)
+    }
+
+    /// Like `read` but, on error, also reports how many bytes were received before the transfer
+    /// was stopped
+    ///
+    /// The first `received` bytes of `buf` hold those bytes. A framing or parity error doesn't
+    /// discard the offending byte so it can be the last of them
+    pub async fn read_partial(&mut self, buf: &mut [u8]) -> Result<(), PartialRead> {
+        self.read_amount(buf).await.map(drop)
+    }
+
+    /// Receives up to `buf.len()` bytes; returns once `buf` is full or the line has been quiet for
+    /// `idle` after the first byte
+    ///
+    /// Returns the number of bytes received. This waits indefinitely for the first byte
+    ///
+    /// The UARTE can't detect an idle line by itself; the line is checked for new bytes every
+    /// `idle` period (using `timer`) so up to twice `idle` can pass after the last byte before
+    /// this returns. Bytes that arrive while the transfer is being stopped stay in the UARTE and
+    /// are lost
+    pub async fn read_some(
+        &mut self,
+        buf: &mut [u8],
+        timer: &Timer,
+        idle: Duration,
+    ) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // RXDRDY: a byte was received
+        UARTE0::borrow_unchecked(|uarte| uarte.events_rxdrdy.reset());
+
+        let watch = async {
+            let mut started = false;
+            loop {
+                timer.wait(idle).await;
+
+                let received = UARTE0::borrow_unchecked(|uarte| {
+                    let received = uarte.events_rxdrdy.read().bits() != 0;
+                    uarte.events_rxdrdy.reset();
+                    received
+                });
+
+                if received {
+                    started = true;
+                } else if started {
+                    // the line went quiet; the read completes on ENDRX with the bytes received so
+                    // far
+                    UARTE0::borrow_unchecked(|uarte| {
+                        uarte.tasks_stoprx.write(|w| unsafe { w.bits(1) })
+                    });
+                }
+            }
+        };
+
+        let res = match task::select(self.read_amount(buf), watch).await {
+            Either::Left(res) => res.map_err(|e| e.error),
+            Either::Right(()) => unreachable!(),
+        };
+        // produced by the STOPRX task
+        UARTE0::borrow_unchecked(|uarte| uarte.events_rxto.reset());
+        res
+    }
+
+    // Like `read_partial` but returns the number of bytes received; less than `buf.len()` if the
+    // transfer was stopped
     // XXX(Soundness?) The following operation is potentially unsound: `buf`
     // points into RAM; the future returned by this method is `poll`-ed once and
     // then `mem::forget`-ed (forgotten). This lets the caller return from the
     // current stack frame, freeing `buf`: now the DMA can overwrite the stack
     // frames of the program
-    // TODO bubble up errors
-    pub async fn read(&mut self, buf: &mut [u8]) {
+    async fn read_amount(&mut self, buf: &mut [u8]) -> Result<usize, PartialRead> {
         struct Read<'t, 'b> {
             _rx: &'t mut Rx,
             buf: &'b mut [u8],
             state: State,
+            // error reported while the transfer was in progress; the transfer has been stopped
+            // but the DMA may still own `buf` until ENDRX
+            error: Option<Error>,
         }
 
         impl Future for Read<'_, '_> {
-            type Output = ();
+            type Output = Result<usize, PartialRead>;
 
-            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            fn poll(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Result<usize, PartialRead>> {
                 match self.state {
                     // nothing to do
                     State::NotStarted if self.buf.len() == 0 => {
                         self.state = State::Finished;
 
-                        Poll::Ready(())
+                        Poll::Ready(Ok(0))
                     }
 
                     State::NotStarted => {
+                        // data lost between the previous read and this one
+                        if let Some(error) = take_error() {
+                            self.state = State::Finished;
+
+                            return Poll::Ready(Err(PartialRead { error, received: 0 }));
+                        }
+
                         UARTE0::borrow_unchecked(|uarte| {
+                            // the peripheral may have been disabled by `power::LowPower`
+                            uarte.enable.write(|w| w.enable().enabled());
+
                             // reset events
                             uarte.events_endrx.reset();
+                            uarte.events_error.reset();
 
                             uarte
                                 .rxd
@@ -139,6 +408,9 @@ impl Rx {
                                 // NOTE(compiler_fence) writing the waker must
                                 // complete before the interrupt is unmasked
                                 atomic::compiler_fence(Ordering::Release);
+                                // NOTE the error interrupt is only enabled while a read is in
+                                // progress; otherwise, errors would keep waking up the TX task
+                                uarte.intenset.write(|w| w.error().set_bit());
                                 NVIC::unmask(INTERRUPT);
                             }
 
@@ -166,6 +438,7 @@ impl Rx {
 
                                 // uninstall the waker
                                 NVIC::mask(INTERRUPT);
+                                uarte.intenclr.write(|w| w.error().set_bit());
                                 // NOTE(compiler_fence) the interrupt must be
                                 // disabled before we take down the waker
                                 atomic::compiler_fence(Ordering::SeqCst);
@@ -177,8 +450,27 @@ impl Rx {
                                     }
                                 }
 
-                                Poll::Ready(())
+                                // an error may have been raised right before ENDRX
+                                let error = self.error.take().or_else(take_error);
+                                if let Some(error) = error {
+                                    // the STOPRX task also produces this event
+                                    uarte.events_rxto.reset();
+
+                                    let received = uarte.rxd.amount.read().bits() as usize;
+                                    Poll::Ready(Err(PartialRead { error, received }))
+                                } else {
+                                    Poll::Ready(Ok(uarte.rxd.amount.read().bits() as usize))
+                                }
                             } else {
+                                if self.error.is_none() {
+                                    if let Some(e) = take_error() {
+                                        // stop the transfer; ENDRX will be raised when the DMA
+                                        // releases the buffer
+                                        uarte.tasks_stoprx.write(|w| unsafe { w.bits(1) });
+                                        self.error = Some(e);
+                                    }
+                                }
+
                                 // spurious wake up; re-arm the one-shot interrupt
                                 unsafe {
                                     NVIC::unmask(INTERRUPT);
@@ -194,11 +486,40 @@ impl Rx {
             }
         }
 
+        // NOTE a `Read` future can be dropped before it completes, e.g. when it loses a `select`
+        // against a timeout. The DMA must release `buf` before it's freed
         impl Drop for Read<'_, '_> {
             fn drop(&mut self) {
                 if self.state == State::InProgress {
-                    // stop the transfer
-                    todo!()
+                    UARTE0::borrow_unchecked(|uarte| {
+                        // uninstall the waker
+                        NVIC::mask(INTERRUPT);
+                        uarte.intenclr.write(|w| w.error().set_bit());
+                        // NOTE(compiler_fence) the interrupt must be disabled before we take
+                        // down the waker
+                        atomic::compiler_fence(Ordering::SeqCst);
+                        drop(unsafe { RX_WAKER.take() });
+                        unsafe {
+                            // the TX waker may still need to be serviced
+                            if TX_WAKER.is_some() {
+                                NVIC::unmask(INTERRUPT);
+                            }
+                        }
+
+                        // stop the transfer; this is a no-op if ENDRX has already been raised
+                        uarte.tasks_stoprx.write(|w| unsafe { w.bits(1) });
+                        while uarte.events_endrx.read().bits() == 0 {
+                            continue;
+                        }
+                        uarte.events_endrx.reset();
+                        uarte.events_rxto.reset();
+                        // errors belong to the cancelled transfer
+                        let _ = take_error();
+
+                        // NOTE(compiler_fence) the DMA has released `buf`; reads of it must not
+                        // be reordered before this point
+                        atomic::compiler_fence(Ordering::Acquire);
+                    });
                 }
             }
         }
@@ -206,12 +527,45 @@ impl Rx {
         // TODO for large buffers do transfers in chunks
         assert!(buf.len() < (1 << 10));
 
-        Read {
+        let res = Read {
             _rx: self,
             buf,
             state: State::NotStarted,
+            error: None,
         }
-        .await
+        .await;
+        trace!(crate::trace::EventId::SerialRx);
+        res
+    }
+
+    /// *Completely* fills `buf` with 9-bit words received over the serial interface
+    ///
+    /// Bit 8 of each word is the 9th bit of the frame (e.g. the address mark of a multidrop
+    /// bus). Panics if the 9-bit mode has not been enabled (see `set_nine_bit`)
+    ///
+    /// The receiver checks the even parity of each byte; the 9th bit is recovered from the
+    /// presence or absence of a parity error. This relies on the UARTE storing bytes that fail the
+    /// parity check. Words are received one at a time so this is only suitable for low baud rates.
+    /// The parity type is shared with the transmitter so this must not run concurrently with
+    /// `write9`
+    pub async fn read9(&mut self, buf: &mut [u16]) -> Result<(), Error> {
+        assert!(
+            NINE_BIT.load(Ordering::Relaxed),
+            "9-bit mode has not been enabled"
+        );
+
+        for word in buf {
+            let mut byte = [0];
+            let ninth = match self.read(&mut byte).await {
+                Ok(()) => even_parity(byte[0]),
+                // the 9th bit doesn't match the even parity of the byte
+                Err(Error::Parity) => !even_parity(byte[0]),
+                Err(e) => return Err(e),
+            };
+            *word = decode9(byte[0], ninth);
+        }
+
+        Ok(())
     }
 }
 
@@ -222,12 +576,17 @@ pub struct Tx {
 
 impl Tx {
     /// Sends *all* `bytes` over the serial interface
+    ///
+    /// This completes when the DMA has moved the last byte into the UARTE (`ENDTX`); at that
+    /// point the last byte is still being shifted out. Use `flush` to wait until the line is idle
     // NOTE like with `read`, starting a `write` on a `bytes` that points into
     // the stack, `poll`-ing the future and then `mem::forget`-ing it is a Bad
     // Thing To Do. This operation is not unsound on the device side but will
     // sent junk through the serial interface
     // TODO bubble up errors
     pub async fn write(&mut self, bytes: &[u8]) {
+        trace!(crate::trace::EventId::SerialTx);
+
         if crate::slice_in_ram(bytes) {
             self.write_from_ram(bytes).await
         } else {
@@ -241,6 +600,185 @@ impl Tx {
         }
     }
 
+    /// Waits until the last byte written has been completely shifted out and the line is idle
+    ///
+    /// `write` completes on `ENDTX`, when the DMA has moved the last byte into the UARTE. The
+    /// UARTE then needs up to one more frame (~1.1 ms at 9600 bps) to put it on the line. Flush
+    /// before, e.g., reconfiguring the pins or cutting the power of the transceiver. This
+    /// completes immediately if nothing has been written since the last flush
+    pub async fn flush(&mut self) {
+        struct Flush<'t> {
+            _tx: &'t mut Tx,
+            state: State,
+        }
+
+        impl Future for Flush<'_> {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                match self.state {
+                    // nothing to do
+                    State::NotStarted if !TX_ACTIVE.load(Ordering::Relaxed) => {
+                        self.state = State::Finished;
+
+                        Poll::Ready(())
+                    }
+
+                    State::NotStarted => {
+                        UARTE0::borrow_unchecked(|uarte| {
+                            uarte.events_txstopped.reset();
+
+                            // install the waker
+                            NVIC::mask(INTERRUPT);
+                            unsafe {
+                                TX_WAKER = Some(cx.waker().clone());
+                                // NOTE(compiler_fence) writing the waker must
+                                // complete before the interrupt is unmasked
+                                atomic::compiler_fence(Ordering::Release);
+                                uarte.intenset.write(|w| w.txstopped().set_bit());
+                                NVIC::unmask(INTERRUPT);
+                            }
+
+                            // STOPTX lets the byte in the shift register go out before raising
+                            // TXSTOPPED
+                            uarte.tasks_stoptx.write(|w| unsafe { w.bits(1) });
+                        });
+
+                        self.state = State::InProgress;
+
+                        Poll::Pending
+                    }
+
+                    State::InProgress => UARTE0::borrow_unchecked(|uarte| {
+                        if uarte.events_txstopped.read().bits() != 0 {
+                            uarte.events_txstopped.reset();
+                            TX_ACTIVE.store(false, Ordering::Relaxed);
+
+                            self.state = State::Finished;
+
+                            // uninstall the waker
+                            NVIC::mask(INTERRUPT);
+                            uarte.intenclr.write(|w| w.txstopped().set_bit());
+                            // NOTE(compiler_fence) the interrupt must be
+                            // disabled before we take down the waker
+                            atomic::compiler_fence(Ordering::SeqCst);
+                            drop(unsafe { TX_WAKER.take() });
+                            unsafe {
+                                // the RX waker may still need to be serviced
+                                if RX_WAKER.is_some() {
+                                    NVIC::unmask(INTERRUPT);
+                                }
+                            }
+
+                            Poll::Ready(())
+                        } else {
+                            // spurious wake up; re-arm the one-shot interrupt
+                            unsafe {
+                                NVIC::unmask(INTERRUPT);
+                            }
+
+                            Poll::Pending
+                        }
+                    }),
+
+                    State::Finished => unreachable!(),
+                }
+            }
+        }
+
+        // NOTE the transmitter is stopping; finishing that takes at most one frame so we busy wait
+        impl Drop for Flush<'_> {
+            fn drop(&mut self) {
+                if self.state == State::InProgress {
+                    UARTE0::borrow_unchecked(|uarte| {
+                        // uninstall the waker
+                        NVIC::mask(INTERRUPT);
+                        uarte.intenclr.write(|w| w.txstopped().set_bit());
+                        // NOTE(compiler_fence) the interrupt must be disabled before we take
+                        // down the waker
+                        atomic::compiler_fence(Ordering::SeqCst);
+                        drop(unsafe { TX_WAKER.take() });
+                        unsafe {
+                            // the RX waker may still need to be serviced
+                            if RX_WAKER.is_some() {
+                                NVIC::unmask(INTERRUPT);
+                            }
+                        }
+
+                        while uarte.events_txstopped.read().bits() == 0 {}
+                        uarte.events_txstopped.reset();
+                        TX_ACTIVE.store(false, Ordering::Relaxed);
+                    });
+                }
+            }
+        }
+
+        Flush {
+            _tx: self,
+            state: State::NotStarted,
+        }
+        .await
+    }
+
+    /// Sends *all* `words` over the serial interface as 9-bit frames
+    ///
+    /// Bit 8 of each word is sent as the 9th bit of the frame (e.g. the address mark of a
+    /// multidrop bus). Panics if the 9-bit mode has not been enabled (see `set_nine_bit`) or if a
+    /// word doesn't fit in 9 bits
+    ///
+    /// The 9th bit is sent as the parity bit: the parity type (even / odd) is switched whenever
+    /// the 9th bit of a word doesn't match the even parity of its lower 8 bits. Each switch
+    /// stops the transmitter, leaving a gap on the line. This needs the `PARITYTYPE` field of the
+    /// `CONFIG` register, which is missing on older silicon; on those devices such words are sent
+    /// with the wrong 9th bit
+    pub async fn write9(&mut self, words: &[u16]) {
+        assert!(
+            NINE_BIT.load(Ordering::Relaxed),
+            "9-bit mode has not been enabled"
+        );
+
+        const BUFSZ: usize = 32;
+        let mut buf = [0; BUFSZ];
+        let mut n = 0;
+        // `set_nine_bit` selects even parity
+        let mut odd = false;
+        let mut sent = false;
+        for &word in words {
+            let (byte, ninth) = encode9(word);
+            let needs_odd = ninth != even_parity(byte);
+
+            if n != 0 && (needs_odd != odd || n == BUFSZ) {
+                self.write_from_ram(&buf[..n]).await;
+                n = 0;
+                sent = true;
+            }
+
+            if needs_odd != odd {
+                if sent {
+                    drain_tx();
+                }
+                set_config(true, needs_odd);
+                odd = needs_odd;
+            }
+
+            buf[n] = byte;
+            n += 1;
+        }
+
+        if n != 0 {
+            self.write_from_ram(&buf[..n]).await;
+            sent = true;
+        }
+
+        // leave the receiver with the parity type `read9` expects
+        if odd {
+            if sent {
+                drain_tx();
+            }
+            set_config(true, false);
+        }
+    }
+
     // `bytes` has already been checked to point into RAM
     async fn write_from_ram(&mut self, bytes: &[u8]) {
         struct Write<'t, 'b> {
@@ -263,6 +801,9 @@ impl Tx {
 
                     State::NotStarted => {
                         UARTE0::borrow_unchecked(|uarte| {
+                            // the peripheral may have been disabled by `power::LowPower`
+                            uarte.enable.write(|w| w.enable().enabled());
+
                             // reset events
                             uarte.events_endtx.reset();
 
@@ -292,6 +833,7 @@ impl Tx {
                             // the compiler fence -- but it's redundant because
                             // of the preceding barrier
                             atomic::compiler_fence(Ordering::Release);
+                            TX_ACTIVE.store(true, Ordering::Relaxed);
                             uarte.tasks_starttx.write(|w| unsafe { w.bits(1) });
                         });
 
@@ -337,11 +879,40 @@ impl Tx {
             }
         }
 
+        // NOTE a `Write` future can be dropped before it completes, e.g. when it loses a
+        // `select` against a timeout. The DMA must release `bytes` before they are freed
         impl Drop for Write<'_, '_> {
             fn drop(&mut self) {
                 if self.state == State::InProgress {
-                    // stop the transfer
-                    todo!()
+                    UARTE0::borrow_unchecked(|uarte| {
+                        // uninstall the waker
+                        NVIC::mask(INTERRUPT);
+                        // NOTE(compiler_fence) the interrupt must be disabled before we take
+                        // down the waker
+                        atomic::compiler_fence(Ordering::SeqCst);
+                        drop(unsafe { TX_WAKER.take() });
+                        unsafe {
+                            // the RX waker may still need to be serviced
+                            if RX_WAKER.is_some() {
+                                NVIC::unmask(INTERRUPT);
+                            }
+                        }
+
+                        // stop the transfer; the byte on the line, if any, goes out before
+                        // TXSTOPPED is raised so this takes at most one frame
+                        uarte.events_txstopped.reset();
+                        uarte.tasks_stoptx.write(|w| unsafe { w.bits(1) });
+                        while uarte.events_txstopped.read().bits() == 0 {
+                            continue;
+                        }
+                        uarte.events_txstopped.reset();
+                        uarte.events_endtx.reset();
+                        TX_ACTIVE.store(false, Ordering::Relaxed);
+
+                        // NOTE(compiler_fence) the DMA has released `bytes`; accesses to them
+                        // must not be reordered before this point
+                        atomic::compiler_fence(Ordering::Acquire);
+                    });
                 }
             }
         }
@@ -358,9 +929,261 @@ impl Tx {
     }
 }
 
+/// Reads frames that start with their length, as a little endian `u16`, followed by the payload
+pub struct LengthPrefixedReader {
+    rx: Rx,
+}
+
+/// Error returned by `LengthPrefixedReader::read_frame`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameError {
+    /// The frame doesn't fit in the buffer; it has been discarded
+    Oversize {
+        /// Length of the discarded frame
+        len: usize,
+    },
+
+    /// Reception error; the reader may no longer be at a frame boundary
+    Serial(Error),
+}
+
+impl From<Error> for FrameError {
+    fn from(e: Error) -> Self {
+        FrameError::Serial(e)
+    }
+}
+
+// NOTE `Rx::read` can't do larger transfers
+const CHUNK: usize = 512;
+
+impl LengthPrefixedReader {
+    /// Wraps the receiver
+    pub fn new(rx: Rx) -> Self {
+        Self { rx }
+    }
+
+    /// Reads the next frame into `buf` and returns the length of its payload
+    ///
+    /// If the payload is larger than `buf` it's read and discarded, so that the next call starts at
+    /// the next frame, and `FrameError::Oversize` is returned
+    pub async fn read_frame(&mut self, buf: &mut [u8]) -> Result<usize, FrameError> {
+        let mut header = [0; 2];
+        self.rx.read(&mut header).await?;
+        let len = usize::from(u16::from_le_bytes(header));
+
+        if len > buf.len() {
+            let mut scratch = [0; CHUNK];
+            let mut left = len;
+            while left != 0 {
+                let n = cmp::min(left, CHUNK);
+                self.rx.read(&mut scratch[..n]).await?;
+                left -= n;
+            }
+
+            return Err(FrameError::Oversize { len });
+        }
+
+        for chunk in buf[..len].chunks_mut(CHUNK) {
+            self.rx.read(chunk).await?;
+        }
+
+        Ok(len)
+    }
+
+    /// Returns the wrapped receiver
+    pub fn into_inner(self) -> Rx {
+        self.rx
+    }
+}
+
+/// Writes frames that start with their length, as a little endian `u16`, followed by the payload
+///
+/// The counterpart of `LengthPrefixedReader`
+pub struct LengthPrefixedWriter {
+    tx: Tx,
+}
+
+impl LengthPrefixedWriter {
+    /// Wraps the transmitter
+    pub fn new(tx: Tx) -> Self {
+        Self { tx }
+    }
+
+    /// Sends `payload` as a single frame
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `payload` is longer than 65,535 bytes
+    pub async fn write_frame(&mut self, payload: &[u8]) {
+        assert!(
+            payload.len() <= usize::from(u16::max_value()),
+            "frame is too long"
+        );
+
+        self.tx.write(&(payload.len() as u16).to_le_bytes()).await;
+        for chunk in payload.chunks(CHUNK) {
+            self.tx.write(chunk).await;
+        }
+    }
+
+    /// Returns the wrapped transmitter
+    pub fn into_inner(self) -> Tx {
+        self.tx
+    }
+}
+
+/// Reads lines of text terminated by a carriage return (`\r`) or a line feed (`\n`)
+pub struct LineReader {
+    rx: Rx,
+}
+
+/// Error returned by `LineReader::read_line`; in all cases the line has been discarded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineError {
+    /// The line doesn't fit in the buffer
+    Oversize,
+
+    /// The line is not valid UTF-8
+    Utf8,
+
+    /// Reception error
+    Serial(Error),
+}
+
+impl LineReader {
+    /// Wraps the receiver
+    pub fn new(rx: Rx) -> Self {
+        Self { rx }
+    }
+
+    /// Reads the next line into `line`, without its terminator
+    ///
+    /// Empty lines are skipped so a `\r\n` terminator is handled as a single one. When an error
+    /// occurs the rest of the line is read and discarded, so that the next call starts at the next
+    /// line, before the error is returned
+    pub async fn read_line<N>(&mut self, line: &mut String<N>) -> Result<(), LineError>
+    where
+        N: ArrayLength<u8>,
+    {
+        let mut bytes = Vec::<u8, N>::new();
+        let mut error = None;
+
+        loop {
+            let mut byte = [0];
+            if let Err(e) = self.rx.read(&mut byte).await {
+                error = Some(LineError::Serial(e));
+                continue;
+            }
+
+            match byte[0] {
+                b'\r' | b'\n' => {
+                    if let Some(e) = error {
+                        return Err(e);
+                    }
+
+                    if !bytes.is_empty() {
+                        break;
+                    }
+                }
+
+                byte => {
+                    if error.is_none() && bytes.push(byte).is_err() {
+                        error = Some(LineError::Oversize);
+                    }
+                }
+            }
+        }
+
+        *line = String::from_utf8(bytes).map_err(|_| LineError::Utf8)?;
+        Ok(())
+    }
+
+    /// Returns the wrapped receiver
+    pub fn into_inner(self) -> Rx {
+        self.rx
+    }
+}
+
+/// Reads lines from `rx` and sends them into `out`; returns when `out` is closed
+///
+/// Lines that can't be read (see `LineError`) are dropped. This is meant to be `spawn`-ed as a
+/// task; consumers just need to `recv` lines from `out`
+pub async fn pump_lines<N, M>(rx: Rx, out: &Channel<String<N>, M>)
+where
+    N: ArrayLength<u8>,
+    M: generic_array::ArrayLength<String<N>>,
+{
+    let mut reader = LineReader::new(rx);
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.is_ok() {
+            if out.send(line).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Periodically sends a message so that a host can tell the device is still running
+///
+/// The transmitter is shared with other tasks through a `Mutex`; it's only locked while the
+/// message is being sent
+pub struct Beacon<'a> {
+    tx: &'a Mutex<Tx>,
+    message: &'a [u8],
+    interval: Duration,
+}
+
+impl<'a> Beacon<'a> {
+    /// Creates a beacon that sends `message` every `interval`
+    pub fn new(tx: &'a Mutex<Tx>, message: &'a [u8], interval: Duration) -> Self {
+        Self {
+            tx,
+            message,
+            interval,
+        }
+    }
+
+    /// Sends the message, sleeping on `timer` in between; never returns
+    ///
+    /// This is meant to be `spawn`-ed as a task
+    pub async fn run(self, timer: &mut Timer) {
+        let mut next = Timer::now();
+        loop {
+            self.tx.lock().await.write(self.message).await;
+
+            // NOTE sleep until an absolute deadline so that the time spent waiting for the
+            // transmitter doesn't make the beacon drift
+            next = next + self.interval;
+            let now = Timer::now();
+            if next > now {
+                timer.wait(next - now).await;
+            } else {
+                // fell behind; skip the missed beacons
+                next = now;
+            }
+        }
+    }
+}
+
 static mut RX_WAKER: Option<Waker> = None;
 static mut TX_WAKER: Option<Waker> = None;
 
+// NOTE(unsafe) the wakers are only modified from thread mode
+pub(crate) fn is_idle() -> bool {
+    unsafe { RX_WAKER.is_none() && TX_WAKER.is_none() }
+}
+
+// NOTE the next transfer re-enables the peripheral
+pub(crate) fn disable() {
+    // don't cut off the last byte written
+    if TX_ACTIVE.load(Ordering::Relaxed) {
+        drain_tx();
+    }
+
+    UARTE0::borrow_unchecked(|uarte| uarte.enable.write(|w| w.enable().disabled()));
+}
+
 #[allow(non_snake_case)]
 #[no_mangle]
 fn UARTE0_UART0() {
@@ -383,6 +1206,103 @@ fn UARTE0_UART0() {
     }
 }
 
+// CONFIG bits: 0 = HWFC, 1..=3 = PARITY (0b111 = included), 8 = PARITYTYPE (1 = odd)
+fn set_config(parity: bool, odd: bool) {
+    let mut bits = 0;
+    if parity {
+        bits |= 0b111 << 1;
+    }
+    if odd {
+        bits |= 1 << 8;
+    }
+
+    UARTE0::borrow_unchecked(|uarte| uarte.config.write(|w| unsafe { w.bits(bits) }));
+}
+
+// Waits until the last byte has left the transmitter
+//
+// NOTE ENDTX only means that the DMA has moved all the bytes into the UARTE; the last one may
+// still be on the line. STOPTX lets it go out before raising TXSTOPPED. This takes at most one
+// frame (~1.1 ms at 9600 bps) so we busy wait
+fn drain_tx() {
+    UARTE0::borrow_unchecked(|uarte| {
+        uarte.events_txstopped.reset();
+        uarte.tasks_stoptx.write(|w| unsafe { w.bits(1) });
+        while uarte.events_txstopped.read().bits() == 0 {}
+        uarte.events_txstopped.reset();
+    });
+    TX_ACTIVE.store(false, Ordering::Relaxed);
+}
+
+// Value of the parity bit for `byte` when the UARTE is configured for even parity
+fn even_parity(byte: u8) -> bool {
+    byte.count_ones() % 2 == 1
+}
+
+// Splits a 9-bit word into its lower 8 bits and its 9th bit
+fn encode9(word: u16) -> (u8, bool) {
+    assert!(word < 1 << 9, "word doesn't fit in 9 bits");
+
+    (word as u8, word & (1 << 8) != 0)
+}
+
+fn decode9(byte: u8, ninth: bool) -> u16 {
+    u16::from(ninth) << 8 | u16::from(byte)
+}
+
+// Reads and clears the error source register
+fn take_error() -> Option<Error> {
+    UARTE0::borrow_unchecked(|uarte| {
+        let src = uarte.errorsrc.read().bits();
+        if src == 0 {
+            return None;
+        }
+
+        // write-1-to-clear register
+        uarte.errorsrc.write(|w| unsafe { w.bits(src) });
+        uarte.events_error.reset();
+
+        // ERRORSRC bits: 0 = OVERRUN, 1 = PARITY, 2 = FRAMING, 3 = BREAK
+        Some(if src & (1 << 0) != 0 {
+            Error::Overrun
+        } else if src & (1 << 1) != 0 {
+            Error::Parity
+        } else if src & (1 << 2) != 0 {
+            Error::Framing
+        } else {
+            Error::Break
+        })
+    })
+}
+
+/// Reception error reported by `Rx::read_partial`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PartialRead {
+    /// The error
+    pub error: Error,
+
+    /// Number of bytes received before the transfer was stopped
+    pub received: usize,
+}
+
+/// Serial reception error
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// A byte was received before the previous one was read out; at least one byte was lost
+    ///
+    /// The hardware does not report how many bytes were lost
+    Overrun,
+
+    /// A byte with the wrong parity was received
+    Parity,
+
+    /// A byte without a valid stop bit was received
+    Framing,
+
+    /// The RX line was held low for longer than one frame
+    Break,
+}
+
 #[derive(Clone, Copy, PartialEq)]
 enum State {
     NotStarted,
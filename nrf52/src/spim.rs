@@ -0,0 +1,484 @@
+//! Serial Peripheral Interface master
+//!
+//! The SPIM uses the `Serial2` instance by default; `Serial0` is shared with the `twim` module and
+//! only one of the two drivers can use it (see the `instance` module). The driver doesn't manage
+//! any chip select line; drive it with a regular GPIO
+
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{self, AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use cortex_m::peripheral::NVIC;
+use pac::{spim0::RegisterBlock, SPIM0, SPIM1, SPIM2};
+
+use crate::{
+    gpio::{self, PinState},
+    instance::{self, InUse, Owner, Serial},
+    BorrowUnchecked as _, NotSync,
+};
+
+// default pins (SCK, MOSI, MISO); the SPI pins of the Arduino header on the DK
+#[cfg(feature = "nrf52840")]
+const DEFAULT_PINS: (gpio::Pin, gpio::Pin, gpio::Pin) =
+    (crate::pin!(1, 15), crate::pin!(1, 13), crate::pin!(1, 14));
+// NOTE the nRF52833 doesn't have P1.13 - P1.15
+#[cfg(any(feature = "nrf52833", feature = "nrf52832"))]
+const DEFAULT_PINS: (gpio::Pin, gpio::Pin, gpio::Pin) =
+    (crate::pin!(0, 25), crate::pin!(0, 23), crate::pin!(0, 24));
+
+// largest transfer EasyDMA can do on all the supported chips
+const MAX_TRANSFER: usize = 255;
+
+/// Clock frequency
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Frequency {
+    /// 125 kbps
+    K125,
+    /// 250 kbps
+    K250,
+    /// 500 kbps
+    K500,
+    /// 1 Mbps (default)
+    M1,
+    /// 2 Mbps
+    M2,
+    /// 4 Mbps
+    M4,
+    /// 8 Mbps
+    M8,
+}
+
+impl Frequency {
+    fn bits(self) -> u32 {
+        match self {
+            Frequency::K125 => 0x0200_0000,
+            Frequency::K250 => 0x0400_0000,
+            Frequency::K500 => 0x0800_0000,
+            Frequency::M1 => 0x1000_0000,
+            Frequency::M2 => 0x2000_0000,
+            Frequency::M4 => 0x4000_0000,
+            Frequency::M8 => 0x8000_0000,
+        }
+    }
+}
+
+/// Clock polarity and phase
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// CPOL = 0 (SCK idles low), CPHA = 0 (sample on the leading edge) (default)
+    Mode0,
+    /// CPOL = 0, CPHA = 1 (sample on the trailing edge)
+    Mode1,
+    /// CPOL = 1 (SCK idles high), CPHA = 0
+    Mode2,
+    /// CPOL = 1, CPHA = 1
+    Mode3,
+}
+
+impl Mode {
+    fn idles_high(self) -> bool {
+        self == Mode::Mode2 || self == Mode::Mode3
+    }
+
+    // CONFIG value; MSB first
+    fn config(self) -> u32 {
+        // CONFIG bits: 0 = ORDER (0 = MSB first), 1 = CPHA, 2 = CPOL
+        match self {
+            Mode::Mode0 => 0b000,
+            Mode::Mode1 => 0b010,
+            Mode::Mode2 => 0b100,
+            Mode::Mode3 => 0b110,
+        }
+    }
+}
+
+/// SPIM configuration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    /// Clock pin
+    pub sck: gpio::Pin,
+
+    /// Data out pin
+    pub mosi: gpio::Pin,
+
+    /// Data in pin
+    pub miso: gpio::Pin,
+
+    /// Clock frequency
+    pub frequency: Frequency,
+
+    /// Clock polarity and phase
+    pub mode: Mode,
+
+    /// SERIAL instance
+    pub instance: Serial,
+}
+
+impl Default for Config {
+    /// The SPI pins of the Arduino header on the DK; 1 Mbps; mode 0; `Serial2`
+    fn default() -> Self {
+        let (sck, mosi, miso) = DEFAULT_PINS;
+
+        Self {
+            sck,
+            mosi,
+            miso,
+            frequency: Frequency::M1,
+            mode: Mode::Mode0,
+            instance: Serial::Serial2,
+        }
+    }
+}
+
+/// Error returned by `Spim::take_with`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// One of the pins can't be used
+    Pin(gpio::Error),
+
+    /// The SERIAL instance is in use by another driver
+    InUse,
+}
+
+impl From<gpio::Error> for Error {
+    fn from(e: gpio::Error) -> Self {
+        Error::Pin(e)
+    }
+}
+
+impl From<InUse> for Error {
+    fn from(_: InUse) -> Self {
+        Error::InUse
+    }
+}
+
+/// [singleton] An `async`-aware SPI master
+pub struct Spim {
+    serial: Serial,
+    _not_sync: NotSync,
+}
+
+impl Spim {
+    /// Takes the singleton instance of this SPI master, with the default configuration (see
+    /// `Config::default`)
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the SPI master has already been taken
+    pub fn take() -> Self {
+        match Self::take_with(Config::default()) {
+            Ok(spim) => spim,
+            // NOTE the default pins can't be used as NFC pins and no other driver uses `Serial2`
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Like `take` but applies the given `config`uration
+    ///
+    /// Returns an error if one of the pins can't be used (see `gpio::Pin::check`) or if the
+    /// SERIAL instance is in use by another driver; in that case the SPI master is not taken
+    pub fn take_with(config: Config) -> Result<Self, Error> {
+        let sck = config.sck.check()?;
+        let mosi = config.mosi.check()?;
+        let miso = config.miso.check()?;
+        let serial = config.instance;
+
+        instance::claim(serial, Owner::Spim)?;
+
+        static TAKEN: AtomicBool = AtomicBool::new(false);
+
+        if TAKEN
+            .compare_exchange_weak(false, true, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            panic!("`Spim` has already been taken")
+        }
+
+        // the outputs keep their idle level while the SPIM is disabled; MISO is left as an input
+        let idle = if config.mode.idles_high() {
+            PinState::High
+        } else {
+            PinState::Low
+        };
+        sck.gpio_port().write(sck.pin(), idle);
+        sck.gpio_port().set_outputs(1 << sck.pin());
+        mosi.gpio_port().write(mosi.pin(), PinState::Low);
+        mosi.gpio_port().set_outputs(1 << mosi.pin());
+
+        // NOTE no transfer can be in progress before the SPIM is taken
+        borrow(serial, |spim| {
+            // the TWIM is enabled after reset (see `twim::init`)
+            spim.enable.write(|w| w.enable().disabled());
+
+            // INTEN: bit 6 = END; clear the TWIM interrupts too
+            spim.intenclr.write(|w| unsafe { w.bits(!0) });
+            spim.intenset.write(|w| unsafe { w.bits(1 << 6) });
+            // over-read character; clocked out once the TX buffer is exhausted
+            spim.orc.write(|w| unsafe { w.bits(0xff) });
+            spim.psel.sck.write(|w| unsafe {
+                w.pin()
+                    .bits(sck.pin())
+                    .port()
+                    .bit(sck.psel_port())
+                    .connect()
+                    .connected()
+            });
+            spim.psel.mosi.write(|w| unsafe {
+                w.pin()
+                    .bits(mosi.pin())
+                    .port()
+                    .bit(mosi.psel_port())
+                    .connect()
+                    .connected()
+            });
+            spim.psel.miso.write(|w| unsafe {
+                w.pin()
+                    .bits(miso.pin())
+                    .port()
+                    .bit(miso.psel_port())
+                    .connect()
+                    .connected()
+            });
+            spim.frequency
+                .write(|w| unsafe { w.bits(config.frequency.bits()) });
+            spim.config
+                .write(|w| unsafe { w.bits(config.mode.config()) });
+            spim.enable.write(|w| w.enable().enabled());
+        });
+
+        Ok(Self {
+            serial,
+            _not_sync: NotSync::new(),
+        })
+    }
+
+    /// Sends the contents of `buf` and overwrites them with the bytes received at the same time
+    ///
+    /// Buffers larger than 255 bytes are sent in several DMA transfers, back to back
+    pub async fn transfer(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(MAX_TRANSFER) {
+            let (ptr, len) = (chunk.as_mut_ptr(), chunk.len());
+            // NOTE the DMA reads each byte out of the buffer before it writes the received byte
+            // into the same position
+            self.dma(ptr, len, ptr, len).await;
+        }
+    }
+
+    /// Sends `bytes`, discarding the received bytes
+    ///
+    /// Buffers larger than 255 bytes are sent in several DMA transfers, back to back
+    pub async fn write(&mut self, bytes: &[u8]) {
+        let mut buf = [0; MAX_TRANSFER];
+        for chunk in bytes.chunks(MAX_TRANSFER) {
+            let chunk = if crate::slice_in_ram(chunk) {
+                chunk
+            } else {
+                // EasyDMA can only read from RAM
+                let n = chunk.len();
+                buf[..n].copy_from_slice(chunk);
+                &buf[..n]
+            };
+
+            self.dma(chunk.as_ptr(), chunk.len(), buf.as_mut_ptr(), 0)
+                .await;
+        }
+    }
+
+    // Runs a single DMA transfer
+    //
+    // NOTE the caller must keep both buffers alive (and the TX buffer in RAM) until the returned
+    // future completes or is dropped
+    async fn dma(&mut self, tx: *const u8, tx_len: usize, rx: *mut u8, rx_len: usize) {
+        struct Transfer<'t> {
+            spim: &'t mut Spim,
+            tx: *const u8,
+            tx_len: usize,
+            rx: *mut u8,
+            rx_len: usize,
+            state: State,
+        }
+
+        impl Future for Transfer<'_> {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                match self.state {
+                    State::NotStarted => {
+                        let interrupt = self.spim.serial.interrupt();
+                        borrow(self.spim.serial, |spim| {
+                            NVIC::mask(interrupt);
+
+                            // NOTE program defensively: the user could poll a `Transfer` future
+                            // once (and start the DMA transfer) and then `mem::forget` it. We
+                            // cannot assume any `async` method was driven to completion
+                            if spim.events_started.read().bits() != 0 {
+                                abort(spim);
+                            }
+
+                            spim.txd.ptr.write(|w| unsafe { w.bits(self.tx as u32) });
+                            spim.txd
+                                .maxcnt
+                                .write(|w| unsafe { w.bits(self.tx_len as u32) });
+                            spim.rxd.ptr.write(|w| unsafe { w.bits(self.rx as u32) });
+                            spim.rxd
+                                .maxcnt
+                                .write(|w| unsafe { w.bits(self.rx_len as u32) });
+
+                            // here we finishing transferring the buffers to the DMA; all previous
+                            // memory operations on them should be finished before then, thus the
+                            // compiler fence
+                            atomic::compiler_fence(Ordering::Release);
+                            spim.tasks_start.write(|w| unsafe { w.bits(1) });
+
+                            // install the waker
+                            unsafe {
+                                WAKER = Some(cx.waker().clone());
+
+                                // updating the `WAKER` needs to be completed before unmasking the
+                                // interrupt; hence the compiler fence
+                                atomic::compiler_fence(Ordering::Release);
+                                NVIC::unmask(interrupt);
+                            }
+
+                            self.state = State::InProgress;
+
+                            Poll::Pending
+                        })
+                    }
+
+                    State::InProgress => borrow(self.spim.serial, |spim| {
+                        let interrupt = self.spim.serial.interrupt();
+
+                        if spim.events_end.read().bits() != 0 {
+                            // the buffers have been handed back to us; any future operation on
+                            // them should not be reordered to before this point
+                            atomic::compiler_fence(Ordering::Acquire);
+
+                            spim.events_end.reset();
+                            spim.events_endrx.reset();
+                            spim.events_endtx.reset();
+                            spim.events_started.reset();
+
+                            // uninstall the waker
+                            NVIC::mask(interrupt);
+                            // NOTE(compiler_fence) the interrupt must be disabled before we take
+                            // down the waker
+                            atomic::compiler_fence(Ordering::Release);
+                            drop(unsafe { WAKER.take() });
+
+                            self.state = State::Finished;
+
+                            Poll::Ready(())
+                        } else {
+                            // spurious wake up; re-arm the one-shot interrupt
+                            unsafe {
+                                NVIC::unmask(interrupt);
+                            }
+
+                            Poll::Pending
+                        }
+                    }),
+
+                    State::Finished => unreachable!(),
+                }
+            }
+        }
+
+        // NOTE a `Transfer` future can be dropped before it completes, e.g. when it loses a
+        // `select` against a timeout; the DMA must release the buffers before they go away
+        impl Drop for Transfer<'_> {
+            fn drop(&mut self) {
+                if self.state == State::InProgress {
+                    let interrupt = self.spim.serial.interrupt();
+
+                    // uninstall the waker
+                    NVIC::mask(interrupt);
+                    // NOTE(compiler_fence) the interrupt must be disabled before we take down the
+                    // waker
+                    atomic::compiler_fence(Ordering::SeqCst);
+                    drop(unsafe { WAKER.take() });
+
+                    borrow(self.spim.serial, abort);
+                    NVIC::unpend(interrupt);
+
+                    // NOTE(compiler_fence) the DMA has released the buffers; operations on them
+                    // must not be reordered to before this point
+                    atomic::compiler_fence(Ordering::Acquire);
+                }
+            }
+        }
+
+        Transfer {
+            spim: self,
+            tx,
+            tx_len,
+            rx,
+            rx_len,
+            state: State::NotStarted,
+        }
+        .await
+    }
+}
+
+fn borrow<T>(serial: Serial, f: impl FnOnce(&RegisterBlock) -> T) -> T {
+    match serial {
+        Serial::Serial0 => SPIM0::borrow_unchecked(|spim| f(spim)),
+        Serial::Serial1 => SPIM1::borrow_unchecked(|spim| f(spim)),
+        Serial::Serial2 => SPIM2::borrow_unchecked(|spim| f(spim)),
+    }
+}
+
+// Stops the transfer in progress, if any, and clears its events
+fn abort(spim: &RegisterBlock) {
+    if spim.events_end.read().bits() == 0 {
+        spim.events_stopped.reset();
+        spim.tasks_stop.write(|w| unsafe { w.bits(1) });
+        while spim.events_stopped.read().bits() == 0 {
+            continue;
+        }
+    }
+
+    spim.events_end.reset();
+    spim.events_endrx.reset();
+    spim.events_endtx.reset();
+    spim.events_started.reset();
+    spim.events_stopped.reset();
+}
+
+static mut WAKER: Option<Waker> = None;
+
+// NOTE the `Serial0` interrupt handler lives in the `twim` module
+pub(crate) fn on_interrupt(serial: Serial) {
+    // NOTE(unsafe) the only other context that can access this static variable
+    // runs at lower priority
+    if let Some(waker) = unsafe { WAKER.as_ref() } {
+        waker.wake_by_ref();
+
+        // avoid continuously re-entering this interrupt handler
+        NVIC::mask(serial.interrupt());
+    } else {
+        // reachable if the user manually pends this interrupt
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+fn SPIM1_SPIS1_TWIM1_TWIS1_SPI1_TWI1() {
+    on_interrupt(Serial::Serial1);
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+fn SPIM2_SPIS2_SPI2() {
+    on_interrupt(Serial::Serial2);
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    NotStarted,
+    InProgress,
+    Finished,
+}
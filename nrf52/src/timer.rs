@@ -1,31 +1,48 @@
 //! Timers
 
 use core::{
+    cell::Cell,
+    cmp,
     future::Future,
+    ops::{Add, Sub},
     pin::Pin,
-    sync::atomic::{self, AtomicBool, Ordering},
+    sync::atomic::{self, AtomicBool, AtomicU32, Ordering},
     task::{Context, Poll, Waker},
     time::Duration,
 };
 
-use cortex_m::peripheral::NVIC;
+use async_embedded::{
+    task::{self, Delay, Either, TimedOut},
+    unsync::Notify,
+};
+use cortex_m::{interrupt, peripheral::NVIC};
 use pac::{Interrupt, RTC0};
 
-use crate::{BorrowUnchecked as _, NotSync};
+use crate::{token::Token, BorrowUnchecked as _, NotSync};
+
+pub mod ext;
 
 // NOTE called from `pre_init`
 pub(crate) fn init() {
     pac::RTC0::borrow_unchecked(|rtc| {
-        // enable compare0 interrupt
-        rtc.intenset.write(|w| w.compare0().set_bit());
+        // enable the overflow interrupt; the wrap-arounds are counted in the interrupt handler.
+        // The compare0 interrupt is enabled on demand; see `Queue::rearm`
+        rtc.intenset.write(|w| w.ovrflw().set_bit());
         rtc.tasks_clear.write(|w| w.tasks_clear().set_bit());
         rtc.tasks_start.write(|w| w.tasks_start().set_bit());
     });
+
+    // NOTE(unsafe) the interrupt stays unmasked to count the overflows. `.bss` is not yet
+    // initialized but the first overflow is 512 seconds away
+    unsafe { NVIC::unmask(Interrupt::RTC0) }
 }
 
 /// [singleton] An `async`-aware timer
 pub struct Timer {
     _not_sync: NotSync,
+    // fraction of a tick, in units of 1 / (F * 1e9) seconds, that was rounded down by the last
+    // `wait`; it's carried into the next `wait` so long sequences of waits stay accurate on average
+    remainder: Cell<u32>,
 }
 
 impl Timer {
@@ -43,106 +60,622 @@ impl Timer {
         {
             Self {
                 _not_sync: NotSync::new(),
+                remainder: Cell::new(0),
             }
         } else {
             panic!("`Timer` has already been taken")
         }
     }
 
-    /// Waits for at least `dur`
-    // NOTE we could support several "timeouts" by making this take `&self` and
-    // using a priority queue (sorted queue) to store the deadlines
-    pub async fn wait(&mut self, dur: Duration) {
-        struct Wait<'a> {
-            _timer: &'a mut Timer,
-            installed_waker: bool,
-        }
-
-        impl<'a> Future for Wait<'a> {
-            type Output = ();
-
-            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
-                static mut WAKER: Option<Waker> = None;
-
-                if has_expired() {
-                    if self.installed_waker {
-                        // uninstall the waker
-                        NVIC::mask(Interrupt::RTC0);
-                        // NOTE(compiler_fence) the interrupt must be disabled
-                        // before we take down the waker
-                        atomic::compiler_fence(Ordering::SeqCst);
-                        drop(unsafe { WAKER.take() })
-                    }
-
-                    Poll::Ready(())
-                } else {
-                    if !self.installed_waker {
-                        unsafe {
-                            WAKER = Some(cx.waker().clone());
-                            // NOTE(compiler_fence) `WAKER` write must complete
-                            // before we enable the interrupt
-                            atomic::compiler_fence(Ordering::Release);
-                            NVIC::unmask(Interrupt::RTC0); // atomic write
-                        }
-
-                        #[allow(non_snake_case)]
-                        #[no_mangle]
-                        fn RTC0() {
-                            // NOTE(unsafe) the only other context that can
-                            // access this static variable runs at lower
-                            // priority -- that context won't overlap in
-                            // execution with this operation
-                            if let Some(waker) = unsafe { WAKER.as_ref() } {
-                                waker.wake_by_ref();
-
-                                // one shot interrupt -- this won't fire again
-                                NVIC::mask(Interrupt::RTC0);
-                            } else {
-                                // this could be have been triggered by the user
-                            }
-                        }
-                    } else {
-                        // prepare another one-shot interrupt
-                        unsafe {
-                            NVIC::unmask(Interrupt::RTC0);
-                        }
-                    }
-
-                    Poll::Pending
-                }
-            }
+    /// Gives up the timer in exchange for direct access to the RTC; see the `token` module
+    pub fn into_token(self) -> Token<RTC0> {
+        Token::new()
+    }
+
+    /// Gets the timer back from the RTC token
+    pub fn from_token(_token: Token<RTC0>) -> Self {
+        Self {
+            _not_sync: NotSync::new(),
+            remainder: Cell::new(0),
         }
+    }
+
+    /// Returns the current time
+    ///
+    /// The RTC counter is 24-bit wide so it wraps around every 512 seconds; the wrap-arounds are
+    /// counted by the RTC0 interrupt handler so the returned `Instant`s are monotonic no matter
+    /// how often this function is called. It can also be called from interrupt handlers: the
+    /// RTC0 handler counts a wrap-around and clears its event in a single critical section, so a
+    /// handler that preempts it never sees one without the other
+    pub fn now() -> Instant {
+        RTC0::borrow_unchecked(|rtc| loop {
+            let snapshot = OVERFLOWS.load(Ordering::Acquire);
+            let mut counter = rtc.counter.read().bits();
+            let mut overflows = snapshot;
 
+            // NOTE the counter may have wrapped around before the interrupt handler got to run,
+            // e.g. because the interrupt is briefly masked by `Queue::rearm`. Account for that
+            // overflow here; the event may have been raised after the counter was read so read
+            // the counter again
+            if rtc.events_ovrflw.read().bits() != 0 {
+                counter = rtc.counter.read().bits();
+                overflows += 1;
+            }
+
+            // the interrupt handler preempted us; try again
+            if OVERFLOWS.load(Ordering::Acquire) != snapshot {
+                continue;
+            }
+
+            break Instant {
+                ticks: u64::from(overflows) << 24 | u64::from(counter),
+            };
+        })
+    }
+
+    /// Waits for `dur`
+    ///
+    /// `dur` is rounded down to a whole number of ticks of the 32,768 Hz clock; the rounding
+    /// error is carried into the next `wait` call so the total time spent waiting on a sequence
+    /// of `wait` calls is accurate to within one tick
+    ///
+    /// Several waits (and other timed operations) can be in flight at the same time, e.g. in
+    /// different tasks that share the timer; up to `MAX_DEADLINES` of them
+    pub async fn wait(&self, dur: Duration) {
+        self.delay(dur).await;
+        trace!(crate::trace::EventId::TimerExpired);
+    }
+
+    // Converts `dur` into ticks; the rounding error is carried over to the next call
+    fn carry_ticks(&self, dur: Duration) -> u64 {
         // TODO do this without 64-bit arithmetic
         const F: u64 = 32_768; // frequency of the LFCLK
-        let ticks = dur.as_secs() * F + (u64::from(dur.subsec_nanos()) * F) / 1_000_000_000;
-        // NOTE we could support 64-bit ticks
-        assert!(ticks < (1 << 24));
-        let ticks = ticks as u32;
+        const NANOS_PER_SEC: u64 = 1_000_000_000;
+        let nanos = u64::from(dur.subsec_nanos()) * F + u64::from(self.remainder.get());
+        self.remainder.set((nanos % NANOS_PER_SEC) as u32);
+        dur.as_secs() * F + nanos / NANOS_PER_SEC
+    }
 
-        NVIC::mask(Interrupt::RTC0);
-        RTC0::borrow_unchecked(|rtc| {
-            let now = rtc.counter.read().bits();
-            rtc.events_compare[0].reset();
-            // NOTE(unsafe) this operation shouldn't be marked as `unsafe`
-            rtc.cc[0].write(|w| unsafe { w.compare().bits(now.wrapping_add(ticks)) });
-        });
+    /// Waits until `deadline`
+    ///
+    /// Returns right away if `deadline` is not in the future
+    pub async fn wait_until(&self, deadline: Instant) {
+        Wait {
+            _timer: self,
+            deadline,
+            key: None,
+        }
+        .await;
+    }
+
+    /// Returns a stream of ticks `period` apart; see `Interval`
+    ///
+    /// `period` is rounded down to a whole number of ticks of the 32,768 Hz clock. The first tick
+    /// happens `period` from now
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `period` is shorter than 2 ticks
+    pub fn interval(&self, period: Duration) -> Interval<'_> {
+        let period = duration_to_ticks(period);
+        assert!(period >= 2);
+
+        Interval {
+            next: Instant {
+                ticks: Timer::now().ticks + period,
+            },
+            period,
+            timer: self,
+        }
+    }
 
+    /// Waits until `deadline` or until `notify` is notified, whichever happens first
+    ///
+    /// Returns `Wakeup::Deadline` right away if `deadline` is not in the future; otherwise, if
+    /// both events have happened by the time this operation is resumed `Wakeup::Notified` is
+    /// reported and the `notify` permit is consumed
+    pub async fn sleep_until_or(&self, deadline: Instant, notify: &Notify) -> Wakeup {
+        match self.timeout_at(deadline, notify.notified()).await {
+            Ok(()) => Wakeup::Notified,
+            Err(TimedOut) => Wakeup::Deadline,
+        }
+    }
+
+    /// Drives `f` to completion unless `deadline` is reached first
+    ///
+    /// On time out, `f` is dropped. Returns `Err(TimedOut)` right away, without polling `f`, if
+    /// `deadline` is not in the future; otherwise, if `f` completes by the time the deadline is
+    /// reached its output is returned
+    pub async fn timeout_at<F>(&self, deadline: Instant, f: F) -> Result<F::Output, TimedOut>
+    where
+        F: Future,
+    {
+        let now = Timer::now();
+        if deadline <= now {
+            return Err(TimedOut);
+        }
+
+        // NOTE `f` goes first so it wins when it completes right at the deadline
+        match task::select(f, self.wait_until(deadline)).await {
+            Either::Left(val) => Ok(val),
+            Either::Right(()) => Err(TimedOut),
+        }
+    }
+}
+
+/// A future that completes at a deadline; see `Timer::wait_until`
+pub struct Wait<'a> {
+    _timer: &'a Timer,
+    deadline: Instant,
+    // key of our entry in the deadline queue
+    key: Option<u32>,
+}
+
+impl<'a> Future for Wait<'a> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // NOTE(unsafe) the queue is only accessed from thread mode
+        let queue = unsafe { &mut QUEUE };
+
+        if Timer::now() >= self.deadline {
+            if let Some(key) = self.key.take() {
+                queue.remove(key);
+                queue.rearm();
+            }
+
+            Poll::Ready(())
+        } else {
+            match self.key {
+                Some(key) => queue.update(key, cx.waker()),
+                None => self.key = Some(queue.insert(self.deadline, cx.waker())),
+            }
+
+            // NOTE this also prepares another one-shot interrupt
+            queue.rearm();
+
+            Poll::Pending
+        }
+    }
+}
+
+// NOTE a `Wait` future can be dropped before it completes, e.g. when it loses a race
+// against another future. Remove its deadline from the queue
+impl Drop for Wait<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            // NOTE(unsafe) the queue is only accessed from thread mode
+            let queue = unsafe { &mut QUEUE };
+            queue.remove(key);
+            queue.rearm();
+        }
+    }
+}
+
+impl<'a> Delay for &'a Timer {
+    type Delay = Wait<'a>;
+
+    fn delay(self, dur: Duration) -> Wait<'a> {
         Wait {
             _timer: self,
-            installed_waker: false,
+            deadline: Instant {
+                ticks: Timer::now().ticks + self.carry_ticks(dur),
+            },
+            key: None,
         }
-        .await
     }
 }
 
-fn has_expired() -> bool {
+/// Periodic ticks created with `Timer::interval`
+///
+/// Each deadline is computed from the previous one, not from the time `tick` is called, so
+/// the ticks stay phase-locked to the first one: a tick that is serviced late doesn't delay the
+/// ones that follow. If a deadline has already passed when `tick` is called, `tick` completes
+/// right away so a late caller catches up with a burst of ticks
+pub struct Interval<'a> {
+    timer: &'a Timer,
+    // in ticks
+    period: u64,
+    next: Instant,
+}
+
+impl Interval<'_> {
+    /// Waits for the next tick and returns its deadline
+    ///
+    /// Successive deadlines are exactly one period apart
+    pub async fn tick(&mut self) -> Instant {
+        let deadline = self.next;
+        self.next = Instant {
+            ticks: deadline.ticks + self.period,
+        };
+
+        self.timer.wait_until(deadline).await;
+
+        deadline
+    }
+
+    /// Returns the deadline of the next tick
+    pub fn next_deadline(&self) -> Instant {
+        self.next
+    }
+}
+
+/// The event that ended a `Timer::sleep_until_or` call
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Wakeup {
+    /// The deadline was reached
+    Deadline,
+
+    /// The `Notify` was notified
+    Notified,
+}
+
+// number of times the RTC counter has wrapped around
+static OVERFLOWS: AtomicU32 = AtomicU32::new(0);
+
+static mut WAKER: Option<Waker> = None;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+fn RTC0() {
     RTC0::borrow_unchecked(|rtc| {
-        if rtc.events_compare[0].read().events_compare().bit_is_set() {
-            rtc.events_compare[0].reset();
-            true
+        if rtc.events_ovrflw.read().bits() != 0 {
+            // NOTE between these two operations `Timer::now` would miss this wrap-around; don't
+            // let higher priority interrupt handlers (e.g. one calling `trace::record`) observe
+            // that state
+            interrupt::free(|_| {
+                rtc.events_ovrflw.reset();
+                // NOTE(store) no RMW race: this is the only context that writes this variable
+                OVERFLOWS.store(OVERFLOWS.load(Ordering::Relaxed) + 1, Ordering::Release);
+            });
+        }
+
+        // NOTE(unsafe) the only other context that can access this static variable
+        // runs at lower priority -- that context won't overlap in execution with
+        // this operation
+        if let Some(waker) = unsafe { WAKER.as_ref() } {
+            // NOTE an overflow also lands here; the task will find that its deadline has not
+            // been reached yet and will re-arm the timer
+            waker.wake_by_ref();
+
+            // one shot interrupt -- this won't fire again
+            rtc.intenclr.write(|w| w.compare0().set_bit());
         } else {
-            false
+            // this could be have been triggered by the user
         }
     })
 }
+
+/// Maximum number of deadlines (e.g. `wait`s) that can be in flight at the same time
+pub const MAX_DEADLINES: usize = 8;
+
+// NOTE(unsafe) only accessed from thread mode
+static mut QUEUE: Queue = Queue {
+    entries: [None, None, None, None, None, None, None, None],
+    len: 0,
+    next_key: 0,
+};
+
+struct Entry {
+    deadline: Instant,
+    key: u32,
+    waker: Waker,
+}
+
+// Outstanding deadlines, sorted by deadline (earliest first)
+//
+// The RTC0 compare register is programmed for the earliest deadline and the interrupt handler
+// wakes the task waiting for it (see `rearm`); that task removes its entry and programs the
+// compare register for the next deadline
+struct Queue {
+    entries: [Option<Entry>; MAX_DEADLINES],
+    len: usize,
+    next_key: u32,
+}
+
+impl Queue {
+    fn insert(&mut self, deadline: Instant, waker: &Waker) -> u32 {
+        assert!(
+            self.len < MAX_DEADLINES,
+            "more than `MAX_DEADLINES` deadlines in flight"
+        );
+
+        let key = self.next_key;
+        self.next_key = key.wrapping_add(1);
+
+        // NOTE entries with the same deadline are kept in insertion order
+        let mut i = self.len;
+        while i > 0 && self.deadline(i - 1) > deadline {
+            self.entries[i] = self.entries[i - 1].take();
+            i -= 1;
+        }
+        self.entries[i] = Some(Entry {
+            deadline,
+            key,
+            waker: waker.clone(),
+        });
+        self.len += 1;
+
+        key
+    }
+
+    fn remove(&mut self, key: u32) {
+        if let Some(i) = self.position(key) {
+            for j in i..self.len - 1 {
+                self.entries[j] = self.entries[j + 1].take();
+            }
+            self.entries[self.len - 1] = None;
+            self.len -= 1;
+        }
+    }
+
+    fn update(&mut self, key: u32, waker: &Waker) {
+        if let Some(i) = self.position(key) {
+            if let Some(entry) = self.entries[i].as_mut() {
+                if !entry.waker.will_wake(waker) {
+                    entry.waker = waker.clone();
+                }
+            }
+        }
+    }
+
+    // Programs the compare register for the earliest deadline and arms the one-shot interrupt
+    fn rearm(&mut self) {
+        NVIC::mask(Interrupt::RTC0);
+        // NOTE(compiler_fence) the interrupt must be disabled before we change the waker
+        atomic::compiler_fence(Ordering::SeqCst);
+
+        let head = match self.entries[0].as_ref() {
+            Some(head) => head,
+            None => {
+                drop(unsafe { WAKER.take() });
+                RTC0::borrow_unchecked(|rtc| {
+                    rtc.intenclr.write(|w| w.compare0().set_bit());
+                    rtc.events_compare[0].reset();
+                });
+                // NOTE(unsafe) the interrupt stays unmasked to count the counter overflows
+                unsafe { NVIC::unmask(Interrupt::RTC0) }
+                return;
+            }
+        };
+
+        // NOTE deadlines can be further away than one period of the 24-bit counter. The compare
+        // register is programmed at most half a period ahead; the woken task finds that its
+        // deadline has not been reached yet and programs the next hop
+        let hop = cmp::min(head.deadline.ticks, Timer::now().ticks + MAX_HOP);
+        RTC0::borrow_unchecked(|rtc| {
+            // NOTE(unsafe) this operation shouldn't be marked as `unsafe`
+            rtc.cc[0].write(|w| unsafe { w.compare().bits(hop as u32 & COUNTER_MASK) });
+            rtc.events_compare[0].reset();
+        });
+
+        unsafe {
+            WAKER = Some(head.waker.clone());
+            // NOTE(compiler_fence) `WAKER` write must complete before we enable the interrupt
+            atomic::compiler_fence(Ordering::Release);
+            RTC0::borrow_unchecked(|rtc| rtc.intenset.write(|w| w.compare0().set_bit()));
+            NVIC::unmask(Interrupt::RTC0); // atomic write
+        }
+
+        // NOTE the RTC may miss a compare value that's less than 2 ticks ahead of the counter, and
+        // the deadline may have been reached while the compare register was being programmed. In
+        // either case fire the interrupt right away; the task will check the time when it's polled
+        if hop < Timer::now().ticks + 2 {
+            NVIC::pend(Interrupt::RTC0);
+        }
+    }
+
+    fn deadline(&self, i: usize) -> Instant {
+        self.entries[i]
+            .as_ref()
+            .map(|entry| entry.deadline)
+            .unwrap_or(Instant { ticks: 0 })
+    }
+
+    fn position(&self, key: u32) -> Option<usize> {
+        self.entries[..self.len]
+            .iter()
+            .position(|entry| entry.as_ref().map(|entry| entry.key) == Some(key))
+    }
+}
+
+const TICKS_PER_SEC: u64 = 32_768;
+// the RTC counter is 24-bit wide
+const COUNTER_MASK: u32 = (1 << 24) - 1;
+// how far ahead of the counter the compare register may be programmed; half a period
+const MAX_HOP: u64 = 1 << 23;
+
+/// Returns a deadline `dur` from now
+///
+/// Checking a deadline is cheap (it reads the clock; it doesn't wait) so this is meant to bound
+/// polling loops, e.g. `while !ready().await { if deadline.expired() { /* give up */ } }`
+pub fn deadline(dur: Duration) -> Deadline {
+    Deadline {
+        at: Timer::now() + dur,
+    }
+}
+
+/// A point in time after which an operation should be given up; see `deadline`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deadline {
+    at: Instant,
+}
+
+impl Deadline {
+    /// Returns `true` if the deadline has been reached
+    ///
+    /// Like `Timer::now`, this must not be called from interrupt handlers
+    pub fn expired(&self) -> bool {
+        Timer::now() >= self.at
+    }
+
+    /// Returns the time left until the deadline; zero if it has been reached
+    pub fn remaining(&self) -> Duration {
+        self.at.duration_since(Timer::now())
+    }
+
+    /// Returns the point in time of the deadline
+    pub fn instant(&self) -> Instant {
+        self.at
+    }
+}
+
+/// A time budget that is consumed across several operations
+///
+/// E.g. to spend at most 5 ms per cycle on optional work: create a `Budget` at the start of the
+/// cycle, `charge_since` the start of each operation once it completes and skip the remaining
+/// operations once the budget is exhausted
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Budget {
+    total: Duration,
+    remaining: Duration,
+}
+
+impl Budget {
+    /// Creates a full budget of `total`
+    pub fn new(total: Duration) -> Self {
+        Self {
+            total,
+            remaining: total,
+        }
+    }
+
+    /// Consumes `dur` from the budget
+    ///
+    /// Returns `true` if the budget is exhausted. Consuming more than the remaining budget
+    /// leaves it at zero
+    pub fn consume(&mut self, dur: Duration) -> bool {
+        self.remaining = self.remaining.checked_sub(dur).unwrap_or_default();
+        self.is_exhausted()
+    }
+
+    /// Consumes the time elapsed since `since` from the budget
+    ///
+    /// Returns `true` if the budget is exhausted. Like `Timer::now`, this must not be called
+    /// from interrupt handlers
+    pub fn charge_since(&mut self, since: Instant) -> bool {
+        self.consume(since.elapsed())
+    }
+
+    /// Returns `true` if nothing is left of the budget
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == Duration::default()
+    }
+
+    /// Returns what's left of the budget
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    /// Returns the size of the full budget
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// Refills the budget, e.g. at the start of a new cycle
+    pub fn reset(&mut self) {
+        self.remaining = self.total;
+    }
+}
+
+/// A point in time, measured since the start of the program with a resolution of ~30.5 us
+/// (one tick of the 32,768 Hz clock)
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Instant {
+    ticks: u64,
+}
+
+impl Instant {
+    /// Returns the number of clock ticks since the start of the program
+    pub fn ticks(self) -> u64 {
+        self.ticks
+    }
+
+    // NOTE for the unit tests of other modules; instants otherwise come from the `Timer`
+    #[cfg(test)]
+    pub(crate) fn from_ticks(ticks: u64) -> Self {
+        Self { ticks }
+    }
+
+    /// Returns the time elapsed since `earlier`
+    ///
+    /// Returns a zero duration if `earlier` is later than `self`
+    pub fn duration_since(self, earlier: Instant) -> Duration {
+        ticks_to_duration(self.ticks.saturating_sub(earlier.ticks))
+    }
+
+    /// Returns the time elapsed since this instant
+    pub fn elapsed(self) -> Duration {
+        Timer::now().duration_since(self)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, dur: Duration) -> Instant {
+        Instant {
+            ticks: self.ticks + duration_to_ticks(dur),
+        }
+    }
+}
+
+impl Sub for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+fn ticks_to_duration(ticks: u64) -> Duration {
+    let secs = ticks / TICKS_PER_SEC;
+    let nanos = (ticks % TICKS_PER_SEC) * 1_000_000_000 / TICKS_PER_SEC;
+    Duration::new(secs, nanos as u32)
+}
+
+// NOTE rounds down to a whole number of ticks
+fn duration_to_ticks(dur: Duration) -> u64 {
+    dur.as_secs() * TICKS_PER_SEC + u64::from(dur.subsec_nanos()) * TICKS_PER_SEC / 1_000_000_000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ext::DurationExt as _, Budget};
+
+    #[test]
+    fn budget_consume() {
+        let mut budget = Budget::new(10.millis());
+        assert!(!budget.consume(4.millis()));
+        assert_eq!(budget.remaining(), 6.millis());
+        assert!(!budget.consume(5.millis()));
+        assert_eq!(budget.remaining(), 1.millis());
+
+        // overdrawing leaves the budget at zero
+        assert!(budget.consume(3.millis()));
+        assert_eq!(budget.remaining(), 0.millis());
+        assert!(budget.is_exhausted());
+        // and it stays there
+        assert!(budget.consume(1.millis()));
+        assert_eq!(budget.remaining(), 0.millis());
+
+        budget.reset();
+        assert_eq!(budget.remaining(), budget.total());
+        assert!(!budget.is_exhausted());
+    }
+
+    #[test]
+    fn budget_exact() {
+        // using up exactly what's left exhausts the budget
+        let mut budget = Budget::new(10.millis());
+        assert!(budget.consume(10.millis()));
+
+        // consuming nothing from a full budget
+        let mut budget = Budget::new(10.millis());
+        assert!(!budget.consume(0.millis()));
+        assert_eq!(budget.remaining(), 10.millis());
+
+        // an empty budget starts out exhausted
+        assert!(Budget::new(0.millis()).is_exhausted());
+    }
+}
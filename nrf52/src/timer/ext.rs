@@ -0,0 +1,81 @@
+//! Extension trait for writing `Duration`s
+//!
+//! ``` ignore
+//! use nrf52::timer::ext::DurationExt as _;
+//!
+//! timer.wait(100.millis()).await;
+//! ```
+
+use core::time::Duration;
+
+/// Conversions from integers into `Duration`s
+pub trait DurationExt {
+    /// `Duration::from_micros`
+    fn micros(self) -> Duration;
+
+    /// `Duration::from_millis`
+    fn millis(self) -> Duration;
+
+    /// `Duration::from_secs`
+    fn secs(self) -> Duration;
+}
+
+// NOTE only implemented for `u32` so that integer literals (`100.millis()`) don't need a suffix
+impl DurationExt for u32 {
+    #[inline(always)]
+    fn micros(self) -> Duration {
+        Duration::from_micros(self.into())
+    }
+
+    #[inline(always)]
+    fn millis(self) -> Duration {
+        Duration::from_millis(self.into())
+    }
+
+    #[inline(always)]
+    fn secs(self) -> Duration {
+        Duration::from_secs(self.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::DurationExt as _;
+
+    #[test]
+    fn constructors() {
+        for &n in [0, 1, 999, 1_000, 1_001, 123_456, u32::max_value()].iter() {
+            assert_eq!(n.micros(), Duration::from_micros(n.into()));
+            assert_eq!(n.millis(), Duration::from_millis(n.into()));
+            assert_eq!(n.secs(), Duration::from_secs(n.into()));
+        }
+
+        // the units carry over into the next one
+        assert_eq!(1_000.micros(), 1.millis());
+        assert_eq!(1_000.millis(), 1.secs());
+        assert_eq!(1_500.micros(), 1.millis() + 500.micros());
+    }
+
+    #[test]
+    fn truncation() {
+        // nothing is rounded: sub-unit parts are kept exactly ...
+        assert_eq!(1_999.micros().as_micros(), 1_999);
+        assert_eq!(1_999.micros().subsec_nanos(), 1_999_000);
+        // ... and only truncated when converting to a coarser unit
+        assert_eq!(1_999.micros().as_millis(), 1);
+        assert_eq!(999.millis().as_secs(), 0);
+    }
+
+    #[test]
+    fn no_overflow() {
+        // the largest values don't overflow nor lose precision
+        let max = u32::max_value();
+        assert_eq!(max.micros().as_secs(), 4_294);
+        assert_eq!(max.micros().subsec_micros(), 967_295);
+        assert_eq!(max.millis().as_secs(), 4_294_967);
+        assert_eq!(max.millis().subsec_millis(), 295);
+        assert_eq!(max.secs().as_secs(), u64::from(max));
+    }
+}
@@ -0,0 +1,47 @@
+//! Explicit peripheral ownership
+//!
+//! The drivers in this crate access their peripherals through `borrow_unchecked`, which makes the
+//! registers reachable from anywhere in the crate. This is convenient but it leaves the question
+//! "who owns this peripheral right now?" to the reader. For applications that want that
+//! relationship in the type system, the driver singletons can be turned into a `Token`, which
+//! gives access to the registers, and back. A token can only be obtained by giving up the driver
+//! (and vice versa) so the driver and the raw registers can't be used at the same time
+//!
+//! The tradeoff: register access through a token bypasses the driver invariants (e.g. the driver
+//! assumes the configuration done in `pre_init`), and the interrupt handlers and the `power`
+//! module keep using `borrow_unchecked`; a driver must be idle when it's turned into a token
+
+use core::marker::PhantomData;
+
+use crate::{BorrowUnchecked as _, NotSync};
+
+/// Zero-sized proof of ownership of the peripheral `P`
+pub struct Token<P> {
+    _peripheral: PhantomData<P>,
+    _not_sync: NotSync,
+}
+
+impl<P> Token<P> {
+    // NOTE only the driver singleton that owns `P` may create its token
+    pub(crate) fn new() -> Self {
+        Self {
+            _peripheral: PhantomData,
+            _not_sync: NotSync::new(),
+        }
+    }
+}
+
+macro_rules! registers {
+    ($($peripheral:ident),*) => {
+        $(
+            impl Token<pac::$peripheral> {
+                /// Gives access to the registers of the peripheral
+                pub fn registers<T>(&mut self, f: impl FnOnce(&pac::$peripheral) -> T) -> T {
+                    pac::$peripheral::borrow_unchecked(f)
+                }
+            }
+        )*
+    }
+}
+
+registers!(RTC0, TWIM0, UARTE0);
@@ -0,0 +1,143 @@
+//! Event tracing
+//!
+//! The drivers record some of their operations (see `EventId`) into a global ring buffer; the
+//! application can add its own events with `record`. The buffer can later be retrieved with `take`
+//! and, for example, dumped over the serial interface
+
+use core::fmt::{self, Write as _};
+
+use cortex_m::interrupt;
+
+use crate::{
+    serial::Tx,
+    timer::{Instant, Timer},
+};
+
+/// Number of events the trace buffer can hold; when full, the oldest events are overwritten
+pub const CAPACITY: usize = 32;
+
+/// Traced event
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventId {
+    /// An I2C transfer to the device with this address started
+    TwimStart(u8),
+
+    /// An I2C transfer to the device with this address ended
+    TwimEnd(u8),
+
+    /// A serial read completed
+    SerialRx,
+
+    /// A serial write started
+    SerialTx,
+
+    /// A `Timer::wait` expired
+    TimerExpired,
+
+    /// Application defined event
+    User(u16),
+}
+
+/// An event and the time at which it was recorded
+#[derive(Clone, Copy, Debug)]
+pub struct Record {
+    /// When the event happened
+    pub time: Instant,
+
+    /// What happened
+    pub id: EventId,
+}
+
+/// A fixed capacity ring buffer of `Record`s
+pub struct Tracer {
+    ring: [Option<Record>; CAPACITY],
+    // index of the next slot to write; when the buffer is full, also the oldest record
+    next: usize,
+}
+
+impl Tracer {
+    const fn new() -> Self {
+        Self {
+            ring: [None; CAPACITY],
+            next: 0,
+        }
+    }
+
+    /// Returns an iterator over the records, from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = &Record> {
+        let (newer, older) = self.ring.split_at(self.next);
+        older.iter().chain(newer).filter_map(|r| r.as_ref())
+    }
+
+    /// Writes the records, one per line, over the serial interface
+    ///
+    /// Each line contains the timestamp, in clock ticks (32,768 ticks = 1 second), and the event
+    pub async fn dump(&self, tx: &mut Tx) {
+        for record in self.iter() {
+            let mut line = Line::new();
+            // will not fail; the buffer is big enough
+            let _ = writeln!(line, "{:>10} {:?}", record.time.ticks(), record.id);
+            tx.write(line.as_bytes()).await;
+        }
+    }
+
+    fn push(&mut self, record: Record) {
+        self.ring[self.next] = Some(record);
+        self.next = (self.next + 1) % CAPACITY;
+    }
+}
+
+static mut TRACER: Tracer = Tracer::new();
+
+/// Records an event
+///
+/// This function can be called from interrupt handlers
+pub fn record(id: EventId) {
+    // NOTE the time is read in the critical section so that the records are in time order
+    interrupt::free(|_| {
+        let record = Record {
+            time: Timer::now(),
+            id,
+        };
+        // NOTE(unsafe) exclusive access due to the critical section
+        unsafe { TRACER.push(record) }
+    })
+}
+
+/// Takes the current contents of the trace buffer, leaving the buffer empty
+pub fn take() -> Tracer {
+    // NOTE(unsafe) exclusive access due to the critical section
+    interrupt::free(|_| unsafe { core::mem::replace(&mut TRACER, Tracer::new()) })
+}
+
+// formatting buffer for a single line of the dump
+struct Line {
+    buf: [u8; 48],
+    len: usize,
+}
+
+impl Line {
+    fn new() -> Self {
+        Self {
+            buf: [0; 48],
+            len: 0,
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl fmt::Write for Line {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let bytes = s.as_bytes();
+        let end = self.len + bytes.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+}
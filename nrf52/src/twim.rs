@@ -3,23 +3,33 @@
 // Based on https://github.com/nrf-rs/nrf52-hal/commit/f05d471996c63f605cab43aa76c8fd990b852460
 
 use core::{
+    cell::Cell,
+    fmt,
     future::Future,
+    iter,
+    ops::{Deref, DerefMut},
     pin::Pin,
-    sync::atomic::{self, AtomicBool, Ordering},
+    sync::atomic::{self, Ordering},
     task::{Context, Poll, Waker},
 };
 
-use cortex_m::peripheral::NVIC;
-use pac::{Interrupt, TWIM0};
+use async_embedded::unsync::{Mutex, MutexGuard};
+use cortex_m::{asm, peripheral::NVIC};
+use pac::{Interrupt, P0, TWIM0};
 
-use crate::{BorrowUnchecked, NotSync};
+use crate::{
+    instance::{self, InUse, Owner, Serial},
+    token::Token,
+    BorrowUnchecked, NotSync,
+};
+
+const SDA_PIN: u8 = 26;
+const SCL_PIN: u8 = 27;
 
 // NOTE called from `pre_init`
 pub(crate) fn init() {
     use pac::twim0::frequency::FREQUENCY_A;
 
-    const SDA_PIN: u8 = 26;
-    const SCL_PIN: u8 = 27;
     const TWIM_PORT: bool = false; // 0
 
     // pin configuration
@@ -73,6 +83,12 @@ pub(crate) fn init() {
 
 const INTERRUPT: Interrupt = Interrupt::SPIM0_SPIS0_TWIM0_TWIS0_SPI0_TWI0;
 const MAXCNT: usize = 256;
+// largest transfer EasyDMA can do
+const MAX_TRANSFER: usize = MAXCNT - 1;
+
+// how many times `stop_transfer` checks for the STOPPED event before giving up; roughly 10 ms
+// @ 64 MHz, the time it takes to clock ~100 bytes over the 100 KHz bus
+const STOP_TIMEOUT: u32 = 100_000;
 
 /// [singleton] An `async`-aware I2C host
 pub struct Twim {
@@ -82,21 +98,40 @@ pub struct Twim {
 impl Twim {
     /// Takes the singleton instance of this I2C bus
     ///
-    /// This returns the `Some` variant only once
+    /// # Panics
+    ///
+    /// This function panics if the I2C bus has already been taken or if its SERIAL instance is in
+    /// use by another driver (see `try_take`)
     pub fn take() -> Self {
+        match Self::try_take() {
+            Ok(twim) => twim,
+            Err(_) => panic!("`Twim` has already been taken or SERIAL0 is in use"),
+        }
+    }
+
+    /// Like `take` but returns an error instead of panicking
+    ///
+    /// The TWIM uses the `Serial0` instance; see the `instance` module
+    pub fn try_take() -> Result<Self, InUse> {
         // NOTE peripheral initialization is done in `#[pre_init]`
 
-        static TAKEN: AtomicBool = AtomicBool::new(false);
+        instance::claim(Serial::Serial0, Owner::Twim)?;
 
-        if TAKEN
-            .compare_exchange_weak(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .is_ok()
-        {
-            Self {
-                _not_sync: NotSync::new(),
-            }
-        } else {
-            panic!("`Twim` has already been taken")
+        Ok(Self {
+            _not_sync: NotSync::new(),
+        })
+    }
+
+    /// Gives up the driver in exchange for direct access to the peripheral; see the `token`
+    /// module
+    pub fn into_token(self) -> Token<TWIM0> {
+        Token::new()
+    }
+
+    /// Gets the driver back from the peripheral token
+    pub fn from_token(_token: Token<TWIM0>) -> Self {
+        Self {
+            _not_sync: NotSync::new(),
         }
     }
 
@@ -105,7 +140,29 @@ impl Twim {
     /// Events: START - ADDR - (D -> H) - STOP
     ///
     /// `(D -> H)` denotes data being sent from the Device to the Host
+    ///
+    /// Buffers larger than 255 bytes are filled by several transactions, one per 255-byte chunk,
+    /// so there's a STOP after each chunk. On error, the previous chunks have been read and
+    /// `ShortRead` reports the number of bytes read across all chunks
     pub async fn read(&mut self, address: u8, buf: &mut [u8]) -> Result<(), Error> {
+        if buf.len() <= MAX_TRANSFER {
+            return self.read_chunk(address, buf).await;
+        }
+
+        let mut done = 0;
+        for chunk in buf.chunks_mut(MAX_TRANSFER) {
+            let n = chunk.len();
+            self.read_chunk(address, chunk)
+                .await
+                .map_err(|e| e.after(done))?;
+            done += n;
+        }
+
+        Ok(())
+    }
+
+    // NOTE `buf.len()` must be less than `MAXCNT`
+    async fn read_chunk(&mut self, address: u8, buf: &mut [u8]) -> Result<(), Error> {
         struct Read<'t, 'b> {
             _twim: &'t mut Twim,
             address: u8,
@@ -122,6 +179,9 @@ impl Twim {
                         TWIM0::borrow_unchecked(|twim| {
                             NVIC::mask(INTERRUPT);
 
+                            // the peripheral may have been disabled by `power::LowPower`
+                            twim.enable.write(|w| w.enable().enabled());
+
                             // NOTE program defensively: the user could poll a `Read` future once
                             // (and start the DMA transfer) and then `mem::forget` (or `drop`) it.
                             // We cannot assume any `async` method was driven to completion
@@ -190,6 +250,13 @@ impl Twim {
                                 twim.events_rxstarted.reset();
                                 twim.events_lastrx.reset();
 
+                                // uninstall the waker
+                                NVIC::mask(INTERRUPT);
+                                // NOTE(compiler_fence) the interrupt must be
+                                // disabled before we take down the waker
+                                atomic::compiler_fence(Ordering::Release);
+                                drop(unsafe { WAKER.take() });
+
                                 self.state = State::Finished;
 
                                 Poll::Ready(Err(Error::Src(twim.errorsrc.read().bits() as u8)))
@@ -210,11 +277,11 @@ impl Twim {
                                 atomic::compiler_fence(Ordering::Release);
                                 drop(unsafe { WAKER.take() });
 
-                                let amount = twim.rxd.amount.read().bits() as u8;
+                                let amount = twim.rxd.amount.read().bits() as usize;
 
                                 self.state = State::Finished;
 
-                                let n = self.buf.len() as u8;
+                                let n = self.buf.len();
                                 if amount == n {
                                     Poll::Ready(Ok(()))
                                 } else {
@@ -239,22 +306,29 @@ impl Twim {
         impl Drop for Read<'_, '_> {
             fn drop(&mut self) {
                 if self.state == State::InProgress {
-                    // stop the transfer
-                    todo!()
+                    stop_transfer();
                 }
             }
         }
 
-        // TODO do reads/writes in chunks?
-        assert!(buf.len() < MAXCNT);
+        // NOTE the TWIM cannot do an address-only read (it always clocks in at least one byte
+        // after the ADDR ACK) so there's nothing to do here. Use `probe` to check if a device is
+        // present
+        if buf.is_empty() {
+            return Ok(());
+        }
 
-        Read {
+        let _transaction = Transaction::start();
+        trace!(crate::trace::EventId::TwimStart(address));
+        let res = Read {
             _twim: self,
             address,
             buf,
             state: State::NotStarted,
         }
-        .await
+        .await;
+        trace!(crate::trace::EventId::TwimEnd(address));
+        res
     }
 
     /// `write` followed by `read` in a single transaction (without an intermediate STOP)
@@ -262,14 +336,47 @@ impl Twim {
     /// Events: START - ADDR - (H -> D) - reSTART - ADDR - (D -> H) - STOP
     ///
     /// `reSTART` denotes a "repeated START"
+    ///
+    /// If `rd_buf` is larger than 255 bytes the first 255-byte chunk is read as above and the rest
+    /// are read as in `read` (a STOP follows each chunk)
+    ///
+    /// Returns `Err(TooLong)`, without starting the transaction, if `rd_buf` is not empty and
+    /// `wr_buf` is larger than 255 bytes
     pub async fn write_then_read(
         &mut self,
         address: u8,
         wr_buf: &[u8],
         rd_buf: &mut [u8],
     ) -> Result<(), Error> {
-        // TODO do reads/writes in chunks?
-        assert!(wr_buf.len() < MAXCNT && rd_buf.len() < MAXCNT);
+        if wr_buf.len() > MAX_TRANSFER && !rd_buf.is_empty() {
+            return Err(Error::TooLong(wr_buf.len()));
+        }
+
+        if rd_buf.len() > MAX_TRANSFER && !wr_buf.is_empty() {
+            let (first, rest) = rd_buf.split_at_mut(MAX_TRANSFER);
+            self.write_then_read_chunk(address, wr_buf, first).await?;
+            return self
+                .read(address, rest)
+                .await
+                .map_err(|e| e.after(MAX_TRANSFER));
+        }
+
+        self.write_then_read_chunk(address, wr_buf, rd_buf).await
+    }
+
+    // NOTE `wr_buf.len()` and `rd_buf.len()` must be less than `MAXCNT` (unless the other buffer
+    // is empty)
+    async fn write_then_read_chunk(
+        &mut self,
+        address: u8,
+        wr_buf: &[u8],
+        rd_buf: &mut [u8],
+    ) -> Result<(), Error> {
+        if rd_buf.is_empty() {
+            return self.write(address, wr_buf).await;
+        } else if wr_buf.is_empty() {
+            return self.read(address, rd_buf).await;
+        }
 
         if crate::slice_in_ram(wr_buf) {
             self.write_from_ram_then_read(address, wr_buf, rd_buf).await
@@ -282,6 +389,288 @@ impl Twim {
         }
     }
 
+    /// `write` followed by several `read`s in a single transaction (without intermediate STOPs)
+    ///
+    /// Events: START - ADDR - (H -> D) - reSTART - ADDR - (D -> H: `rd_bufs[0]`) - .. -
+    /// (D -> H: `rd_bufs[n-1]`) - STOP
+    ///
+    /// The read buffers are filled in order, as if they were a single contiguous buffer; this is
+    /// meant for devices that auto-increment the register address on each read byte. Each buffer
+    /// is filled by its own DMA transfer; between buffers the bus is suspended (SCL held low) only
+    /// for as long as it takes to hand the next buffer to the DMA
+    ///
+    /// Returns `Err(TooLong)`, without starting the transaction, if `wr_buf` or any of the read
+    /// buffers is larger than 255 bytes. On error, `ShortRead` reports the number of bytes read
+    /// across all buffers
+    pub async fn write_then_reads(
+        &mut self,
+        address: u8,
+        wr_buf: &[u8],
+        rd_bufs: &mut [&mut [u8]],
+    ) -> Result<(), Error> {
+        if let Some(len) = iter::once(wr_buf.len())
+            .chain(rd_bufs.iter().map(|buf| buf.len()))
+            .find(|len| *len > MAX_TRANSFER)
+        {
+            return Err(Error::TooLong(len));
+        }
+
+        // NOTE empty buffers would never raise LASTRX; they are skipped
+        let n = rd_bufs.iter().filter(|buf| !buf.is_empty()).count();
+        if n == 0 {
+            return self.write(address, wr_buf).await;
+        }
+
+        let mut buf = [0; MAXCNT];
+        let wr_buf = if crate::slice_in_ram(wr_buf) {
+            wr_buf
+        } else {
+            // NOTE EasyDMA can't read from Flash
+            let len = wr_buf.len();
+            buf[..len].copy_from_slice(wr_buf);
+            &buf[..len]
+        };
+
+        let mut done = 0;
+        for (i, rd_buf) in rd_bufs.iter_mut().filter(|buf| !buf.is_empty()).enumerate() {
+            let first = i == 0;
+            let last = i + 1 == n;
+            let wr_buf = if first { wr_buf } else { &[] };
+            // NOTE if the segment is cancelled while in progress its destructor stops the
+            // transaction (see `stop_transfer`)
+            if let Err(e) = self
+                .read_segment(address, wr_buf, rd_buf, first, last)
+                .await
+            {
+                if !last {
+                    release_suspended_bus();
+                }
+
+                return Err(e.after(done));
+            }
+            done += rd_buf.len();
+        }
+
+        Ok(())
+    }
+
+    // Fills one buffer of a `write_then_reads` transaction; `wr_buf` points into RAM and is only
+    // sent by the first segment
+    //
+    // The first segment starts the transaction; the others resume it. Segments other than the last
+    // one suspend the bus after their last byte (LASTRX -> SUSPEND) and complete on SUSPENDED; the
+    // last one ends the transaction (LASTRX -> STOP) and completes on STOPPED
+    async fn read_segment(
+        &mut self,
+        address: u8,
+        wr_buf: &[u8],
+        rd_buf: &mut [u8],
+        first: bool,
+        last: bool,
+    ) -> Result<(), Error> {
+        struct Segment<'t, 'b> {
+            _twim: &'t mut Twim,
+            address: u8,
+            wr_buf: &'b [u8],
+            rd_buf: &'b mut [u8],
+            first: bool,
+            last: bool,
+            state: State,
+        }
+
+        impl Future for Segment<'_, '_> {
+            type Output = Result<(), Error>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+                match self.state {
+                    State::NotStarted => {
+                        TWIM0::borrow_unchecked(|twim| {
+                            NVIC::mask(INTERRUPT);
+
+                            if self.first {
+                                // the peripheral may have been disabled by `power::LowPower`
+                                twim.enable.write(|w| w.enable().enabled());
+
+                                // NOTE program defensively; see `write_from_ram`
+                                if twim.events_rxstarted.read().bits() != 0
+                                    || twim.events_txstarted.read().bits() != 0
+                                {
+                                    // abort any pending transaction
+                                    twim.tasks_stop.write(|w| unsafe { w.bits(1) });
+
+                                    // clear any unhandled error
+                                    twim.errorsrc.reset();
+
+                                    // clear any unhandled event
+                                    twim.events_error.reset();
+                                    twim.events_lastrx.reset();
+                                    twim.events_lasttx.reset();
+                                    twim.events_stopped.reset();
+                                }
+
+                                // NOTE(unsafe) this operation is not unsafe at all
+                                twim.address
+                                    .write(|w| unsafe { w.address().bits(self.address) });
+                            }
+
+                            let write = !self.wr_buf.is_empty();
+                            if write {
+                                twim.txd.ptr.write(|w| unsafe {
+                                    w.ptr().bits(self.wr_buf.as_ptr() as u32)
+                                });
+                                twim.txd.maxcnt.write(|w| unsafe {
+                                    w.maxcnt().bits(self.wr_buf.len() as u16)
+                                });
+                            }
+
+                            twim.rxd.ptr.write(|w| unsafe {
+                                w.ptr().bits(self.rd_buf.as_mut_ptr() as u32)
+                            });
+                            twim.rxd
+                                .maxcnt
+                                .write(|w| unsafe { w.maxcnt().bits(self.rd_buf.len() as u16) });
+
+                            let last = self.last;
+                            twim.shorts.write(|w| {
+                                // start reading (with a repeated START) after the write
+                                let w = if write {
+                                    w.lasttx_startrx().set_bit()
+                                } else {
+                                    w
+                                };
+
+                                if last {
+                                    // send STOP after last byte is received
+                                    w.lastrx_stop().set_bit()
+                                } else {
+                                    // hold the bus after the last byte is received
+                                    w.lastrx_suspend().set_bit()
+                                }
+                            });
+                            if !last {
+                                twim.intenset.write(|w| w.suspended().set_bit());
+                            }
+
+                            // here we finishing transferring the slices to the DMA; all previous
+                            // memory operations on the slices should be finished before then, thus
+                            // the compiler fence
+                            atomic::compiler_fence(Ordering::Release);
+                            if write {
+                                twim.tasks_starttx.write(|w| unsafe { w.bits(1) });
+                            } else {
+                                twim.tasks_startrx.write(|w| unsafe { w.bits(1) });
+                            }
+                            if !self.first {
+                                // the previous segment left the bus suspended
+                                twim.tasks_resume.write(|w| unsafe { w.bits(1) });
+                            }
+
+                            // install the waker
+                            unsafe {
+                                WAKER = Some(cx.waker().clone());
+
+                                // updating the `WAKER` needs to be complete before unmasking the
+                                // interrupt; hence the compiler fence
+                                atomic::compiler_fence(Ordering::Release);
+                                NVIC::unmask(INTERRUPT);
+                            }
+
+                            self.state = State::InProgress;
+
+                            Poll::Pending
+                        })
+                    }
+
+                    State::InProgress => {
+                        TWIM0::borrow_unchecked(|twim| {
+                            let done = if self.last {
+                                twim.events_stopped.read().bits() != 0
+                            } else {
+                                twim.events_suspended.read().bits() != 0
+                            };
+
+                            if twim.events_error.read().bits() != 0 || done {
+                                // slices have been handed back to us; any future operation on
+                                // them should not be reordered to before this point
+                                atomic::compiler_fence(Ordering::Acquire);
+
+                                twim.intenclr.write(|w| w.suspended().set_bit());
+                                twim.events_suspended.reset();
+                                twim.events_stopped.reset();
+                                twim.events_txstarted.reset();
+                                twim.events_rxstarted.reset();
+                                twim.events_lasttx.reset();
+                                twim.events_lastrx.reset();
+
+                                // uninstall the waker
+                                NVIC::mask(INTERRUPT);
+                                // NOTE(compiler_fence) the interrupt must be
+                                // disabled before we take down the waker
+                                atomic::compiler_fence(Ordering::Release);
+                                drop(unsafe { WAKER.take() });
+
+                                self.state = State::Finished;
+
+                                if twim.events_error.read().bits() != 0 {
+                                    twim.events_error.reset();
+                                    return Poll::Ready(Err(Error::Src(
+                                        twim.errorsrc.read().bits() as u8,
+                                    )));
+                                }
+
+                                let written = twim.txd.amount.read().bits() as usize;
+                                let n = self.wr_buf.len();
+                                if n != 0 && written != n {
+                                    return Poll::Ready(Err(Error::ShortWrite(written)));
+                                }
+
+                                let amount = twim.rxd.amount.read().bits() as usize;
+                                let n = self.rd_buf.len();
+                                if amount == n {
+                                    Poll::Ready(Ok(()))
+                                } else {
+                                    Poll::Ready(Err(Error::ShortRead(amount)))
+                                }
+                            } else {
+                                // spurious wake up; re-arm the one-shot interrupt
+                                unsafe {
+                                    NVIC::unmask(INTERRUPT);
+                                }
+
+                                Poll::Pending
+                            }
+                        })
+                    }
+
+                    State::Finished => unreachable!(),
+                }
+            }
+        }
+
+        impl Drop for Segment<'_, '_> {
+            fn drop(&mut self) {
+                if self.state == State::InProgress {
+                    stop_transfer();
+                }
+            }
+        }
+
+        let _transaction = Transaction::start();
+        trace!(crate::trace::EventId::TwimStart(address));
+        let res = Segment {
+            _twim: self,
+            address,
+            wr_buf,
+            rd_buf,
+            first,
+            last,
+            state: State::NotStarted,
+        }
+        .await;
+        trace!(crate::trace::EventId::TwimEnd(address));
+        res
+    }
+
     async fn write_from_ram_then_read(
         &mut self,
         address: u8,
@@ -305,6 +694,9 @@ impl Twim {
                         TWIM0::borrow_unchecked(|twim| {
                             NVIC::mask(INTERRUPT);
 
+                            // the peripheral may have been disabled by `power::LowPower`
+                            twim.enable.write(|w| w.enable().enabled());
+
                             // NOTE program defensively: the user could poll a `WriteThenRead`
                             // future once (and start the DMA transfer) and then `mem::forget` (or
                             // `drop`) it. We cannot assume any `async` method was driven to
@@ -384,6 +776,13 @@ impl Twim {
                                 twim.events_txstarted.reset();
                                 twim.events_lasttx.reset();
 
+                                // uninstall the waker
+                                NVIC::mask(INTERRUPT);
+                                // NOTE(compiler_fence) the interrupt must be
+                                // disabled before we take down the waker
+                                atomic::compiler_fence(Ordering::Release);
+                                drop(unsafe { WAKER.take() });
+
                                 self.state = State::Finished;
 
                                 Poll::Ready(Err(Error::Src(twim.errorsrc.read().bits() as u8)))
@@ -406,19 +805,18 @@ impl Twim {
                                 atomic::compiler_fence(Ordering::Release);
                                 drop(unsafe { WAKER.take() });
 
-                                let amount = twim.rxd.amount.read().bits() as u8;
+                                self.state = State::Finished;
 
-                                if amount != self.rd_buf.len() as u8 {
+                                let amount = twim.rxd.amount.read().bits() as usize;
+                                if amount != self.rd_buf.len() {
                                     return Poll::Ready(Err(Error::ShortRead(amount)));
                                 }
 
-                                let amount = twim.txd.amount.read().bits() as u8;
-                                if amount != self.wr_buf.len() as u8 {
+                                let amount = twim.txd.amount.read().bits() as usize;
+                                if amount != self.wr_buf.len() {
                                     return Poll::Ready(Err(Error::ShortWrite(amount)));
                                 }
 
-                                self.state = State::Finished;
-
                                 Poll::Ready(Ok(()))
                             } else {
                                 // spurious wake up; re-arm the one-shot interrupt
@@ -439,20 +837,23 @@ impl Twim {
         impl Drop for WriteThenRead<'_, '_> {
             fn drop(&mut self) {
                 if self.state == State::InProgress {
-                    // stop the transfer
-                    todo!()
+                    stop_transfer();
                 }
             }
         }
 
-        WriteThenRead {
+        let _transaction = Transaction::start();
+        trace!(crate::trace::EventId::TwimStart(address));
+        let res = WriteThenRead {
             _twim: self,
             address,
             rd_buf,
             state: State::NotStarted,
             wr_buf,
         }
-        .await
+        .await;
+        trace!(crate::trace::EventId::TwimEnd(address));
+        res
     }
 
     /// Sends `bytes` to the device with the specified address
@@ -460,11 +861,18 @@ impl Twim {
     /// Events: START - ADDR - (H -> D) - STOP
     ///
     /// `(H -> D)` denotes data being sent from the Host to the Device
+    ///
+    /// If `bytes` is empty only the address is sent (START - ADDR - STOP); see `probe`
+    ///
+    /// More than 255 `bytes` are sent in a single transaction, without intermediate STOPs; see
+    /// `write_chained`
     pub async fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Error> {
-        // TODO do writes in chunks?
-        assert!(bytes.len() < MAXCNT);
+        if bytes.len() > MAX_TRANSFER {
+            return self.write_chained(address, &[bytes]).await;
+        }
 
-        if crate::slice_in_ram(bytes) {
+        // NOTE the pointer of an empty slice can be dangling but the DMA won't access it
+        if bytes.is_empty() || crate::slice_in_ram(bytes) {
             self.write_from_ram(address, bytes).await
         } else {
             let mut buf = [0; MAXCNT];
@@ -474,16 +882,76 @@ impl Twim {
         }
     }
 
-    // NOTE `bytes` points into RAM
-    async fn write_from_ram(&mut self, address: u8, bytes: &[u8]) -> Result<(), Error> {
-        struct Write<'t, 'b> {
-            _twim: &'t Twim,
+    /// Sends all the `chunks`, in order, to the device with the specified address in a single
+    /// transaction
+    ///
+    /// Events: START - ADDR - (H -> D: `chunks[0]`) - .. - (H -> D: `chunks[n-1]`) - STOP
+    ///
+    /// This is equivalent to `write`-ing the concatenation of `chunks` but without copying the
+    /// chunks into a contiguous buffer. Between chunks the bus is suspended (SCL held low) only for
+    /// as long as it takes to hand the next chunk to the DMA; no STOP is sent until the last chunk
+    ///
+    /// Chunks larger than 255 bytes are split into several DMA transfers. On error, `ShortWrite`
+    /// reports the number of bytes written across all chunks
+    pub async fn write_chained(&mut self, address: u8, chunks: &[&[u8]]) -> Result<(), Error> {
+        // NOTE empty chunks would never raise LASTTX; `chunks` yields no segment for them
+        let segments = || chunks.iter().flat_map(|chunk| chunk.chunks(MAX_TRANSFER));
+        let n = segments().count();
+        if n == 0 {
+            // NOTE not `write`; `async fn`s can't recurse
+            return self.write_from_ram(address, &[]).await;
+        }
+
+        let mut buf = [0; MAXCNT];
+        let mut done = 0;
+        for (i, chunk) in segments().enumerate() {
+            let bytes = if crate::slice_in_ram(chunk) {
+                chunk
+            } else {
+                // NOTE the previous chunk has already been sent so `buf` can be reused
+                let len = chunk.len();
+                buf[..len].copy_from_slice(chunk);
+                &buf[..len]
+            };
+
+            let last = i + 1 == n;
+            // NOTE if the segment is cancelled while in progress its destructor stops the
+            // transaction (see `stop_transfer`)
+            if let Err(e) = self.write_segment(address, bytes, i == 0, last).await {
+                if !last {
+                    release_suspended_bus();
+                }
+
+                return Err(e.after(done));
+            }
+            done += bytes.len();
+        }
+
+        Ok(())
+    }
+
+    // Sends one segment of a `write_chained` transaction; `bytes` points into RAM
+    //
+    // The first segment starts the transaction; the others resume it. Segments other than the last
+    // one suspend the bus after their last byte (LASTTX -> SUSPEND) and complete on SUSPENDED; the
+    // last one ends the transaction (LASTTX -> STOP) and completes on STOPPED
+    async fn write_segment(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        first: bool,
+        last: bool,
+    ) -> Result<(), Error> {
+        struct Segment<'t, 'b> {
+            _twim: &'t mut Twim,
             address: u8,
             bytes: &'b [u8],
+            first: bool,
+            last: bool,
             state: State,
         }
 
-        impl Future for Write<'_, '_> {
+        impl Future for Segment<'_, '_> {
             type Output = Result<(), Error>;
 
             fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
@@ -492,29 +960,32 @@ impl Twim {
                         TWIM0::borrow_unchecked(|twim| {
                             NVIC::mask(INTERRUPT);
 
-                            // NOTE program defensively: the user could poll a `Write` future (start
-                            // the transfer) and then `mem::forget` it. We cannot assume any `async`
-                            // method was driven to completion
-                            if twim.events_rxstarted.read().bits() != 0
-                                || twim.events_txstarted.read().bits() != 0
-                            {
-                                // abort any pending transaction
-                                twim.tasks_stop.write(|w| unsafe { w.bits(1) });
-
-                                // clear any unhandled error
-                                twim.errorsrc.reset();
+                            if self.first {
+                                // the peripheral may have been disabled by `power::LowPower`
+                                twim.enable.write(|w| w.enable().enabled());
+
+                                // NOTE program defensively; see `write_from_ram`
+                                if twim.events_rxstarted.read().bits() != 0
+                                    || twim.events_txstarted.read().bits() != 0
+                                {
+                                    // abort any pending transaction
+                                    twim.tasks_stop.write(|w| unsafe { w.bits(1) });
+
+                                    // clear any unhandled error
+                                    twim.errorsrc.reset();
+
+                                    // clear any unhandled event
+                                    twim.events_error.reset();
+                                    twim.events_lastrx.reset();
+                                    twim.events_lasttx.reset();
+                                    twim.events_stopped.reset();
+                                }
 
-                                // clear any unhandled event
-                                twim.events_error.reset();
-                                twim.events_lastrx.reset();
-                                twim.events_lasttx.reset();
-                                twim.events_stopped.reset();
+                                // NOTE(unsafe) this operation is not unsafe at all
+                                twim.address
+                                    .write(|w| unsafe { w.address().bits(self.address) });
                             }
 
-                            // NOTE(unsafe) this operation is not unsafe at all
-                            twim.address
-                                .write(|w| unsafe { w.address().bits(self.address) });
-
                             twim.txd
                                 .ptr
                                 .write(|w| unsafe { w.ptr().bits(self.bytes.as_ptr() as u32) });
@@ -522,14 +993,24 @@ impl Twim {
                                 .maxcnt
                                 .write(|w| unsafe { w.maxcnt().bits(self.bytes.len() as u16) });
 
-                            // send STOP after last byte is transmitted
-                            twim.shorts.write(|w| w.lasttx_stop().set_bit());
+                            if self.last {
+                                // send STOP after last byte is transmitted
+                                twim.shorts.write(|w| w.lasttx_stop().set_bit());
+                            } else {
+                                // hold the bus after the last byte is transmitted
+                                twim.shorts.write(|w| w.lasttx_suspend().set_bit());
+                                twim.intenset.write(|w| w.suspended().set_bit());
+                            }
 
                             // here we finishing transferring the slice to the DMA; all previous
                             // memory operations on the slice should be finished before then, thus
                             // the compiler fence
                             atomic::compiler_fence(Ordering::Release);
                             twim.tasks_starttx.write(|w| unsafe { w.bits(1) });
+                            if !self.first {
+                                // the previous segment left the bus suspended
+                                twim.tasks_resume.write(|w| unsafe { w.bits(1) });
+                            }
 
                             // install the waker
                             unsafe {
@@ -549,18 +1030,412 @@ impl Twim {
 
                     State::InProgress => {
                         TWIM0::borrow_unchecked(|twim| {
-                            if twim.events_error.read().bits() != 0 {
+                            let done = if self.last {
+                                twim.events_stopped.read().bits() != 0
+                            } else {
+                                twim.events_suspended.read().bits() != 0
+                            };
+
+                            if twim.events_error.read().bits() != 0 || done {
                                 // slice has been handed back to us; any future operation on the
                                 // slice should not be reordered to before this point
                                 atomic::compiler_fence(Ordering::Acquire);
 
-                                // XXX do we need to clear `events_{stopped,lasttx}` here?
+                                twim.intenclr.write(|w| w.suspended().set_bit());
+                                twim.events_suspended.reset();
                                 twim.events_stopped.reset();
                                 twim.events_txstarted.reset();
                                 twim.events_lasttx.reset();
 
-                                self.state = State::Finished;
-
+                                // uninstall the waker
+                                NVIC::mask(INTERRUPT);
+                                // NOTE(compiler_fence) the interrupt must be
+                                // disabled before we take down the waker
+                                atomic::compiler_fence(Ordering::Release);
+                                drop(unsafe { WAKER.take() });
+
+                                self.state = State::Finished;
+
+                                if twim.events_error.read().bits() != 0 {
+                                    twim.events_error.reset();
+                                    return Poll::Ready(Err(Error::Src(
+                                        twim.errorsrc.read().bits() as u8,
+                                    )));
+                                }
+
+                                let amount = twim.txd.amount.read().bits() as usize;
+                                let n = self.bytes.len();
+                                if amount == n {
+                                    Poll::Ready(Ok(()))
+                                } else {
+                                    Poll::Ready(Err(Error::ShortWrite(amount)))
+                                }
+                            } else {
+                                // spurious wake up; re-arm the one-shot interrupt
+                                unsafe {
+                                    NVIC::unmask(INTERRUPT);
+                                }
+
+                                Poll::Pending
+                            }
+                        })
+                    }
+
+                    State::Finished => unreachable!(),
+                }
+            }
+        }
+
+        impl Drop for Segment<'_, '_> {
+            fn drop(&mut self) {
+                if self.state == State::InProgress {
+                    stop_transfer();
+                }
+            }
+        }
+
+        let _transaction = Transaction::start();
+        trace!(crate::trace::EventId::TwimStart(address));
+        let res = Segment {
+            _twim: self,
+            address,
+            bytes,
+            first,
+            last,
+            state: State::NotStarted,
+        }
+        .await;
+        trace!(crate::trace::EventId::TwimEnd(address));
+        res
+    }
+
+    /// Frees a bus whose SDA line is held low by a device, e.g. a device that was reset (or whose
+    /// host was reset) in the middle of a transfer
+    ///
+    /// The TWIM is disabled while SCL is clocked (up to 9 pulses, at ~100 KHz) until the device
+    /// releases SDA; then a STOP condition is generated. This busy waits for up to ~100 us
+    pub fn recover(&mut self) {
+        // ~5 us @ 64 MHz; half a period of the 100 KHz bus clock
+        const HALF_PERIOD: u32 = 320;
+
+        TWIM0::borrow_unchecked(|twim| twim.enable.write(|w| w.enable().disabled()));
+
+        P0::borrow_unchecked(|p0| {
+            let sda_is_high = || p0.in_.read().bits() & (1 << SDA_PIN) != 0;
+
+            // drive the lines (open drain) as GPIOs; a 1 releases the line
+            p0.outset
+                .write(|w| unsafe { w.bits(1 << SDA_PIN | 1 << SCL_PIN) });
+            p0.dirset
+                .write(|w| unsafe { w.bits(1 << SDA_PIN | 1 << SCL_PIN) });
+
+            for _ in 0..9 {
+                if sda_is_high() {
+                    break;
+                }
+
+                p0.outclr.write(|w| unsafe { w.bits(1 << SCL_PIN) });
+                asm::delay(HALF_PERIOD);
+                p0.outset.write(|w| unsafe { w.bits(1 << SCL_PIN) });
+                asm::delay(HALF_PERIOD);
+            }
+
+            // STOP: SDA goes high while SCL is high
+            p0.outclr.write(|w| unsafe { w.bits(1 << SDA_PIN) });
+            asm::delay(HALF_PERIOD);
+            p0.outset.write(|w| unsafe { w.bits(1 << SDA_PIN) });
+            asm::delay(HALF_PERIOD);
+
+            // hand the pins back to the TWIM
+            p0.dirclr
+                .write(|w| unsafe { w.bits(1 << SDA_PIN | 1 << SCL_PIN) });
+        });
+
+        TWIM0::borrow_unchecked(|twim| twim.enable.write(|w| w.enable().enabled()));
+    }
+
+    /// Reads the register `R` of the device with the specified address
+    ///
+    /// See [`i2c_reg!`](../macro.i2c_reg.html)
+    pub async fn read_register<R>(&mut self, address: u8) -> Result<R::Value, Error>
+    where
+        R: Register,
+    {
+        let mut bytes = R::Bytes::default();
+        self.read_reg(address, R::ADDRESS, bytes.as_mut()).await?;
+        Ok(R::decode(bytes))
+    }
+
+    /// Writes `value` into the register `R` of the device with the specified address
+    ///
+    /// See [`i2c_reg!`](../macro.i2c_reg.html)
+    pub async fn write_register<R>(&mut self, address: u8, value: R::Value) -> Result<(), Error>
+    where
+        R: Register,
+    {
+        let bytes = R::encode(value);
+        self.write_reg(address, R::ADDRESS, bytes.as_ref()).await
+    }
+
+    /// Reads the register `reg` (and the following ones, if `buf` is larger than 1 byte) of the
+    /// device with the specified address
+    ///
+    /// Events: START - ADDR - (H -> D: `reg`) - reSTART - ADDR - (D -> H) - STOP
+    pub async fn read_reg(&mut self, address: u8, reg: u8, buf: &mut [u8]) -> Result<(), Error> {
+        self.write_then_read(address, &[reg], buf).await
+    }
+
+    /// Reads the 8-bit register `reg` of the device with the specified address
+    pub async fn read_reg_u8(&mut self, address: u8, reg: u8) -> Result<u8, Error> {
+        let mut buf = [0];
+        self.read_reg(address, reg, &mut buf).await?;
+        Ok(buf[0])
+    }
+
+    /// Reads the 16-bit, big endian (most significant byte first), register `reg` of the device
+    /// with the specified address
+    pub async fn read_reg_u16(&mut self, address: u8, reg: u8) -> Result<u16, Error> {
+        let mut buf = [0; 2];
+        self.read_reg(address, reg, &mut buf).await?;
+        Ok(u16::from_be_bytes(buf))
+    }
+
+    /// Reads the 16-bit, little endian (least significant byte first), register `reg` of the
+    /// device with the specified address
+    pub async fn read_reg_u16_le(&mut self, address: u8, reg: u8) -> Result<u16, Error> {
+        let mut buf = [0; 2];
+        self.read_reg(address, reg, &mut buf).await?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    /// Reads the 32-bit, big endian (most significant byte first), register `reg` of the device
+    /// with the specified address
+    pub async fn read_reg_u32(&mut self, address: u8, reg: u8) -> Result<u32, Error> {
+        let mut buf = [0; 4];
+        self.read_reg(address, reg, &mut buf).await?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    /// Reads the 32-bit, little endian (least significant byte first), register `reg` of the
+    /// device with the specified address
+    pub async fn read_reg_u32_le(&mut self, address: u8, reg: u8) -> Result<u32, Error> {
+        let mut buf = [0; 4];
+        self.read_reg(address, reg, &mut buf).await?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    /// Writes `data` into the register `reg` (and the following ones, if `data` is larger than 1
+    /// byte) of the device with the specified address
+    ///
+    /// Events: START - ADDR - (H -> D: `reg`, `data`) - STOP
+    pub async fn write_reg(&mut self, address: u8, reg: u8, data: &[u8]) -> Result<(), Error> {
+        if data.len() + 1 > MAX_TRANSFER {
+            return self.write_chained(address, &[&[reg], data]).await;
+        }
+
+        let mut buf = [0; MAXCNT];
+        let n = data.len() + 1;
+        buf[0] = reg;
+        buf[1..n].copy_from_slice(data);
+        self.write_from_ram(address, &buf[..n]).await
+    }
+
+    /// Writes `value` into the 8-bit register `reg` of the device with the specified address
+    pub async fn write_reg_u8(&mut self, address: u8, reg: u8, value: u8) -> Result<(), Error> {
+        self.write_from_ram(address, &[reg, value]).await
+    }
+
+    /// Writes `value` into the 16-bit, big endian (most significant byte first), register `reg`
+    /// of the device with the specified address
+    pub async fn write_reg_u16(&mut self, address: u8, reg: u8, value: u16) -> Result<(), Error> {
+        let [hi, lo] = value.to_be_bytes();
+        self.write_from_ram(address, &[reg, hi, lo]).await
+    }
+
+    /// Checks if a device with the specified address is present on the bus
+    ///
+    /// Events: START - ADDR - STOP
+    ///
+    /// Returns `Ok(true)` if the device ACK-ed its address and `Ok(false)` if it NACK-ed it
+    pub async fn probe(&mut self, address: u8) -> Result<bool, Error> {
+        match self.write(address, &[]).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.is_address_nack() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Checks if the device with the specified address is the expected one by reading its
+    /// identification (e.g. `WHO_AM_I`) register, `id_reg`, and comparing its contents to
+    /// `expected`
+    ///
+    /// Events: START - ADDR - TX (`id_reg`) - RESTART - ADDR - RX (`expected.len()` bytes) - STOP
+    ///
+    /// Returns `Ok(false)` if no device answers at `address`
+    ///
+    /// Returns `Err(TooLong)`, without starting the transaction, if `expected` is longer than 16
+    /// bytes
+    pub async fn identify(
+        &mut self,
+        address: u8,
+        id_reg: u8,
+        expected: &[u8],
+    ) -> Result<bool, Error> {
+        let mut buf = [0; 16];
+        if expected.len() > buf.len() {
+            return Err(Error::TooLong(expected.len()));
+        }
+        let id = &mut buf[..expected.len()];
+
+        match self.write_then_read(address, &[id_reg], id).await {
+            Ok(()) => Ok(id == expected),
+            Err(e) if e.is_address_nack() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    // NOTE `bytes` points into RAM
+    async fn write_from_ram(&mut self, address: u8, bytes: &[u8]) -> Result<(), Error> {
+        struct Write<'t, 'b> {
+            _twim: &'t Twim,
+            address: u8,
+            bytes: &'b [u8],
+            state: State,
+        }
+
+        impl Future for Write<'_, '_> {
+            type Output = Result<(), Error>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+                match self.state {
+                    State::NotStarted => {
+                        TWIM0::borrow_unchecked(|twim| {
+                            NVIC::mask(INTERRUPT);
+
+                            // the peripheral may have been disabled by `power::LowPower`
+                            twim.enable.write(|w| w.enable().enabled());
+
+                            // NOTE program defensively: the user could poll a `Write` future (start
+                            // the transfer) and then `mem::forget` it. We cannot assume any `async`
+                            // method was driven to completion
+                            if twim.events_rxstarted.read().bits() != 0
+                                || twim.events_txstarted.read().bits() != 0
+                            {
+                                // abort any pending transaction
+                                twim.tasks_stop.write(|w| unsafe { w.bits(1) });
+
+                                // clear any unhandled error
+                                twim.errorsrc.reset();
+
+                                // clear any unhandled event
+                                twim.events_error.reset();
+                                twim.events_lastrx.reset();
+                                twim.events_lasttx.reset();
+                                twim.events_stopped.reset();
+                            }
+
+                            // NOTE(unsafe) this operation is not unsafe at all
+                            twim.address
+                                .write(|w| unsafe { w.address().bits(self.address) });
+
+                            twim.txd
+                                .ptr
+                                .write(|w| unsafe { w.ptr().bits(self.bytes.as_ptr() as u32) });
+                            twim.txd
+                                .maxcnt
+                                .write(|w| unsafe { w.maxcnt().bits(self.bytes.len() as u16) });
+
+                            if self.bytes.is_empty() {
+                                // there's no last byte so no LASTTX event; STOP is requested
+                                // right after the transfer starts and it's issued after ADDR
+                                twim.shorts.reset();
+                                twim.tasks_starttx.write(|w| unsafe { w.bits(1) });
+                                twim.tasks_stop.write(|w| unsafe { w.bits(1) });
+                            } else {
+                                // send STOP after last byte is transmitted
+                                twim.shorts.write(|w| w.lasttx_stop().set_bit());
+
+                                // here we finishing transferring the slice to the DMA; all
+                                // previous memory operations on the slice should be finished
+                                // before then, thus the compiler fence
+                                atomic::compiler_fence(Ordering::Release);
+                                twim.tasks_starttx.write(|w| unsafe { w.bits(1) });
+                            }
+
+                            // install the waker
+                            unsafe {
+                                WAKER = Some(cx.waker().clone());
+
+                                // updating the `WAKER` needs to be complete before unmasking the
+                                // interrupt; hence the compiler fence
+                                atomic::compiler_fence(Ordering::Release);
+                                NVIC::unmask(INTERRUPT);
+                            }
+
+                            self.state = State::InProgress;
+
+                            Poll::Pending
+                        })
+                    }
+
+                    State::InProgress if self.bytes.is_empty() => {
+                        TWIM0::borrow_unchecked(|twim| {
+                            // NOTE the STOP was requested up front so wait for STOPPED even if the
+                            // address was NACK-ed; that way no stale event is left behind
+                            if twim.events_stopped.read().bits() != 0 {
+                                twim.events_stopped.reset();
+                                twim.events_txstarted.reset();
+                                twim.events_error.reset();
+
+                                // uninstall the waker
+                                NVIC::mask(INTERRUPT);
+                                // NOTE(compiler_fence) the interrupt must be
+                                // disabled before we take down the waker
+                                atomic::compiler_fence(Ordering::Release);
+                                drop(unsafe { WAKER.take() });
+
+                                self.state = State::Finished;
+
+                                let src = twim.errorsrc.read().bits();
+                                if src == 0 {
+                                    Poll::Ready(Ok(()))
+                                } else {
+                                    // write-1-to-clear register
+                                    twim.errorsrc.write(|w| unsafe { w.bits(src) });
+                                    Poll::Ready(Err(Error::Src(src as u8)))
+                                }
+                            } else {
+                                // spurious wake up; re-arm the one-shot interrupt
+                                unsafe {
+                                    NVIC::unmask(INTERRUPT);
+                                }
+
+                                Poll::Pending
+                            }
+                        })
+                    }
+
+                    State::InProgress => {
+                        TWIM0::borrow_unchecked(|twim| {
+                            if twim.events_error.read().bits() != 0 {
+                                // slice has been handed back to us; any future operation on the
+                                // slice should not be reordered to before this point
+                                atomic::compiler_fence(Ordering::Acquire);
+
+                                // XXX do we need to clear `events_{stopped,lasttx}` here?
+                                twim.events_stopped.reset();
+                                twim.events_txstarted.reset();
+                                twim.events_lasttx.reset();
+
+                                // uninstall the waker
+                                NVIC::mask(INTERRUPT);
+                                // NOTE(compiler_fence) the interrupt must be
+                                // disabled before we take down the waker
+                                atomic::compiler_fence(Ordering::Release);
+                                drop(unsafe { WAKER.take() });
+
+                                self.state = State::Finished;
+
                                 Poll::Ready(Err(Error::Src(twim.errorsrc.read().bits() as u8)))
                             } else if twim.events_stopped.read().bits() != 0 {
                                 // slice has been handed back to us; any future operation on the
@@ -579,11 +1454,11 @@ impl Twim {
                                 atomic::compiler_fence(Ordering::Release);
                                 drop(unsafe { WAKER.take() });
 
-                                let amount = twim.txd.amount.read().bits() as u8;
+                                let amount = twim.txd.amount.read().bits() as usize;
 
                                 self.state = State::Finished;
 
-                                let n = self.bytes.len() as u8;
+                                let n = self.bytes.len();
                                 if amount == n {
                                     Poll::Ready(Ok(()))
                                 } else {
@@ -608,27 +1483,291 @@ impl Twim {
         impl Drop for Write<'_, '_> {
             fn drop(&mut self) {
                 if self.state == State::InProgress {
-                    // stop the transfer
-                    todo!()
+                    stop_transfer();
                 }
             }
         }
 
-        Write {
+        let _transaction = Transaction::start();
+        trace!(crate::trace::EventId::TwimStart(address));
+        let res = Write {
             _twim: self,
             address,
             bytes,
             state: State::NotStarted,
         }
-        .await
+        .await;
+        trace!(crate::trace::EventId::TwimEnd(address));
+        res
     }
 }
 
+/// A device register (or a block of consecutive registers) with a fixed width and encoding
+///
+/// Use the [`i2c_reg!`](../macro.i2c_reg.html) macro to implement this trait
+pub trait Register {
+    /// Address of the (first) register
+    const ADDRESS: u8;
+
+    /// Raw contents of the register; a byte array
+    type Bytes: AsMut<[u8]> + AsRef<[u8]> + Default;
+
+    /// Decoded contents of the register
+    type Value;
+
+    /// Decodes the raw contents of the register
+    fn decode(bytes: Self::Bytes) -> Self::Value;
+
+    /// Encodes a value into raw register contents
+    fn encode(value: Self::Value) -> Self::Bytes;
+}
+
+/// Declares device registers
+///
+/// Each register becomes a type that implements [`twim::Register`](twim/trait.Register.html) and
+/// that can be used with `Twim::read_register` and `Twim::write_register`
+///
+/// ``` ignore
+/// i2c_reg! {
+///     /// 8-bit register at address 0x0e
+///     pub struct Control: 0x0e => u8;
+///     /// 16-bit register, most significant byte first
+///     pub struct Config: 0x10 => u16_be;
+///     /// 16-bit register, least significant byte first
+///     pub struct Threshold: 0x12 => u16_le;
+///     /// 8-bit register that holds a BCD (Binary Coded Decimal) number
+///     pub struct Minutes: 0x01 => bcd;
+///     /// block of 3 consecutive 8-bit registers
+///     pub struct Time: 0x00 => [u8; 3];
+/// }
+///
+/// let ctrl: u8 = twim.read_register::<Control>(ADDRESS).await?;
+/// twim.write_register::<Minutes>(ADDRESS, 42).await?;
+/// ```
+#[macro_export]
+macro_rules! i2c_reg {
+    ($($(#[$attr:meta])* $vis:vis struct $name:ident: $addr:expr => $kind:tt;)+) => {
+        $(
+            $(#[$attr])*
+            $vis struct $name;
+
+            $crate::i2c_reg!(@impl $name, $addr, $kind);
+        )+
+    };
+
+    (@impl $name:ident, $addr:expr, u8) => {
+        impl $crate::twim::Register for $name {
+            const ADDRESS: u8 = $addr;
+            type Bytes = [u8; 1];
+            type Value = u8;
+
+            fn decode(bytes: [u8; 1]) -> u8 {
+                bytes[0]
+            }
+
+            fn encode(value: u8) -> [u8; 1] {
+                [value]
+            }
+        }
+    };
+
+    (@impl $name:ident, $addr:expr, u16_be) => {
+        impl $crate::twim::Register for $name {
+            const ADDRESS: u8 = $addr;
+            type Bytes = [u8; 2];
+            type Value = u16;
+
+            fn decode(bytes: [u8; 2]) -> u16 {
+                u16::from_be_bytes(bytes)
+            }
+
+            fn encode(value: u16) -> [u8; 2] {
+                value.to_be_bytes()
+            }
+        }
+    };
+
+    (@impl $name:ident, $addr:expr, u16_le) => {
+        impl $crate::twim::Register for $name {
+            const ADDRESS: u8 = $addr;
+            type Bytes = [u8; 2];
+            type Value = u16;
+
+            fn decode(bytes: [u8; 2]) -> u16 {
+                u16::from_le_bytes(bytes)
+            }
+
+            fn encode(value: u16) -> [u8; 2] {
+                value.to_le_bytes()
+            }
+        }
+    };
+
+    (@impl $name:ident, $addr:expr, bcd) => {
+        impl $crate::twim::Register for $name {
+            const ADDRESS: u8 = $addr;
+            type Bytes = [u8; 1];
+            type Value = u8;
+
+            fn decode(bytes: [u8; 1]) -> u8 {
+                10 * (bytes[0] >> 4) + (bytes[0] & 0b1111)
+            }
+
+            // NOTE `value` must be less than 100
+            fn encode(value: u8) -> [u8; 1] {
+                [(value / 10) << 4 | value % 10]
+            }
+        }
+    };
+
+    (@impl $name:ident, $addr:expr, [u8; $n:expr]) => {
+        impl $crate::twim::Register for $name {
+            const ADDRESS: u8 = $addr;
+            type Bytes = [u8; $n];
+            type Value = [u8; $n];
+
+            fn decode(bytes: [u8; $n]) -> [u8; $n] {
+                bytes
+            }
+
+            fn encode(value: [u8; $n]) -> [u8; $n] {
+                value
+            }
+        }
+    };
+}
+
 static mut WAKER: Option<Waker> = None;
 
+// a transaction is in progress; only tracked with the "reentrancy-guard" feature
+#[cfg(feature = "reentrancy-guard")]
+static IN_PROGRESS: atomic::AtomicBool = atomic::AtomicBool::new(false);
+
+// Marks a transaction as in progress until dropped
+//
+// `Twim` is a singleton and all transactions take `&mut self` so a transaction can only start
+// while another one is in progress if a transaction future was leaked (`mem::forget`) or through
+// `borrow_unchecked`. Either way the DMA transfer in progress would be silently corrupted; with
+// the "reentrancy-guard" feature enabled this panics instead
+struct Transaction;
+
+impl Transaction {
+    fn start() -> Self {
+        #[cfg(feature = "reentrancy-guard")]
+        {
+            if IN_PROGRESS.swap(true, Ordering::Relaxed) {
+                panic!("`Twim` transaction started while another one was in progress")
+            }
+        }
+
+        Transaction
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        #[cfg(feature = "reentrancy-guard")]
+        IN_PROGRESS.store(false, Ordering::Relaxed);
+    }
+}
+
+// Releases the bus after a failed segment of a chained transaction (see `write_chained` and
+// `write_then_reads`); the bus may have been left suspended so resume it and release it with a
+// STOP
+fn release_suspended_bus() {
+    TWIM0::borrow_unchecked(|twim| {
+        twim.shorts.reset();
+        twim.tasks_resume.write(|w| unsafe { w.bits(1) });
+        twim.tasks_stop.write(|w| unsafe { w.bits(1) });
+    });
+}
+
+// Stops the transfer in progress and waits until the DMA has released its buffer
+//
+// NOTE called when a transfer future is dropped before it completes, e.g. when it loses a
+// `select` against a timeout
+fn stop_transfer() {
+    // uninstall the waker
+    NVIC::mask(INTERRUPT);
+    // NOTE(compiler_fence) the interrupt must be disabled before we take down the waker
+    atomic::compiler_fence(Ordering::SeqCst);
+    drop(unsafe { WAKER.take() });
+
+    TWIM0::borrow_unchecked(|twim| {
+        twim.shorts.reset();
+        twim.intenclr.write(|w| w.suspended().set_bit());
+
+        if twim.events_stopped.read().bits() == 0 {
+            // NOTE a suspended transfer must be resumed before it can be stopped
+            twim.tasks_resume.write(|w| unsafe { w.bits(1) });
+            twim.tasks_stop.write(|w| unsafe { w.bits(1) });
+
+            let mut stopped = false;
+            for _ in 0..STOP_TIMEOUT {
+                if twim.events_stopped.read().bits() != 0 {
+                    stopped = true;
+                    break;
+                }
+            }
+
+            if !stopped {
+                // NOTE the bus is wedged (e.g. a device holds SCL low) so the STOP condition
+                // can't be sent. Disabling the peripheral stops the DMA; the next transaction
+                // enables it again. `Twim::recover` can be used to unwedge the bus
+                twim.enable.write(|w| w.enable().disabled());
+            }
+        }
+
+        // clear the events (and error) of the cancelled transfer
+        twim.errorsrc.reset();
+        twim.events_error.reset();
+        twim.events_lastrx.reset();
+        twim.events_lasttx.reset();
+        twim.events_rxstarted.reset();
+        twim.events_txstarted.reset();
+        twim.events_suspended.reset();
+        twim.events_stopped.reset();
+    });
+    NVIC::unpend(INTERRUPT);
+
+    // NOTE(compiler_fence) the DMA has released the buffer; operations on it must not be
+    // reordered to before this point
+    atomic::compiler_fence(Ordering::Acquire);
+}
+
+// NOTE(unsafe) the waker is only modified from thread mode
+pub(crate) fn is_idle() -> bool {
+    unsafe { WAKER.is_none() }
+}
+
+fn enable() {
+    // the instance may be in use by the SPIM
+    if instance::owner(Serial::Serial0) == Some(Owner::Spim) {
+        return;
+    }
+
+    TWIM0::borrow_unchecked(|twim| twim.enable.write(|w| w.enable().enabled()));
+}
+
+// NOTE the next transfer re-enables the peripheral
+pub(crate) fn disable() {
+    // the instance may be in use by the SPIM
+    if instance::owner(Serial::Serial0) == Some(Owner::Spim) {
+        return;
+    }
+
+    TWIM0::borrow_unchecked(|twim| twim.enable.write(|w| w.enable().disabled()));
+}
+
 #[allow(non_snake_case)]
 #[no_mangle]
 fn SPIM0_SPIS0_TWIM0_TWIS0_SPI0_TWI0() {
+    // the instance may be in use by the SPIM
+    if instance::owner(Serial::Serial0) == Some(Owner::Spim) {
+        crate::spim::on_interrupt(Serial::Serial0);
+        return;
+    }
+
     // NOTE(unsafe) the only other context that can access this static variable
     // runs at lower priority
     if let Some(waker) = unsafe { WAKER.as_ref() } {
@@ -649,14 +1788,339 @@ enum State {
 }
 
 /// I2C error
-#[derive(Debug)]
 pub enum Error {
-    /// Wrote less data than requested
-    ShortWrite(u8),
+    /// Wrote less data than requested; the number of bytes written
+    ShortWrite(usize),
 
-    /// Read less data than requested
-    ShortRead(u8),
+    /// Read less data than requested; the number of bytes read
+    ShortRead(usize),
 
     /// ERRORSRC encoded error
     Src(u8),
+
+    /// A buffer is larger than the operation allows (e.g. a single DMA transfer); the length of
+    /// the buffer
+    TooLong(usize),
+}
+
+/// A shared I2C bus that recovers from bus faults
+///
+/// Hand `twim()` to the drivers and run their operations through `run`: when an operation fails
+/// with a bus fault (see `Error::is_bus_fault`) the bus is recovered (see `Twim::recover`) before
+/// the error is returned so the next operation can succeed
+pub struct RecoveringBus {
+    twim: Mutex<Twim>,
+    recoveries: Cell<u32>,
+}
+
+impl RecoveringBus {
+    /// Wraps the I2C bus
+    pub fn new(twim: Twim) -> Self {
+        Self {
+            twim: Mutex::new(twim),
+            recoveries: Cell::new(0),
+        }
+    }
+
+    /// Returns the shared bus
+    pub fn twim(&self) -> &Mutex<Twim> {
+        &self.twim
+    }
+
+    /// Drives the driver operation `op` to completion; recovers the bus if `op` failed with a bus
+    /// fault
+    pub async fn run<T, E>(&self, op: impl Future<Output = Result<T, E>>) -> Result<T, E>
+    where
+        E: BusError,
+    {
+        let res = op.await;
+
+        if let Err(e) = &res {
+            if needs_recovery(e) {
+                self.twim.lock().await.recover();
+                self.recoveries.set(self.recoveries.get().wrapping_add(1));
+            }
+        }
+
+        res
+    }
+
+    /// Returns the number of times the bus has been recovered
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries.get()
+    }
+}
+
+// whether `RecoveringBus` recovers the bus after an operation failed with `e`
+fn needs_recovery(e: &impl BusError) -> bool {
+    e.twim_error().map(Error::is_bus_fault) == Some(true)
+}
+
+/// An I2C bus that is only enabled while it's in use
+///
+/// An enabled TWIM keeps the HFCLK requested while the core sleeps; see the `power` module. This
+/// wrapper enables the TWIM when the bus is locked and disables it when the lock is released. To
+/// avoid thrashing the enable bit, the bus stays enabled when another task is already waiting for
+/// it, and all the transfers done through one lock share one enable / disable cycle
+///
+/// Re-enabling the TWIM is a register write but the first transfer afterwards also has to wait
+/// for the HFCLK to start (a few microseconds with the internal oscillator). While disabled the
+/// pins fall back to their GPIO configuration, inputs with pull-ups, so the bus stays idle
+pub struct PowerGated {
+    twim: Mutex<Twim>,
+    // tasks waiting for the lock
+    waiting: Cell<usize>,
+    cycles: Cell<u32>,
+}
+
+impl PowerGated {
+    /// Wraps the I2C bus; disables it until it's first locked
+    pub fn new(twim: Twim) -> Self {
+        disable();
+
+        Self {
+            twim: Mutex::new(twim),
+            waiting: Cell::new(0),
+            cycles: Cell::new(0),
+        }
+    }
+
+    /// Waits until the bus is free; then enables it
+    pub async fn lock(&self) -> Powered<'_> {
+        struct Waiting<'a>(&'a Cell<usize>);
+
+        // NOTE also runs if the `lock` future is cancelled
+        impl Drop for Waiting<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() - 1);
+            }
+        }
+
+        self.waiting.set(self.waiting.get() + 1);
+        let waiting = Waiting(&self.waiting);
+        let guard = self.twim.lock().await;
+        drop(waiting);
+
+        if !self.is_enabled() {
+            enable();
+            self.cycles.set(self.cycles.get().wrapping_add(1));
+        }
+
+        Powered { gate: self, guard }
+    }
+
+    /// Returns `true` if the TWIM is currently enabled
+    pub fn is_enabled(&self) -> bool {
+        TWIM0::borrow_unchecked(|twim| twim.enable.read().enable().is_enabled())
+    }
+
+    /// Returns the number of times the TWIM has been enabled by this wrapper
+    pub fn cycles(&self) -> u32 {
+        self.cycles.get()
+    }
+}
+
+/// Exclusive access to a `PowerGated` bus; derefs to the `Twim`
+///
+/// Disables the TWIM when dropped, unless another task is waiting for the bus
+pub struct Powered<'a> {
+    gate: &'a PowerGated,
+    guard: MutexGuard<'a, Twim>,
+}
+
+impl Deref for Powered<'_> {
+    type Target = Twim;
+
+    fn deref(&self) -> &Twim {
+        &self.guard
+    }
+}
+
+impl DerefMut for Powered<'_> {
+    fn deref_mut(&mut self) -> &mut Twim {
+        &mut self.guard
+    }
+}
+
+impl Drop for Powered<'_> {
+    fn drop(&mut self) {
+        // NOTE the transfers are over: they borrow the `Twim` mutably, which outlives them
+        if self.gate.waiting.get() == 0 {
+            disable();
+        }
+    }
+}
+
+/// An error that may have been caused by the I2C bus
+pub trait BusError {
+    /// Returns the I2C error, if that's what this error is
+    fn twim_error(&self) -> Option<&Error>;
+}
+
+impl BusError for Error {
+    fn twim_error(&self) -> Option<&Error> {
+        Some(self)
+    }
+}
+
+// ERRORSRC bits
+const ERRORSRC_OVERRUN: u8 = 1 << 0;
+const ERRORSRC_ANACK: u8 = 1 << 1;
+const ERRORSRC_DNACK: u8 = 1 << 2;
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::ShortWrite(n) => write!(f, "ShortWrite({} bytes written)", n),
+            Error::ShortRead(n) => write!(f, "ShortRead({} bytes read)", n),
+            Error::TooLong(n) => write!(f, "TooLong({} bytes)", n),
+            Error::Src(src) => {
+                f.write_str("Src(")?;
+
+                // e.g. `Src(address NACK | data NACK)`
+                let mut first = true;
+                for (bit, reason) in &[
+                    (ERRORSRC_OVERRUN, "overrun"),
+                    (ERRORSRC_ANACK, "address NACK"),
+                    (ERRORSRC_DNACK, "data NACK"),
+                ] {
+                    if src & bit != 0 {
+                        if !first {
+                            f.write_str(" | ")?;
+                        }
+                        f.write_str(reason)?;
+                        first = false;
+                    }
+                }
+
+                let unknown = src & !(ERRORSRC_OVERRUN | ERRORSRC_ANACK | ERRORSRC_DNACK);
+                if unknown != 0 || first {
+                    if !first {
+                        f.write_str(" | ")?;
+                    }
+                    write!(f, "{:#04x}", unknown)?;
+                }
+
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+impl Error {
+    /// The device did not acknowledge its address, e.g. because it's not present on the bus
+    pub fn is_address_nack(&self) -> bool {
+        match self {
+            Error::Src(src) => src & ERRORSRC_ANACK != 0,
+            _ => false,
+        }
+    }
+
+    /// The device did not acknowledge a data byte
+    pub fn is_data_nack(&self) -> bool {
+        match self {
+            Error::Src(src) => src & ERRORSRC_DNACK != 0,
+            _ => false,
+        }
+    }
+
+    // Accounts for the `done` bytes transferred by the previous chunks of an operation
+    fn after(self, done: usize) -> Self {
+        match self {
+            Error::ShortWrite(n) => Error::ShortWrite(done + n),
+            Error::ShortRead(n) => Error::ShortRead(done + n),
+            e => e,
+        }
+    }
+
+    /// The bus may be wedged; any `ERRORSRC` error except for an address NACK, which only means
+    /// that the device is not present
+    pub fn is_bus_fault(&self) -> bool {
+        match self {
+            Error::Src(src) => src & !ERRORSRC_ANACK != 0,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, ERRORSRC_ANACK, ERRORSRC_DNACK, ERRORSRC_OVERRUN, MAX_TRANSFER};
+    use crate::sensirion;
+
+    // an error halfway through the second chunk of a `read` or `write_chained` counts the bytes
+    // of the first chunk
+    #[test]
+    fn error_in_later_chunk() {
+        match Error::ShortRead(100).after(MAX_TRANSFER) {
+            Error::ShortRead(n) => assert_eq!(n, MAX_TRANSFER + 100),
+            e => panic!("{:?}", e),
+        }
+
+        match Error::ShortWrite(0).after(2 * MAX_TRANSFER) {
+            Error::ShortWrite(n) => assert_eq!(n, 2 * MAX_TRANSFER),
+            e => panic!("{:?}", e),
+        }
+
+        // bus errors don't carry a count
+        match Error::Src(ERRORSRC_DNACK).after(MAX_TRANSFER) {
+            Error::Src(src) => assert_eq!(src, ERRORSRC_DNACK),
+            e => panic!("{:?}", e),
+        }
+    }
+
+    #[test]
+    fn bus_faults() {
+        // the device is not there; the bus is fine
+        assert!(!Error::Src(ERRORSRC_ANACK).is_bus_fault());
+        // the bus may be wedged
+        assert!(Error::Src(ERRORSRC_DNACK).is_bus_fault());
+        assert!(Error::Src(ERRORSRC_OVERRUN).is_bus_fault());
+        assert!(Error::Src(ERRORSRC_ANACK | ERRORSRC_DNACK).is_bus_fault());
+        // not reported by the peripheral
+        assert!(!Error::ShortRead(1).is_bus_fault());
+        assert!(!Error::ShortWrite(1).is_bus_fault());
+        assert!(!Error::TooLong(256).is_bus_fault());
+    }
+
+    #[test]
+    fn recovery() {
+        assert!(super::needs_recovery(&Error::Src(ERRORSRC_DNACK)));
+        assert!(!super::needs_recovery(&Error::Src(ERRORSRC_ANACK)));
+
+        // driver errors that wrap an I2C error
+        let e = sensirion::Error::Twim(Error::Src(ERRORSRC_DNACK));
+        assert!(super::needs_recovery(&e));
+        let e = sensirion::Error::Twim(Error::Src(ERRORSRC_ANACK));
+        assert!(!super::needs_recovery(&e));
+        // the transfer went fine; the data is bad
+        let e = sensirion::Error::Checksum { word: 0 };
+        assert!(!super::needs_recovery(&e));
+    }
+
+    #[test]
+    fn debug() {
+        // ERRORSRC: bit 0 = overrun, bit 1 = address NACK, bit 2 = data NACK
+        assert_eq!(format!("{:?}", Error::Src(0b010)), "Src(address NACK)");
+        assert_eq!(format!("{:?}", Error::Src(0b100)), "Src(data NACK)");
+        assert_eq!(
+            format!("{:?}", Error::Src(0b011)),
+            "Src(overrun | address NACK)"
+        );
+        // bits the hardware doesn't define are printed as they are
+        assert_eq!(
+            format!("{:?}", Error::Src(0b1_0010)),
+            "Src(address NACK | 0x10)"
+        );
+        assert_eq!(format!("{:?}", Error::Src(0)), "Src(0x00)");
+        assert_eq!(
+            format!("{:?}", Error::ShortRead(3)),
+            "ShortRead(3 bytes read)"
+        );
+        assert_eq!(
+            format!("{:?}", Error::ShortWrite(0)),
+            "ShortWrite(0 bytes written)"
+        );
+        assert_eq!(format!("{:?}", Error::TooLong(300)), "TooLong(300 bytes)");
+    }
 }